@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "serde")]
+use crate::error::EnvironmentError;
+
+/// Name of the file containing the public environments, usually checked in
+pub const PUBLIC_ENV_FILE: &str = "http-client.env.json";
+/// Name of the file containing private values such as passwords which override the public ones
+pub const PRIVATE_ENV_FILE: &str = "http-client.private.env.json";
+/// Name of the environment whose variables are shared by all environments of a file
+pub const SHARED_ENV_NAME: &str = "$shared";
+
+/// Variables of an environment which are substituted for their placeholders (`{{name}}`) within
+/// a request
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Environment {
+    pub variables: HashMap<String, String>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Environment::default()
+    }
+
+    /// Returns the value of the variable with the given name if present
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.variables.get(name).map(|value| value.as_str())
+    }
+
+    /// Set the variable `name` to `value`, an existing value is replaced
+    pub fn set<S: Into<String>, T: Into<String>>(&mut self, name: S, value: T) {
+        self.variables.insert(name.into(), value.into());
+    }
+}
+
+impl<S: Into<String>, T: Into<String>> FromIterator<(S, T)> for Environment {
+    fn from_iter<I: IntoIterator<Item = (S, T)>>(iter: I) -> Self {
+        Environment {
+            variables: iter
+                .into_iter()
+                .map(|(name, value)| (name.into(), value.into()))
+                .collect(),
+        }
+    }
+}
+
+/// The environments of the public (`http-client.env.json`) and private
+/// (`http-client.private.env.json`) environment files. Both files contain an object mapping the
+/// name of each environment such as `dev` or `prod` to an object with its variables.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Environments {
+    pub public: HashMap<String, Environment>,
+    pub private: HashMap<String, Environment>,
+}
+
+impl Environments {
+    /// Load the environment files from the given directory, usually the directory of the
+    /// request file. A missing file is treated as a file without environments.
+    #[cfg(feature = "serde")]
+    pub fn load(dir: &std::path::Path) -> Result<Environments, EnvironmentError> {
+        let read = |name: &str| -> Result<Option<String>, EnvironmentError> {
+            let path = dir.join(name);
+            match std::fs::read_to_string(&path) {
+                Ok(content) => Ok(Some(content)),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(err) => Err(EnvironmentError::CouldNotReadFile {
+                    path,
+                    reason: err.to_string(),
+                }),
+            }
+        };
+        Environments::from_json(
+            read(PUBLIC_ENV_FILE)?.as_deref(),
+            read(PRIVATE_ENV_FILE)?.as_deref(),
+        )
+    }
+
+    /// Create the environments from the content of the public and private environment file,
+    /// `None` is treated as a file without environments.
+    #[cfg(feature = "serde")]
+    pub fn from_json(
+        public: Option<&str>,
+        private: Option<&str>,
+    ) -> Result<Environments, EnvironmentError> {
+        Ok(Environments {
+            public: Environments::parse_file(PUBLIC_ENV_FILE, public)?,
+            private: Environments::parse_file(PRIVATE_ENV_FILE, private)?,
+        })
+    }
+
+    // string, number and boolean values are taken as variables, nested objects such as ssl
+    // configurations are ignored
+    #[cfg(feature = "serde")]
+    fn parse_file(
+        file: &str,
+        content: Option<&str>,
+    ) -> Result<HashMap<String, Environment>, EnvironmentError> {
+        let invalid = |reason: String| EnvironmentError::InvalidFile {
+            file: file.to_string(),
+            reason,
+        };
+        let Some(content) = content.filter(|content| !content.trim().is_empty()) else {
+            return Ok(HashMap::new());
+        };
+        let value: serde_json::Value =
+            serde_json::from_str(content).map_err(|err| invalid(err.to_string()))?;
+        let serde_json::Value::Object(environments) = value else {
+            return Err(invalid("the file does not contain an object".to_string()));
+        };
+
+        let mut result: HashMap<String, Environment> = HashMap::new();
+        for (name, variables) in environments {
+            let serde_json::Value::Object(variables) = variables else {
+                return Err(invalid(format!("environment '{}' is not an object", name)));
+            };
+            let environment = variables
+                .into_iter()
+                .filter_map(|(key, value)| match value {
+                    serde_json::Value::String(value) => Some((key, value)),
+                    serde_json::Value::Number(value) => Some((key, value.to_string())),
+                    serde_json::Value::Bool(value) => Some((key, value.to_string())),
+                    _ => None,
+                })
+                .collect::<Environment>();
+            result.insert(name, environment);
+        }
+        Ok(result)
+    }
+
+    /// Returns the names of all environments of both files sorted by name, the shared
+    /// environment `$shared` is not included
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .public
+            .keys()
+            .chain(self.private.keys())
+            .map(|name| name.as_str())
+            .filter(|name| *name != SHARED_ENV_NAME)
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Returns the variables of the environment `name` with the values of both files layered,
+    /// a later layer overrides values of the former ones:
+    /// 1. the public `$shared` environment
+    /// 2. the public environment `name`
+    /// 3. the private `$shared` environment
+    /// 4. the private environment `name`
+    ///
+    /// Returns None if neither file contains the environment.
+    pub fn select(&self, name: &str) -> Option<Environment> {
+        if !self.public.contains_key(name) && !self.private.contains_key(name) {
+            return None;
+        }
+        let mut environment = Environment::new();
+        let layers = [
+            self.public.get(SHARED_ENV_NAME),
+            self.public.get(name),
+            self.private.get(SHARED_ENV_NAME),
+            self.private.get(name),
+        ];
+        for layer in layers.into_iter().flatten() {
+            for (key, value) in layer.variables.iter() {
+                environment.set(key, value);
+            }
+        }
+        Some(environment)
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    const PUBLIC: &str = r#"{
+  "$shared": { "version": "v1" },
+  "dev": { "host": "localhost:8080", "secret": "{{secret}}", "retries": 3 },
+  "prod": { "host": "test.com", "secret": "", "ssl": { "verify": true } }
+}"#;
+    const PRIVATE: &str = r#"{
+  "dev": { "secret": "dev-secret" },
+  "staging": { "host": "staging.test.com" }
+}"#;
+
+    #[test]
+    pub fn select_environment() {
+        let environments = Environments::from_json(Some(PUBLIC), Some(PRIVATE)).unwrap();
+        assert_eq!(environments.names(), vec!["dev", "prod", "staging"]);
+
+        let dev = environments.select("dev").unwrap();
+        assert_eq!(dev.get("secret"), Some("dev-secret"));
+        assert_eq!(dev.get("host"), Some("localhost:8080"));
+        assert_eq!(dev.get("retries"), Some("3"));
+        assert_eq!(dev.get("version"), Some("v1"));
+
+        let prod = environments.select("prod").unwrap();
+        assert_eq!(prod.get("secret"), Some(""));
+        assert_eq!(prod.get("ssl"), None);
+
+        let staging = environments.select("staging").unwrap();
+        assert_eq!(staging.get("host"), Some("staging.test.com"));
+        assert_eq!(staging.get("version"), Some("v1"));
+
+        assert_eq!(environments.select("missing"), None);
+        assert_eq!(
+            environments.select(SHARED_ENV_NAME).unwrap().get("version"),
+            Some("v1")
+        );
+    }
+
+    #[test]
+    pub fn load_environment_files() {
+        let dir = std::env::temp_dir().join("http_rest_file_environments");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(PUBLIC_ENV_FILE), PUBLIC).unwrap();
+        let _ = std::fs::remove_file(dir.join(PRIVATE_ENV_FILE));
+
+        // a missing private file is no error
+        let environments = Environments::load(&dir).unwrap();
+        assert_eq!(
+            environments.select("dev").unwrap().get("secret"),
+            Some("{{secret}}")
+        );
+
+        std::fs::write(dir.join(PRIVATE_ENV_FILE), PRIVATE).unwrap();
+        let environments = Environments::load(&dir).unwrap();
+        assert_eq!(
+            environments.select("dev").unwrap().get("secret"),
+            Some("dev-secret")
+        );
+
+        let empty = Environments::load(&dir.join("missing")).unwrap();
+        assert_eq!(empty, Environments::default());
+
+        assert!(matches!(
+            Environments::from_json(Some("[]"), None),
+            Err(EnvironmentError::InvalidFile { .. })
+        ));
+        assert!(matches!(
+            Environments::from_json(None, Some("{\"dev\": 1}")),
+            Err(EnvironmentError::InvalidFile { .. })
+        ));
+    }
+}
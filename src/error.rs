@@ -6,13 +6,12 @@ use std::path::PathBuf;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::model::PartialRequest;
+use crate::model::{PartialRequest, Request};
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "rspc", derive(Type))]
 #[derive(Error, Debug, PartialEq, Eq, Clone)]
 pub enum ParseError {
-
     #[error("Could not read the file: '{0}'.")]
     CouldNotReadRequestFile(PathBuf),
 
@@ -25,18 +24,41 @@ pub enum ParseError {
     #[error("The HTTP version: '{0}' is not valid. A valid HTTP version requires format: 'HTTP/\\d+.\\d+' or 'HTTP/\\d+'.\nFor example 'HTTP/2.1'. You can also omit the version and only specify the url target of the request or the HTTP method and the url target.")]
     InvalidHttpVersion(String),
 
+    #[error("Invalid value '{value}' for '{setting}', expected the timeout as a whole number.")]
+    InvalidTimeoutValue { setting: String, value: String },
+
+    #[error("Invalid proxy URL: '{0}'. The proxy should be given as URL with scheme and host, for example 'http://proxy:8080'.")]
+    InvalidProxyUrl(String),
+
     #[error("Expected either a prerequest script within '{{% %}}' blocks or a filepath to a pre-request script after matching '<' character.")]
     MissingPreRequestScript,
     #[error("A pre-request script should be ended with '%}}' characters but none were found.")]
     MissingPreRequestScriptClose,
+    // only whitespace or a comment may follow the closing '%}' on the same line
+    #[error("Unexpected '{0}' after the closing '%}}' of a pre-request script, only whitespace or a comment may follow on the same line.")]
+    UnexpectedContentAfterPreRequestScript(String),
 
     #[error("Missing request target line.")]
     MissingRequestTargetLine,
+    #[error("The request line only contains the method '{0}', the request target such as 'https://test.com' is missing.")]
+    MissingRequestTargetAfterMethod(String),
+    #[error("The request line does not contain a method, 'GET' is assumed.")]
+    ImplicitMethod,
     #[error("The request target line containing the url for the request contains too many elements. There should only be a method, the URL and HTTP version. You have additional elements: {0}")]
     TooManyElementsOnRequestLine(String),
 
+    #[error("The lines continuing the request line are indented inconsistently, mixing tabs and spaces. Use the same indentation for all lines of the request target.")]
+    InconsistentRequestLineIndentation,
+
     #[error("Expected header in the form of '<Key>: <Value>'. Found line: {0}")]
     InvalidHeaderField(String),
+    // a line after the headers looks like the body, it is parsed as body instead of failing
+    #[error("Expected an empty line between the headers and the body. Found body line: {0}")]
+    MissingBlankLineBeforeBody(String),
+    #[error("Header name '{0}' is not a valid token, it may not contain whitespace, control or separator characters.")]
+    InvalidHeaderName(String),
+    #[error("Value of header '{0}' contains a carriage return or line feed.")]
+    InvalidHeaderValue(String),
 
     #[error("Missing multipart boundary in 'Content-Type' for 'multipart/form-data'. Using default boundary '{0}' instead.")]
     MissingMultipartHeaderBoundaryDefinition(String),
@@ -47,6 +69,10 @@ pub enum ParseError {
     },
     #[error("Multipart requires a first starting boundary before any content.")]
     MissingMultipartStartingBoundary,
+    #[error("Multipart body contains more than the allowed {0} parts.")]
+    TooManyMultipartParts(usize),
+    #[error("Multipart body is larger than the allowed {0} bytes.")]
+    MultipartBodyTooLarge(usize),
     #[error("Could not parse the headers of the given part of a multipart body. Error during parsing: {error_msg}.")]
     InvalidSingleMultipartHeaders {
         header_parse_err: Box<ParseError>,
@@ -78,13 +104,54 @@ pub enum ParseError {
     InvalidMultipartBoundaryCharacter(String),
     #[error("Content disposition header of a multipart (multipart/formdata) requires a name field, found only {0}")]
     SingleMultipartNameMissing(String),
+    // the name is written without quotes but contains whitespace, quotes or other characters
+    // that are only valid within a quoted value
+    #[error("Name '{0}' of the multipart part contains characters that are only valid if the name is quoted.")]
+    MultipartNameRequiresQuoting(String),
+    #[error("Unknown authentication mode '{0}' given with '@auth', expected one of 'basic', 'digest', 'ntlm', 'negotiate' or 'os-credentials'.")]
+    UnknownAuthMode(String),
+    #[error("Invalid expectation '{0}', expected one of 'status <code>', 'header <name> [<value>]' or 'body-contains <text>'.")]
+    InvalidExpectation(String),
+    #[error("Expected an import in the form of '@import <path>#<request name>', found: '{0}'.")]
+    InvalidImport(String),
+    // '@import <path>' without '#<request name>' fragment
+    #[error("Import '{0}' does not name the request to import, expected '@import <path>#<request name>'.")]
+    MissingImportRequestName(String),
+    // '@ref <name>' references a request which is not part of the file
+    #[error("The request depends on the request '{0}' with '@ref' but there is no request with this name in the file.")]
+    UnknownRequestReference(String),
+    // '@http-version' given after the first request line of the file
+    #[error("The '@http-version' directive is only valid in the file header before the first request line, it is ignored.")]
+    MisplacedHttpVersionDirective,
     // response handler opened with '> {% should be closed again
     #[error("Expected closing '%}}' characters for response handler when opened with '{{%', response handler script is malformed.")]
     MissingResponseHandlerClose,
 
+    #[error("Body started with '<<< {0}' is not ended by a line containing only '{0}'.")]
+    MissingHeredocBodyEnd(String),
+
     #[error("Missing filepath for response after redirecting file using '>>', or '>>!'")]
     MissingResponseOutputPath,
 
+    // warnings of `Request::validate`, the request is syntactically valid but likely not what
+    // was intended
+    #[error(
+        "The request target '{0}' looks like an absolute URL but has no scheme such as 'https://'."
+    )]
+    MissingUrlScheme(String),
+    #[error(
+        "The asterisk request target '*' is only valid for the OPTIONS method, found method '{0}'."
+    )]
+    AsteriskTargetRequiresOptions(String),
+    #[error("The header '{0}' is defined multiple times.")]
+    DuplicateHeader(String),
+    #[error("The multipart boundary '{header}' of the 'Content-Type' header does not match the boundary '{body}' of the body.")]
+    MultipartBoundaryMismatch { header: String, body: String },
+    #[error("The 'Content-Length' header value '{0}' is not a valid length.")]
+    InvalidContentLength(String),
+    #[error("The 'Content-Length' header is {header} but the body has a length of {actual}.")]
+    ContentLengthMismatch { header: usize, actual: usize },
+
     #[error("Could not import collection")]
     ImportCollectionError,
 
@@ -93,6 +160,27 @@ pub enum ParseError {
     Unknown,
 }
 
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum UrlError {
+    #[error("The url contains variables without a value: {}.", .0.join(", "))]
+    UnresolvedVariables(Vec<String>),
+
+    #[error("The relative url '{0}' requires a host, either set the 'Host' header or define the variable 'host'.")]
+    MissingHost(String),
+
+    #[error("Invalid url '{url}': {reason}.")]
+    InvalidUrl { url: String, reason: String },
+}
+
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum EnvironmentError {
+    #[error("Could not read environment file '{path}': {reason}.")]
+    CouldNotReadFile { path: PathBuf, reason: String },
+
+    #[error("Environment file '{file}' is invalid, expected an object of environments with their variables: {reason}.")]
+    InvalidFile { file: String, reason: String },
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct ParseErrorDetails {
     pub error: ParseError,
@@ -144,3 +232,12 @@ pub struct ErrorWithPartial {
     pub partial_request: PartialRequest,
     pub details: Vec<ParseErrorDetails>,
 }
+
+impl ErrorWithPartial {
+    /// Returns the request with everything that could be parsed before or despite the errors,
+    /// for example to show a partially valid request in an editor. Parts that could not be
+    /// parsed are filled with defaults as described for `From<PartialRequest> for Request`.
+    pub fn best_effort_request(&self) -> Request {
+        self.partial_request.clone().into()
+    }
+}
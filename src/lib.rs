@@ -5,11 +5,18 @@
 //! https://github.com/JetBrains/http-request-in-editor-spec
 //! They also have some documentation about their http client here: https://www.jetbrains.com/help/idea/http-client-in-product-code-editor.html#creating-http-request-files
 
+pub mod environment;
 pub mod model;
 pub mod parser;
 mod scanner;
 pub mod serializer;
+pub mod syntax;
+pub use parser::DiagnosticsSink;
+#[cfg(feature = "metrics")]
+pub use parser::ParseMetrics;
 pub use parser::Parser;
+pub use parser::ParserOptions;
 pub use parser::Scanner;
+pub use parser::TokenOptions;
 pub use serializer::Serializer;
 pub mod error;
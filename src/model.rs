@@ -1,11 +1,29 @@
 #[cfg(feature = "rspc")]
 use rspc::Type;
 
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use std::borrow::Cow;
 
-use crate::error::{ErrorWithPartial, ParseError};
+use crate::environment::Environment;
+use crate::error::{ErrorWithPartial, ParseError, ParseErrorDetails, UrlError};
+use crate::scanner::Scanner;
+
+lazy_static::lazy_static! {
+    // variable placeholders such as '{{host}}', escaped braces '\{\{' are never matched as the
+    // backslash separates the two braces
+    static ref VARIABLE: Regex = Regex::new(r"\{\{\s*([^{}\s]+)\s*\}\}").unwrap();
+    // 'client.global.set("name", value)' within a response handler, only literal names match
+    static ref GLOBAL_SET: Regex =
+        Regex::new(r#"client\.global\.set\(\s*(?:"([^"]+)"|'([^']+)')"#).unwrap();
+}
+
+/// Replaces escaped braces `\{\{` and `\}\}` by literal double braces, used once all variables
+/// of a text have been substituted
+pub(crate) fn unescape_braces(text: &str) -> String {
+    text.replace(r"\{\{", "{{").replace(r"\}\}", "}}")
+}
 
 #[allow(dead_code)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -149,6 +167,8 @@ impl<T: std::cmp::PartialEq> PartialEq for WithDefault<T> {
     }
 }
 
+/// Http methods, the methods of RFC 9110 as well as `PATCH` (RFC 5789) and `LINK`/`UNLINK`
+/// (RFC 2068) have their own variant. Any other method is kept as `CUSTOM`.
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -164,6 +184,8 @@ pub enum HttpMethod {
     TRACE,
     OPTIONS,
     CONNECT,
+    LINK,
+    UNLINK,
     CUSTOM(String),
 }
 
@@ -179,6 +201,8 @@ impl ToString for HttpMethod {
             HttpMethod::TRACE => "TRACE",
             HttpMethod::OPTIONS => "OPTIONS",
             HttpMethod::CONNECT => "CONNECT",
+            HttpMethod::LINK => "LINK",
+            HttpMethod::UNLINK => "UNLINK",
             HttpMethod::CUSTOM(string) => string,
         };
         result.to_string()
@@ -195,11 +219,44 @@ impl HttpMethod {
             "DELETE" => HttpMethod::DELETE,
             "HEAD" => HttpMethod::HEAD,
             "OPTIONS" => HttpMethod::OPTIONS,
-            "CONNECT " => HttpMethod::CONNECT,
+            "CONNECT" => HttpMethod::CONNECT,
             "TRACE" => HttpMethod::TRACE,
+            "LINK" => HttpMethod::LINK,
+            "UNLINK" => HttpMethod::UNLINK,
             custom => HttpMethod::CUSTOM(custom.to_string()),
         }
     }
+
+    /// Returns true for methods that are safe, meaning read only, according to RFC 9110:
+    /// `GET`, `HEAD`, `OPTIONS` and `TRACE`. Custom methods are never considered safe.
+    pub fn is_safe(&self) -> bool {
+        matches!(
+            self,
+            HttpMethod::GET | HttpMethod::HEAD | HttpMethod::OPTIONS | HttpMethod::TRACE
+        )
+    }
+
+    /// Returns true for methods that are idempotent according to RFC 9110, which are the safe
+    /// methods as well as `PUT` and `DELETE`. Custom methods are never considered idempotent.
+    pub fn is_idempotent(&self) -> bool {
+        self.is_safe() || matches!(self, HttpMethod::PUT | HttpMethod::DELETE)
+    }
+}
+
+impl From<http::Method> for HttpMethod {
+    fn from(method: http::Method) -> Self {
+        HttpMethod::new(method.as_str())
+    }
+}
+
+impl TryFrom<&HttpMethod> for http::Method {
+    type Error = http::method::InvalidMethod;
+
+    /// Custom methods are converted with `http::Method::from_bytes` which fails if the method
+    /// contains characters not allowed in a http method token
+    fn try_from(method: &HttpMethod) -> Result<Self, Self::Error> {
+        http::Method::from_bytes(method.to_string().as_bytes())
+    }
 }
 
 #[derive(PartialEq, Debug, Clone)]
@@ -209,6 +266,9 @@ pub enum RequestTarget {
     RelativeOrigin { uri: String },
     Absolute { uri: String },
     Asterisk,
+    // 'data:' uri (RFC 2397) which contains the content itself such as
+    // 'data:text/plain;base64,SGVsbG8='
+    Data { uri: String },
     InvalidTarget(String),
     Missing,
 }
@@ -220,9 +280,168 @@ pub enum SettingsEntry {
     NoRedirect,
     NoLog,
     NoCookieJar,
+    ConnectionTimeout(u64),
+    SocketTimeout(u64),
+    NoProxy,
+    Proxy(String),
+    RawBody,
+    Disabled,
+    Chunked,
+    // '@http-version', default version for this and all following requests of the file
+    HttpVersion(HttpVersion),
+    // '@ref <name>', the request depends on the request with the given name
+    Ref(String),
+    // '@auth <mode>' or '@use-os-credentials'
+    Auth(AuthMode),
+    // '@import <path>#<request name>'
+    Import(Import),
+    // '@expect <expectation>'
+    Expect(Expectation),
     NameEntry(String),
 }
 
+/// Expectation about the response of a request given with `@expect`, for example
+/// `@expect status 201`. The expectations are only recorded, asserting them against a response
+/// is left to the runner of the request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "rspc", derive(Type))]
+pub enum Expectation {
+    // 'status <code>', the status code of the response
+    Status(u16),
+    // 'header <name> [<value>]', the response contains the header, optionally with the value
+    Header { name: String, value: Option<String> },
+    // 'body-contains <text>', the body of the response contains the text
+    BodyContains(String),
+}
+
+impl std::str::FromStr for Expectation {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || ParseError::InvalidExpectation(s.to_string());
+        let (kind, rest) = s
+            .split_once(char::is_whitespace)
+            .map_or((s, ""), |(kind, rest)| (kind, rest.trim()));
+        match kind {
+            "status" => match rest.parse::<u16>() {
+                Ok(status) if (100..=599).contains(&status) => Ok(Expectation::Status(status)),
+                _ => Err(invalid()),
+            },
+            "header" if !rest.is_empty() => {
+                let (name, value) = rest
+                    .split_once(char::is_whitespace)
+                    .map_or((rest, None), |(name, value)| (name, Some(value.trim())));
+                Ok(Expectation::Header {
+                    name: name.to_string(),
+                    value: value.map(str::to_string),
+                })
+            }
+            "body-contains" if !rest.is_empty() => Ok(Expectation::BodyContains(rest.to_string())),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl std::fmt::Display for Expectation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Expectation::Status(status) => write!(f, "status {}", status),
+            Expectation::Header { name, value: None } => write!(f, "header {}", name),
+            Expectation::Header {
+                name,
+                value: Some(value),
+            } => write!(f, "header {} {}", name, value),
+            Expectation::BodyContains(text) => write!(f, "body-contains {}", text),
+        }
+    }
+}
+
+/// Request of another file referenced with `@import <path>#<request name>` such as
+/// `@import ./common.http#Auth`. The import is only recorded, resolving the file and combining
+/// the requests is left to the user of the model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "rspc", derive(Type))]
+pub struct Import {
+    pub path: String,
+    pub request_name: String,
+}
+
+impl std::str::FromStr for Import {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.rsplit_once('#') {
+            Some((path, request_name)) => {
+                let (path, request_name) = (path.trim(), request_name.trim());
+                if path.is_empty() || path.contains(char::is_whitespace) {
+                    Err(ParseError::InvalidImport(s.to_string()))
+                } else if request_name.is_empty() {
+                    Err(ParseError::MissingImportRequestName(s.to_string()))
+                } else {
+                    Ok(Import {
+                        path: path.to_string(),
+                        request_name: request_name.to_string(),
+                    })
+                }
+            }
+            None if s.is_empty() || s.contains(char::is_whitespace) => {
+                Err(ParseError::InvalidImport(s.to_string()))
+            }
+            None => Err(ParseError::MissingImportRequestName(s.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for Import {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}#{}", self.path, self.request_name)
+    }
+}
+
+/// Authentication mode of a request given with `@auth <mode>`, only metadata for the runner
+/// of the request to select the credential provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "rspc", derive(Type))]
+pub enum AuthMode {
+    Basic,
+    Digest,
+    Ntlm,
+    Negotiate,
+    // credentials of the logged in user of the operating system, also given with
+    // '@use-os-credentials'
+    OsCredentials,
+}
+
+impl std::str::FromStr for AuthMode {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "basic" => Ok(AuthMode::Basic),
+            "digest" => Ok(AuthMode::Digest),
+            "ntlm" => Ok(AuthMode::Ntlm),
+            "negotiate" => Ok(AuthMode::Negotiate),
+            "os-credentials" => Ok(AuthMode::OsCredentials),
+            _ => Err(ParseError::UnknownAuthMode(s.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for AuthMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mode = match self {
+            AuthMode::Basic => "basic",
+            AuthMode::Digest => "digest",
+            AuthMode::Ntlm => "ntlm",
+            AuthMode::Negotiate => "negotiate",
+            AuthMode::OsCredentials => "os-credentials",
+        };
+        f.write_str(mode)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "rspc", derive(Type))]
@@ -230,6 +449,26 @@ pub struct RequestSettings {
     pub no_redirect: Option<bool>,
     pub no_log: Option<bool>,
     pub no_cookie_jar: Option<bool>,
+    pub connection_timeout: Option<u64>,
+    pub socket_timeout: Option<u64>,
+    pub no_proxy: Option<bool>,
+    pub proxy: Option<String>,
+    // '@raw-body', the body is taken literally until the next request separator, lines starting
+    // with '>' are not treated as response handler or redirect
+    pub raw_body: Option<bool>,
+    // '@disabled', the request is parsed but should be skipped when running requests
+    pub disabled: bool,
+    // '@chunked', the body should be sent with chunked transfer encoding
+    pub chunked: bool,
+    // '@ref <name>', names of the requests of the same file this request depends on and which
+    // have to be run before it, in the order given
+    pub depends_on: Vec<String>,
+    // '@auth <mode>', how the runner of the request should authenticate
+    pub auth: Option<AuthMode>,
+    // '@import <path>#<request name>', requests of other files to include, in the order given
+    pub imports: Vec<Import>,
+    // '@expect <expectation>', expectations about the response, in the order given
+    pub expectations: Vec<Expectation>,
 }
 
 impl Default for RequestSettings {
@@ -238,6 +477,17 @@ impl Default for RequestSettings {
             no_redirect: Some(false),
             no_log: Some(false),
             no_cookie_jar: Some(false),
+            connection_timeout: None,
+            socket_timeout: None,
+            no_proxy: Some(false),
+            proxy: None,
+            raw_body: Some(false),
+            disabled: false,
+            chunked: false,
+            depends_on: vec![],
+            auth: None,
+            imports: vec![],
+            expectations: vec![],
         }
     }
 }
@@ -248,8 +498,25 @@ impl RequestSettings {
             SettingsEntry::NoLog => self.no_log = Some(true),
             SettingsEntry::NoRedirect => self.no_redirect = Some(true),
             SettingsEntry::NoCookieJar => self.no_cookie_jar = Some(true),
+            SettingsEntry::ConnectionTimeout(timeout) => self.connection_timeout = Some(*timeout),
+            SettingsEntry::SocketTimeout(timeout) => self.socket_timeout = Some(*timeout),
+            SettingsEntry::NoProxy => self.no_proxy = Some(true),
+            SettingsEntry::Proxy(proxy) => self.proxy = Some(proxy.clone()),
+            SettingsEntry::RawBody => self.raw_body = Some(true),
+            SettingsEntry::Disabled => self.disabled = true,
+            SettingsEntry::Chunked => self.chunked = true,
             // do nothing with name, is stored directly on the request
             SettingsEntry::NameEntry(_name) => (),
+            // applied by the parser to the request lines without a version
+            SettingsEntry::HttpVersion(_version) => (),
+            SettingsEntry::Ref(name) => {
+                if !self.depends_on.contains(name) {
+                    self.depends_on.push(name.clone());
+                }
+            }
+            SettingsEntry::Auth(mode) => self.auth = Some(*mode),
+            SettingsEntry::Import(import) => self.imports.push(import.clone()),
+            SettingsEntry::Expect(expectation) => self.expectations.push(expectation.clone()),
         }
     }
 
@@ -266,6 +533,39 @@ impl RequestSettings {
         if let Some(true) = self.no_cookie_jar {
             result.push_str("# @no-cookie-jar\n");
         }
+        if let Some(timeout) = self.connection_timeout {
+            result.push_str(&format!("# @connection-timeout {}\n", timeout));
+        }
+        if let Some(timeout) = self.socket_timeout {
+            result.push_str(&format!("# @socket-timeout {}\n", timeout));
+        }
+        if let Some(true) = self.no_proxy {
+            result.push_str("# @no-proxy\n");
+        }
+        if let Some(ref proxy) = self.proxy {
+            result.push_str(&format!("# @proxy {}\n", proxy));
+        }
+        if let Some(true) = self.raw_body {
+            result.push_str("# @raw-body\n");
+        }
+        if self.disabled {
+            result.push_str("# @disabled\n");
+        }
+        if self.chunked {
+            result.push_str("# @chunked\n");
+        }
+        for name in self.depends_on.iter() {
+            result.push_str(&format!("# @ref {}\n", name));
+        }
+        if let Some(mode) = self.auth {
+            result.push_str(&format!("# @auth {}\n", mode));
+        }
+        for import in self.imports.iter() {
+            result.push_str(&format!("# @import {}\n", import));
+        }
+        for expectation in self.expectations.iter() {
+            result.push_str(&format!("# @expect {}\n", expectation));
+        }
         result
     }
 }
@@ -301,6 +601,142 @@ impl DispositionField {
             filename_star: None,
         }
     }
+
+    /// Parse the value of a `Content-Disposition` header such as
+    /// `form-data; name="file"; filename="data.json"`. The disposition type before the first ';'
+    /// is not validated, quotes around values are removed and `filename*` is kept in its encoded
+    /// form. Unknown parameters are ignored.
+    pub fn parse(value: &str) -> Result<DispositionField, ParseError> {
+        let mut field = DispositionField::new("");
+        // skip disposition type
+        for param in split_header_params(value).into_iter().skip(1) {
+            match param.split_once('=') {
+                Some((key, value)) => {
+                    let key = key.trim();
+                    let value = DispositionField::unquote(value.trim());
+                    match key {
+                        "name" => field.name = value,
+                        "filename" => field.filename = Some(value),
+                        "filename*" => field.filename_star = Some(value),
+                        _ => (),
+                    }
+                }
+                None => {
+                    return Err(ParseError::MalformedContentDispositionEntries(
+                        param.to_string(),
+                    ))
+                }
+            }
+        }
+        Ok(field)
+    }
+
+    /// Returns true if the value cannot be written as is within a `Content-Disposition` header
+    /// because it is empty or contains characters other than token characters (RFC 7230), for
+    /// example whitespace, quotes or ';'. Such values have to be quoted.
+    pub fn requires_quoting(value: &str) -> bool {
+        value.is_empty()
+            || !value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+    }
+
+    /// Quote a parameter value of a `Content-Disposition` header, quotes and backslashes within
+    /// the value are escaped with a backslash.
+    pub fn quote(value: &str) -> String {
+        let mut quoted = String::with_capacity(value.len() + 2);
+        quoted.push('"');
+        for c in value.chars() {
+            if c == '"' || c == '\\' {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+        }
+        quoted.push('"');
+        quoted
+    }
+
+    /// Returns the value of the `Content-Disposition` header of a multipart part, for example
+    /// `form-data; name="data"; filename="data.json"`. The name and filename are always quoted.
+    /// `filename*` is an extended value (RFC 8187) which must not be quoted, it is kept in its
+    /// encoded form and characters that are not allowed within it are percent encoded. This is
+    /// the inverse of [`DispositionField::parse`].
+    pub fn to_header_value(&self) -> String {
+        let mut value = format!("form-data; name={}", DispositionField::quote(&self.name));
+        if let Some(ref filename) = self.filename {
+            value.push_str(&format!("; filename={}", DispositionField::quote(filename)));
+        }
+        if let Some(ref filename_star) = self.filename_star {
+            value.push_str(&format!(
+                "; filename*={}",
+                DispositionField::encode_ext_value(filename_star)
+            ));
+        }
+        value
+    }
+
+    // percent encode every character of the value of an extended value
+    // <charset>'[language]'<value> that is not an attr-char (RFC 8187), existing '%XX' sequences
+    // are kept
+    fn encode_ext_value(ext_value: &str) -> String {
+        let value_start = ext_value
+            .match_indices('\'')
+            .nth(1)
+            .map_or(0, |(index, _)| index + 1);
+        let mut encoded = ext_value[..value_start].to_string();
+        for c in ext_value[value_start..].chars() {
+            if c.is_ascii_alphanumeric() || "!#$&+-.^_`|~%".contains(c) {
+                encoded.push(c);
+            } else {
+                let mut buf = [0; 4];
+                for byte in c.encode_utf8(&mut buf).bytes() {
+                    encoded.push_str(&format!("%{:02X}", byte));
+                }
+            }
+        }
+        encoded
+    }
+
+    // remove surrounding quotes and unescape quotes and backslashes escaped by a backslash
+    fn unquote(value: &str) -> String {
+        if value.len() < 2 || !value.starts_with('"') || !value.ends_with('"') {
+            return value.to_string();
+        }
+        let mut unquoted = String::with_capacity(value.len());
+        let mut chars = value[1..(value.len() - 1)].chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                // other backslashes are kept such as within 'C:\dir\a.txt'
+                '\\' if matches!(chars.peek(), Some('"' | '\\')) => unquoted.extend(chars.next()),
+                _ => unquoted.push(c),
+            }
+        }
+        unquoted
+    }
+
+    /// Returns the filename of the disposition, `filename*` (RFC 5987) is preferred over
+    /// `filename` if it can be decoded. Only the charsets `UTF-8` and `ISO-8859-1` are supported.
+    pub fn decoded_filename(&self) -> Option<String> {
+        self.filename_star
+            .as_ref()
+            .and_then(|filename_star| DispositionField::decode_ext_value(filename_star))
+            .or_else(|| self.filename.clone())
+    }
+
+    // decode an extended value of the form <charset>'[language]'<percent encoded value>
+    fn decode_ext_value(value: &str) -> Option<String> {
+        let mut parts = value.splitn(3, '\'');
+        let (charset, _language, encoded) = (parts.next()?, parts.next()?, parts.next()?);
+        let bytes = percent_decode(encoded)?;
+
+        if charset.eq_ignore_ascii_case("UTF-8") {
+            String::from_utf8(bytes).ok()
+        } else if charset.eq_ignore_ascii_case("ISO-8859-1") {
+            Some(bytes.iter().map(|&byte| byte as char).collect())
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(PartialEq, Debug, Clone)]
@@ -312,6 +748,78 @@ pub struct Multipart {
     pub headers: Vec<Header>,
 }
 
+impl Multipart {
+    /// Create a part with the given text as content and without additional headers
+    pub fn text<S, T>(name: S, content: T) -> Self
+    where
+        S: Into<String>,
+        T: Into<String>,
+    {
+        Multipart {
+            data: DataSource::Raw(content.into()),
+            disposition: DispositionField::new(name),
+            headers: vec![],
+        }
+    }
+
+    /// Create a part with content read from the file at `path` (`< path`), the filename of the
+    /// disposition is the last component of the path
+    pub fn file<S, T>(name: S, path: T) -> Self
+    where
+        S: Into<String>,
+        T: Into<String>,
+    {
+        let path: String = path.into();
+        let filename = std::path::Path::new(&path)
+            .file_name()
+            .map(|filename| filename.to_string_lossy().to_string());
+        Multipart {
+            disposition: DispositionField::new_with_filename(name, filename),
+            data: DataSource::FromFilepath(path),
+            headers: vec![],
+        }
+    }
+
+    /// Returns the media type of the `Content-Type` header of the part without parameters such
+    /// as the charset. If the part has no such header `text/plain` is returned as default.
+    pub fn content_type(&self) -> &str {
+        self.content_type_header()
+            .and_then(|value| value.split(';').next())
+            .map(str::trim)
+            .unwrap_or("text/plain")
+    }
+
+    /// Returns the `charset` parameter of the `Content-Type` header of the part if present
+    pub fn charset(&self) -> Option<String> {
+        split_header_params(self.content_type_header()?)
+            .into_iter()
+            .skip(1)
+            .filter_map(|param| param.split_once('='))
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case("charset"))
+            .map(|(_, value)| value.trim().trim_matches('"').to_string())
+    }
+
+    fn content_type_header(&self) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|header| header.key.eq_ignore_ascii_case("Content-Type"))
+            .map(|header| header.value.as_str())
+    }
+
+    /// Same as `Multipart::file` but with a `Content-Type` header for the part
+    pub fn file_with_type<S, T, U>(name: S, path: T, content_type: U) -> Self
+    where
+        S: Into<String>,
+        T: Into<String>,
+        U: Into<String>,
+    {
+        Multipart {
+            headers: vec![Header::new("Content-Type", content_type)],
+            ..Multipart::file(name, path)
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "rspc", derive(Type))]
@@ -320,6 +828,67 @@ pub enum DataSource<T> {
     FromFilepath(String),
 }
 
+impl<T> DataSource<T> {
+    /// Returns true if the data is read from a file (`< path`)
+    pub fn is_file(&self) -> bool {
+        matches!(self, DataSource::FromFilepath(_))
+    }
+
+    /// Returns true if the data is given directly
+    pub fn is_raw(&self) -> bool {
+        matches!(self, DataSource::Raw(_))
+    }
+
+    /// Returns the path of the file the data is read from, if any
+    pub fn file_path(&self) -> Option<&std::path::Path> {
+        match self {
+            DataSource::FromFilepath(path) => Some(std::path::Path::new(path)),
+            DataSource::Raw(_) => None,
+        }
+    }
+
+    /// Returns the path of the file the data is read from resolved against `base_dir` if it is
+    /// relative. Request files are shared between platforms so both '/' and '\\' are taken as
+    /// separator and replaced by the separator of the current platform.
+    pub fn resolve_path(&self, base_dir: &std::path::Path) -> Option<std::path::PathBuf> {
+        match self {
+            DataSource::FromFilepath(path) => Some(resolve_file_path(path, base_dir)),
+            DataSource::Raw(_) => None,
+        }
+    }
+}
+
+impl DataSource<String> {
+    /// Returns the data if it is given directly and not read from a file
+    pub fn as_raw(&self) -> Option<&str> {
+        match self {
+            DataSource::Raw(data) => Some(data),
+            DataSource::FromFilepath(_) => None,
+        }
+    }
+
+    /// Returns the raw data as bytes or reads the content of the file, relative paths are
+    /// resolved against `base_dir`, see `DataSource::resolve_path`.
+    pub fn as_bytes(&self, base_dir: &std::path::Path) -> std::io::Result<Vec<u8>> {
+        match self {
+            DataSource::Raw(str) => Ok(str.as_bytes().to_vec()),
+            DataSource::FromFilepath(path) => std::fs::read(resolve_file_path(path, base_dir)),
+        }
+    }
+}
+
+// paths such as 'C:\data\input.json' are absolute on windows only, on other platforms they are
+// resolved as relative path
+fn resolve_file_path(path: &str, base_dir: &std::path::Path) -> std::path::PathBuf {
+    let normalized = path.replace(['/', '\\'], std::path::MAIN_SEPARATOR_STR);
+    let path = std::path::Path::new(&normalized);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base_dir.join(path)
+    }
+}
+
 impl ToString for DataSource<String> {
     fn to_string(&self) -> String {
         match self {
@@ -350,12 +919,17 @@ impl UrlEncodedParam {
     }
 }
 
+/// Body of a request, a request without body uses `None`. A request with a content type but
+/// without any content, such as a `Content-Type: application/json` header followed by nothing,
+/// uses `Empty`. Every other body contains the parsed content, possibly an empty string.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "rspc", derive(Type))]
 pub enum RequestBody {
     None,
 
+    Empty,
+
     Multipart {
         boundary: String,
         parts: Vec<Multipart>,
@@ -379,75 +953,523 @@ impl RequestBody {
         }
         true
     }
-}
 
-impl ToString for RequestBody {
-    fn to_string(&self) -> String {
+    /// Returns the parts of a multipart body, None for any other body
+    pub fn multipart_parts(&self) -> Option<&[Multipart]> {
         match self {
-            RequestBody::None => "".to_string(),
-            RequestBody::UrlEncoded { url_encoded_params } => {
-                let mut serializer = url::form_urlencoded::Serializer::new(String::new());
-                url_encoded_params.iter().for_each(|param| {
-                    serializer.append_pair(&param.key, &param.value);
-                });
-                serializer.finish()
+            RequestBody::Multipart { parts, .. } => Some(parts),
+            _ => None,
+        }
+    }
+
+    /// Returns the name given in the `Content-Disposition` of each part of a multipart body in
+    /// order, other bodies have no parts and return an empty list
+    pub fn part_names(&self) -> Vec<&str> {
+        self.multipart_parts().map_or(Vec::new(), |parts| {
+            parts
+                .iter()
+                .map(|part| part.disposition.name.as_str())
+                .collect()
+        })
+    }
+
+    /// Guess the content type of the body. Multipart bodies are `multipart/form-data` including
+    /// their boundary and url encoded bodies `application/x-www-form-urlencoded`. Raw bodies
+    /// starting with '{' or '[' are taken as json, bodies starting with '<' as xml and other
+    /// text as `text/plain`. For files the extension is used, unknown extensions result in
+    /// `application/octet-stream`. Returns None if there is no body or it is empty.
+    pub fn guess_content_type(&self) -> Option<String> {
+        let content_type = match self {
+            RequestBody::None | RequestBody::Empty => return None,
+            RequestBody::Multipart { boundary, .. } => {
+                return Some(format!("multipart/form-data; boundary={}", boundary))
             }
-            RequestBody::Multipart { boundary, parts } => {
-                let mut multipart_res = String::new();
+            RequestBody::UrlEncoded { .. } => "application/x-www-form-urlencoded",
+            RequestBody::Raw {
+                data: DataSource::Raw(data),
+            } => match data.trim_start().chars().next() {
+                Some('{') | Some('[') => "application/json",
+                Some('<') => "application/xml",
+                _ => "text/plain",
+            },
+            RequestBody::Raw {
+                data: DataSource::FromFilepath(path),
+            } => {
+                let extension = std::path::Path::new(path)
+                    .extension()
+                    .map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+                match extension.as_deref() {
+                    Some("json") => "application/json",
+                    Some("xml") => "application/xml",
+                    Some("html") | Some("htm") => "text/html",
+                    Some("txt") => "text/plain",
+                    Some("csv") => "text/csv",
+                    Some("graphql") | Some("gql") => "application/graphql",
+                    _ => "application/octet-stream",
+                }
+            }
+        };
+        Some(content_type.to_string())
+    }
 
-                // TODO
-                for part in parts.iter() {
-                    multipart_res.push_str(&format!("--{}\n", boundary));
-                    multipart_res.push_str(&format!(
-                        "Content-Disposition: form-data; name=\"{}\"",
-                        part.disposition.name
-                    ));
+    /// Returns the body with `//` line and `/* */` block comments of a JSONC body removed so it
+    /// is valid json that can be sent, the body itself is kept for display. Comment markers
+    /// within strings such as `"https://test.com"` are kept, lines containing only a comment
+    /// are removed. The body is returned unchanged if it is not a raw body.
+    pub fn strip_json_comments(&self) -> RequestBody {
+        match self {
+            RequestBody::Raw {
+                data: DataSource::Raw(data),
+            } => RequestBody::Raw {
+                data: DataSource::Raw(strip_json_comments(data)),
+            },
+            _ => self.clone(),
+        }
+    }
 
-                    if let Some(ref filename) = part.disposition.filename {
-                        multipart_res.push_str(&format!("; filename=\"{}\"", filename));
-                    }
+    /// Reindent a raw xml body such as the body of a SOAP request, each element is put on its
+    /// own line indented by `indent` spaces per level. Elements containing only text are kept on
+    /// one line. Text is not modified so variable placeholders such as `{{id}}` are kept as is.
+    /// The body is returned unchanged if it is not a raw body or cannot be parsed as xml.
+    pub fn format_xml(&self, indent: usize) -> RequestBody {
+        match self {
+            RequestBody::Raw {
+                data: DataSource::Raw(data),
+            } => match reindent_xml(data, indent) {
+                Some(formatted) => RequestBody::Raw {
+                    data: DataSource::Raw(formatted),
+                },
+                None => self.clone(),
+            },
+            _ => self.clone(),
+        }
+    }
 
-                    if let Some(ref filename_star) = part.disposition.filename_star {
-                        multipart_res.push_str(&format!("; filename*=\"{}\"", filename_star));
-                    }
-                    multipart_res.push('\n');
+    /// Returns the body as bytes as it would be sent. Url encoded parameters are encoded and
+    /// multipart bodies are framed with their boundary, each part starting with `--<boundary>`
+    /// followed by its headers, an empty line and its data, the last part is closed with
+    /// `--<boundary>--`.
+    /// Content referenced by a file path (`< path`) is read relative to `base_dir`.
+    pub fn as_bytes(&self, base_dir: &std::path::Path) -> std::io::Result<Vec<u8>> {
+        match self {
+            RequestBody::None | RequestBody::Empty => Ok(Vec::new()),
+            RequestBody::UrlEncoded { .. } => Ok(self.to_string().into_bytes()),
+            RequestBody::Raw { data } => data.as_bytes(base_dir),
+            RequestBody::Multipart { boundary, parts } => {
+                let mut bytes: Vec<u8> = Vec::new();
+                for part in parts.iter() {
+                    bytes.extend(format!("--{}\r\n", boundary).as_bytes());
+                    bytes.extend(
+                        format!(
+                            "Content-Disposition: {}\r\n",
+                            part.disposition.to_header_value()
+                        )
+                        .as_bytes(),
+                    );
+                    let data = part.data.as_bytes(base_dir)?;
                     for header in part.headers.iter() {
-                        multipart_res.push_str(&format!("{}: {}", header.key, header.value));
-                        multipart_res.push('\n');
+                        // the length of the part may have changed since the header was written
+                        if header.key.eq_ignore_ascii_case("Content-Length") {
+                            bytes.extend(format!("{}: {}", header.key, data.len()).as_bytes());
+                        } else {
+                            bytes.extend(header.to_string().as_bytes());
+                        }
+                        bytes.extend(b"\r\n");
                     }
-                    multipart_res.push('\n');
-                    let content = match part.data {
-                        DataSource::Raw(ref str) => str.to_string(),
-                        DataSource::FromFilepath(ref path) => format!("< {}", path),
-                    };
-                    multipart_res.push_str(&content);
-                    multipart_res.push('\n');
+                    bytes.extend(b"\r\n");
+                    bytes.extend(data);
+                    bytes.extend(b"\r\n");
                 }
-                multipart_res.push_str(&format!("--{}--", boundary));
-                multipart_res
+                bytes.extend(format!("--{}--", boundary).as_bytes());
+                Ok(bytes)
             }
-            RequestBody::Raw { data } => data.to_string(),
         }
     }
 }
 
-impl RequestTarget {
-    pub fn is_missing(&self) -> bool {
-        matches!(self, RequestTarget::Missing)
+fn has_file_scheme(value: &str) -> bool {
+    value
+        .get(..5)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("file:"))
+}
+
+/// Returns the boundary parameter of a multipart content type such as
+/// `multipart/form-data; boundary="a;b"`. The boundary is either given with or without
+/// quotes, a quoted boundary may contain reserved characters such as ';'.
+pub(crate) fn multipart_boundary_param(content_type: &str) -> Option<String> {
+    split_header_params(content_type)
+        .into_iter()
+        .skip(1)
+        .filter_map(|param| param.split_once('='))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case("boundary"))
+        .map(|(_, value)| {
+            let value = value.trim();
+            if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+                value[1..(value.len() - 1)].to_string()
+            } else {
+                value.to_string()
+            }
+        })
+}
+
+/// Split a header value such as `multipart/form-data; boundary="a;b"` at every ';' that is
+/// not within double quotes. Within quotes a backslash escapes the next character.
+pub(crate) fn split_header_params(value: &str) -> Vec<&str> {
+    let mut params: Vec<&str> = Vec::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut start = 0;
+    for (index, c) in value.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => {
+                params.push(&value[start..index]);
+                start = index + 1;
+            }
+            _ => (),
+        }
     }
+    params.push(&value[start..]);
+    params
+}
 
-    pub fn parse(value: &str) -> Result<RequestTarget, ParseError> {
-        if value.is_empty() {
-            return Ok(RequestTarget::Missing);
+// joins `relative` onto `base` with the url crate. The url crate encodes '{' and '}' and does not
+// accept them within hosts, so variables such as '{{host}}' are replaced with placeholders while
+// joining and restored afterwards. Returns None if the base is not a valid url.
+fn join_url(base: &str, relative: &str) -> Option<String> {
+    let mut variables: Vec<String> = Vec::new();
+    let base = replace_variables(base, &mut variables);
+    let relative = replace_variables(relative, &mut variables);
+    let mut joined = url::Url::parse(&base)
+        .ok()?
+        .join(&relative)
+        .ok()?
+        .to_string();
+    // restored in reverse so 'var-1' does not replace the start of 'var-10'
+    for (index, variable) in variables.iter().enumerate().rev() {
+        joined = joined.replace(&variable_placeholder(index), variable);
+    }
+    Some(joined)
+}
+
+// placeholders are lowercase as the url crate lowercases hosts
+fn variable_placeholder(index: usize) -> String {
+    format!("http-rest-file-var-{}", index)
+}
+
+fn replace_variables(value: &str, variables: &mut Vec<String>) -> String {
+    let mut result = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        let end = start + end + 2;
+        result.push_str(&rest[..start]);
+        result.push_str(&variable_placeholder(variables.len()));
+        variables.push(rest[start..end].to_string());
+        rest = &rest[end..];
+    }
+    result.push_str(rest);
+    result
+}
+// decode '%XX' sequences, returns None if a sequence is not followed by two hex digits
+fn percent_decode(encoded: &str) -> Option<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(encoded.len());
+    let mut iter = encoded.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            let hex = std::str::from_utf8(&hex).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            bytes.push(byte);
         }
+    }
+    Some(bytes)
+}
 
-        if value == "*" {
-            return Ok(RequestTarget::Asterisk);
+// decode standard base64 with optional padding, whitespace is ignored
+fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(encoded.len() * 3 / 4);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for byte in encoded.bytes().filter(|byte| !byte.is_ascii_whitespace()) {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
         }
-        match value.parse::<http::Uri>() {
-            Ok(uri) => {
-                // if we have the authority (host:port) then it is an absolute url
-                if let Some(_authority) = uri.authority() {
+    }
+    Some(bytes)
+}
+
+// remove comments outside of strings, newlines within block comments are kept so the lines of
+// the result correspond to the lines of the input
+fn strip_json_comments(json: &str) -> String {
+    let mut stripped = String::with_capacity(json.len());
+    let mut chars = json.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_string = !in_string;
+                stripped.push(c);
+            }
+            '\\' if in_string => {
+                stripped.push(c);
+                stripped.extend(chars.next());
+            }
+            '/' if !in_string && chars.peek() == Some(&'/') => {
+                while chars.peek().is_some_and(|&next| next != '\n') {
+                    chars.next();
+                }
+            }
+            '/' if !in_string && chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                for next in chars.by_ref() {
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    if next == '\n' {
+                        stripped.push(next);
+                    }
+                    prev = next;
+                }
+            }
+            _ => stripped.push(c),
+        }
+    }
+
+    json.lines()
+        .zip(stripped.lines())
+        .filter(|(line, stripped)| line.trim().is_empty() || !stripped.trim().is_empty())
+        .map(|(_, stripped)| stripped.trim_end())
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
+
+#[derive(Debug, PartialEq)]
+enum XmlToken<'a> {
+    // start tag with the element name
+    Start(&'a str, &'a str),
+    End(&'a str, &'a str),
+    // self closing tags, comments, declarations, processing instructions and CDATA sections
+    Other(&'a str),
+    Text(&'a str),
+}
+
+// split xml into tags and text, returns None if a tag is not closed
+fn tokenize_xml(xml: &str) -> Option<Vec<XmlToken<'_>>> {
+    let mut tokens: Vec<XmlToken> = Vec::new();
+    let mut rest = xml;
+    while !rest.is_empty() {
+        if !rest.starts_with('<') {
+            let end = rest.find('<').unwrap_or(rest.len());
+            tokens.push(XmlToken::Text(&rest[..end]));
+            rest = &rest[end..];
+            continue;
+        }
+        let end = if rest.starts_with("<!--") {
+            rest.find("-->")? + 3
+        } else if rest.starts_with("<![CDATA[") {
+            rest.find("]]>")? + 3
+        } else {
+            // '>' within quoted attribute values does not close the tag
+            let mut quote: Option<char> = None;
+            rest.char_indices()
+                .find(|&(_, c)| match quote {
+                    Some(q) if c == q => {
+                        quote = None;
+                        false
+                    }
+                    Some(_) => false,
+                    None if c == '"' || c == '\'' => {
+                        quote = Some(c);
+                        false
+                    }
+                    None => c == '>',
+                })?
+                .0
+                + 1
+        };
+        let tag = &rest[..end];
+        let token = if tag.starts_with("</") {
+            XmlToken::End(tag, xml_tag_name(tag))
+        } else if tag.starts_with("<?") || tag.starts_with("<!") || tag.ends_with("/>") {
+            XmlToken::Other(tag)
+        } else {
+            XmlToken::Start(tag, xml_tag_name(tag))
+        };
+        tokens.push(token);
+        rest = &rest[end..];
+    }
+    Some(tokens)
+}
+
+fn xml_tag_name(tag: &str) -> &str {
+    tag.trim_start_matches(['<', '/'])
+        .trim_end_matches('>')
+        .split(|c: char| c.is_whitespace() || c == '/')
+        .next()
+        .unwrap_or_default()
+}
+
+fn reindent_xml(xml: &str, indent: usize) -> Option<String> {
+    let tokens = tokenize_xml(xml.trim())?
+        .into_iter()
+        .filter(|token| !matches!(token, XmlToken::Text(text) if text.trim().is_empty()))
+        .collect::<Vec<XmlToken>>();
+    // at least one element is required, a declaration or comment alone is no xml document
+    let has_root = tokens.iter().any(|token| match token {
+        XmlToken::Start(..) => true,
+        XmlToken::Other(tag) => !tag.starts_with("<?") && !tag.starts_with("<!"),
+        _ => false,
+    });
+    if !has_root {
+        return None;
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut open: Vec<&str> = Vec::new();
+    let mut index = 0;
+    while index < tokens.len() {
+        let prefix = " ".repeat(indent * open.len());
+        match (&tokens[index], tokens.get(index + 1), tokens.get(index + 2)) {
+            // elements with only text or without any content are kept on a single line
+            (
+                XmlToken::Start(start, name),
+                Some(XmlToken::Text(text)),
+                Some(XmlToken::End(end, end_name)),
+            ) if name == end_name => {
+                lines.push(format!("{}{}{}{}", prefix, start, text.trim(), end));
+                index += 3;
+            }
+            (XmlToken::Start(start, name), Some(XmlToken::End(end, end_name)), _)
+                if name == end_name =>
+            {
+                lines.push(format!("{}{}{}", prefix, start, end));
+                index += 2;
+            }
+            (XmlToken::Start(start, name), _, _) => {
+                lines.push(format!("{}{}", prefix, start));
+                open.push(name);
+                index += 1;
+            }
+            (XmlToken::End(end, name), _, _) => {
+                if open.pop()? != *name {
+                    return None;
+                }
+                lines.push(format!("{}{}", " ".repeat(indent * open.len()), end));
+                index += 1;
+            }
+            (XmlToken::Text(_), _, _) if open.is_empty() => return None,
+            (XmlToken::Text(text), _, _) => {
+                lines.push(format!("{}{}", prefix, text.trim()));
+                index += 1;
+            }
+            (XmlToken::Other(tag), _, _) => {
+                lines.push(format!("{}{}", prefix, tag));
+                index += 1;
+            }
+        }
+    }
+    if !open.is_empty() {
+        return None;
+    }
+    Some(lines.join("\n"))
+}
+
+impl ToString for RequestBody {
+    fn to_string(&self) -> String {
+        match self {
+            RequestBody::None | RequestBody::Empty => "".to_string(),
+            RequestBody::UrlEncoded { url_encoded_params } => {
+                let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+                url_encoded_params.iter().for_each(|param| {
+                    serializer.append_pair(&param.key, &param.value);
+                });
+                serializer.finish()
+            }
+            RequestBody::Multipart { boundary, parts } => {
+                let mut multipart_res = String::new();
+
+                // TODO
+                for part in parts.iter() {
+                    multipart_res.push_str(&format!("--{}\n", boundary));
+                    multipart_res.push_str(&format!(
+                        "Content-Disposition: {}\n",
+                        part.disposition.to_header_value()
+                    ));
+                    for header in part.headers.iter() {
+                        multipart_res.push_str(&format!("{}: {}", header.key, header.value));
+                        multipart_res.push('\n');
+                    }
+                    multipart_res.push('\n');
+                    let content = match part.data {
+                        DataSource::Raw(ref str) => str.to_string(),
+                        DataSource::FromFilepath(ref path) => format!("< {}", path),
+                    };
+                    multipart_res.push_str(&content);
+                    multipart_res.push('\n');
+                }
+                multipart_res.push_str(&format!("--{}--", boundary));
+                multipart_res
+            }
+            RequestBody::Raw { data } => data.to_string(),
+        }
+    }
+}
+
+impl RequestTarget {
+    pub fn is_missing(&self) -> bool {
+        matches!(self, RequestTarget::Missing)
+    }
+
+    pub fn parse(value: &str) -> Result<RequestTarget, ParseError> {
+        if value.is_empty() {
+            return Ok(RequestTarget::Missing);
+        }
+
+        if value == "*" {
+            return Ok(RequestTarget::Asterisk);
+        }
+
+        // schemes are case insensitive
+        let mut scanner = Scanner::new(value);
+        // would otherwise be taken as host 'data' with an invalid port
+        if scanner.match_str_forward_ci("data:") {
+            return Ok(RequestTarget::Data {
+                uri: value.to_string(),
+            });
+        }
+
+        // 'file:///path' has an empty authority which cannot be parsed by http::Uri
+        if scanner.match_str_forward_ci("file:") {
+            return Ok(RequestTarget::Absolute {
+                uri: value.to_string(),
+            });
+        }
+        match value.parse::<http::Uri>() {
+            Ok(uri) => {
+                // if we have the authority (host:port) then it is an absolute url
+                if let Some(_authority) = uri.authority() {
                     Ok(RequestTarget::Absolute {
                         uri: value.to_string(),
                     })
@@ -478,13 +1500,203 @@ impl RequestTarget {
     pub fn has_scheme(&self) -> bool {
         match self {
             RequestTarget::Asterisk => false,
+            RequestTarget::Absolute { uri } if has_file_scheme(uri) => true,
             RequestTarget::Absolute { uri, .. } | RequestTarget::RelativeOrigin { uri, .. } => uri
                 .parse::<http::Uri>()
                 .map_or(false, |uri| uri.scheme().is_some()),
+            RequestTarget::Data { .. } => true,
             RequestTarget::InvalidTarget(_) => false,
             RequestTarget::Missing => false,
         }
     }
+
+    /// Returns the media type of a `data:` target without the `;base64` flag, for
+    /// `data:text/plain;charset=utf-8;base64,SGVsbG8=` this is `text/plain;charset=utf-8`. If
+    /// the media type is omitted the default `text/plain;charset=US-ASCII` is returned.
+    pub fn data_media_type(&self) -> Option<&str> {
+        let (header, _) = self.data_parts()?;
+        let media_type = header.strip_suffix(";base64").unwrap_or(header);
+        if media_type.is_empty() {
+            return Some("text/plain;charset=US-ASCII");
+        }
+        Some(media_type)
+    }
+
+    /// Returns the decoded payload of a `data:` target, for `data:text/plain;base64,SGVsbG8=`
+    /// these are the bytes of `Hello`. The payload is either base64 or percent encoded. Returns
+    /// None for other targets or if the payload cannot be decoded.
+    pub fn data_payload(&self) -> Option<Vec<u8>> {
+        let (header, payload) = self.data_parts()?;
+        if header.ends_with(";base64") {
+            decode_base64(payload)
+        } else {
+            percent_decode(payload)
+        }
+    }
+
+    /// Converts a `file:` target into a local path, `file:///tmp/data.json` becomes
+    /// `/tmp/data.json` and the windows form `file:///C:/data/test.json` becomes
+    /// `C:/data/test.json`. The host `localhost` is ignored, other hosts result in a UNC path
+    /// such as `//server/share/test.json`. Percent encoded characters are decoded. Returns None
+    /// for other targets.
+    pub fn as_file_path(&self) -> Option<std::path::PathBuf> {
+        let RequestTarget::Absolute { uri } = self else {
+            return None;
+        };
+        if !has_file_scheme(uri) {
+            return None;
+        }
+        let rest = &uri[5..];
+        let path = match rest.strip_prefix("//") {
+            Some(rest) => {
+                let host_end = rest.find('/').unwrap_or(rest.len());
+                match &rest[..host_end] {
+                    host if host.is_empty() || host.eq_ignore_ascii_case("localhost") => {
+                        rest[host_end..].to_string()
+                    }
+                    _ => format!("//{}", rest),
+                }
+            }
+            None => rest.to_string(),
+        };
+        let path = String::from_utf8(percent_decode(&path)?).ok()?;
+
+        // windows drive letters are preceded by a slash: '/C:/data'
+        let bytes = path.as_bytes();
+        if bytes.len() >= 3
+            && bytes[0] == b'/'
+            && bytes[1].is_ascii_alphabetic()
+            && bytes[2] == b':'
+        {
+            return Some(std::path::PathBuf::from(&path[1..]));
+        }
+        Some(std::path::PathBuf::from(path))
+    }
+
+    // header with media type and parameters and the payload of a data uri
+    fn data_parts(&self) -> Option<(&str, &str)> {
+        match self {
+            RequestTarget::Data { uri } => uri[5..].split_once(','),
+            _ => None,
+        }
+    }
+
+    // parse an absolute target, targets without scheme are parsed with the default scheme http
+    fn parse_absolute_uri(&self) -> Option<http::Uri> {
+        match self {
+            RequestTarget::Absolute { uri } => uri
+                .parse::<http::Uri>()
+                .or_else(|_| format!("http://{}", uri).parse::<http::Uri>())
+                .ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the host of an absolute target, for `https://test.com:8080/get` this is
+    /// `test.com`. Relative origins and other targets have no host.
+    pub fn host(&self) -> Option<&str> {
+        let RequestTarget::Absolute { uri: value } = self else {
+            return None;
+        };
+        let uri = self.parse_absolute_uri()?;
+        let authority = uri.authority()?;
+        let host = authority.host();
+        // the authority directly follows the scheme, the host may be preceded by user info
+        let start = value.find("://").map_or(0, |index| index + 3);
+        let end = (start + authority.as_str().len()).min(value.len());
+        let offset = value[start..end].find(host)?;
+        Some(&value[(start + offset)..(start + offset + host.len())])
+    }
+
+    // returns the part of the target following the authority for absolute targets or the whole
+    // relative target
+    fn path_and_query(&self) -> &str {
+        match self {
+            RequestTarget::Absolute { uri } => {
+                let start = uri.find("://").map_or(0, |index| index + 3);
+                let rest = &uri[start..];
+                let authority_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+                &rest[authority_end..]
+            }
+            RequestTarget::RelativeOrigin { uri } => uri,
+            RequestTarget::Asterisk => "*",
+            RequestTarget::Data { .. }
+            | RequestTarget::InvalidTarget(_)
+            | RequestTarget::Missing => "",
+        }
+    }
+
+    /// Resolves `relative` against the target the same way a browser resolves links, see
+    /// RFC 3986. For the target `https://api.test.com/v1/users` the reference `/v2/items`
+    /// results in `https://api.test.com/v2/items`, `items?page=2` in
+    /// `https://api.test.com/v1/items?page=2` and `../v2` in `https://api.test.com/v2`. A
+    /// reference with a scheme such as `https://other.com` replaces the target. Relative origin
+    /// targets are joined the same way but stay relative, for other targets such as `*` the
+    /// reference is returned as new target.
+    pub fn join(&self, relative: &str) -> RequestTarget {
+        // the url crate only joins onto absolute urls, targets without scheme are joined with a
+        // default origin which is removed again afterwards unless the reference replaces it
+        let (base, default_origin) = match self {
+            RequestTarget::Absolute { uri } if has_file_scheme(uri) || uri.contains("://") => {
+                (uri.to_string(), None)
+            }
+            RequestTarget::Absolute { uri } => (format!("http://{}", uri), Some("http://")),
+            RequestTarget::RelativeOrigin { uri } => {
+                (format!("http://localhost{}", uri), Some("http://localhost"))
+            }
+            _ => return RequestTarget::from(relative),
+        };
+        let Some(joined) = join_url(&base, relative) else {
+            return RequestTarget::from(relative);
+        };
+        let replaces_origin = relative.starts_with("//") || url::Url::parse(relative).is_ok();
+        match default_origin.and_then(|origin| joined.strip_prefix(origin)) {
+            Some(joined) if !replaces_origin => RequestTarget::from(joined),
+            _ => RequestTarget::from(joined.as_str()),
+        }
+    }
+
+    /// Returns the path of the target without query or fragment, for
+    /// `https://test.com:8080/get/html?id=123` as well as `/get/html?id=123` this is
+    /// `/get/html`. Absolute targets without path return `/`, the asterisk target `*` and missing
+    /// or invalid targets an empty path.
+    pub fn path(&self) -> &str {
+        let path_and_query = self.path_and_query();
+        let path = &path_and_query[..path_and_query
+            .find(['?', '#'])
+            .unwrap_or(path_and_query.len())];
+        if path.is_empty() && matches!(self, RequestTarget::Absolute { .. }) {
+            return "/";
+        }
+        path
+    }
+
+    /// Returns the query of the target without the leading '?' if there is any, for
+    /// `/get?id=123&value=test` this is `id=123&value=test`.
+    pub fn query(&self) -> Option<&str> {
+        let path_and_query = self.path_and_query();
+        let start = path_and_query.find('?')? + 1;
+        let end = path_and_query
+            .find('#')
+            .filter(|end| *end >= start)
+            .unwrap_or(path_and_query.len());
+        Some(&path_and_query[start..end])
+    }
+
+    /// Returns the port of an absolute target. If none is given explicitly the default port of
+    /// the scheme is returned, `443` for https and `80` for http or targets without scheme.
+    /// Relative origins and other targets have no port.
+    pub fn port(&self) -> Option<u16> {
+        let uri = self.parse_absolute_uri()?;
+        if let Some(port) = uri.port_u16() {
+            return Some(port);
+        }
+        match uri.scheme_str() {
+            Some(scheme) if scheme.eq_ignore_ascii_case("https") => Some(443),
+            Some(scheme) if scheme.eq_ignore_ascii_case("http") => Some(80),
+            _ => None,
+        }
+    }
 }
 
 #[derive(PartialEq, Debug, Clone)]
@@ -535,19 +1747,35 @@ impl HttpRestFileExtension {
 
 impl std::fmt::Display for HttpRestFileExtension {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            HttpRestFileExtension::Http => f.write_str("http"),
-            HttpRestFileExtension::Rest => f.write_str("rest"),
-        }
+        f.write_str(self.as_str())
     }
 }
 
 impl HttpRestFileExtension {
+    /// Returns the extension without leading dot, `"http"` or `"rest"`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HttpRestFileExtension::Http => "http",
+            HttpRestFileExtension::Rest => "rest",
+        }
+    }
+
+    /// Returns the extension of the given path, the extension is matched case insensitive so
+    /// `.HTTP` is recognized as well. Paths without an extension, with an unknown extension or
+    /// with an extension that is not valid utf8 return `None`. Both '/' and '\\' separate the
+    /// directories of the path on every platform.
     pub fn from_path(path: &std::path::Path) -> Option<Self> {
-        match path.extension().and_then(|os_str| os_str.to_str()) {
-            Some("http") => Some(HttpRestFileExtension::Http),
-            Some("rest") => Some(HttpRestFileExtension::Rest),
-            _ => None,
+        let extension = path.extension()?.to_str()?;
+        // the dot belongs to a directory such as 'folder.d\\test'
+        if extension.contains('\\') {
+            return None;
+        }
+        if extension.eq_ignore_ascii_case("http") {
+            Some(HttpRestFileExtension::Http)
+        } else if extension.eq_ignore_ascii_case("rest") {
+            Some(HttpRestFileExtension::Rest)
+        } else {
+            None
         }
     }
 }
@@ -558,20 +1786,91 @@ pub struct HttpRestFile {
     pub errs: Vec<ErrorWithPartial>,
     pub path: Box<std::path::PathBuf>,
     pub extension: Option<HttpRestFileExtension>,
+    // first line of the file if it is a shebang such as '#!/usr/bin/env http' or an editor
+    // modeline such as '# -*- mode: restclient -*-', it does not belong to the first request
+    pub modeline: Option<String>,
+}
+
+impl HttpRestFile {
+    /// Returns the successfully parsed requests for editing them before the file is serialized
+    /// again. Use `push_request` and `remove_request` to add or remove requests so the index of
+    /// each request stays consistent.
+    pub fn requests_mut(&mut self) -> &mut Vec<Request> {
+        &mut self.requests
+    }
+
+    /// Returns all successfully parsed requests of the file in order, including disabled ones
+    pub fn all_requests(&self) -> impl Iterator<Item = &Request> {
+        self.requests.iter()
+    }
+
+    /// Returns the successfully parsed requests of the file in order, requests disabled with
+    /// `@disabled` are skipped
+    pub fn enabled_requests(&self) -> impl Iterator<Item = &Request> {
+        self.requests
+            .iter()
+            .filter(|request| !request.is_disabled())
+    }
+
+    /// Add a request at the end of the file, its index is set to follow the index of all other
+    /// requests of the file including those that could not be parsed
+    pub fn push_request(&mut self, mut request: Request) {
+        request.index = self
+            .requests
+            .iter()
+            .map(|request| request.index)
+            .chain(self.errs.iter().map(|err| err.partial_request.index))
+            .max()
+            .map_or(0, |index| index + 1);
+        self.requests.push(request);
+    }
+
+    /// Remove the request at `position` within `requests` and return it, the index of all
+    /// requests following it in the file is decreased by one. Returns None if there is no
+    /// request at the position.
+    pub fn remove_request(&mut self, position: usize) -> Option<Request> {
+        if position >= self.requests.len() {
+            return None;
+        }
+        let removed = self.requests.remove(position);
+        for request in self.requests.iter_mut() {
+            if request.index > removed.index {
+                request.index -= 1;
+            }
+        }
+        for err in self.errs.iter_mut() {
+            if err.partial_request.index > removed.index {
+                err.partial_request.index -= 1;
+            }
+        }
+        Some(removed)
+    }
 }
 
 #[derive(PartialEq, Debug, Clone, Eq)]
 #[cfg_attr(feature = "rspc", derive(Type))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum PreRequestScript {
-    FromFilepath(String),
+    // a script file referenced with '< path' is used verbatim, with '<@ path' variables within
+    // the file are substituted
+    FromFilepath {
+        path: String,
+        substitute_variables: bool,
+    },
     Script(String),
 }
 
 impl ToString for PreRequestScript {
     fn to_string(&self) -> String {
         match self {
-            PreRequestScript::FromFilepath(path) => format!("< {}", path),
+            PreRequestScript::FromFilepath {
+                path,
+                substitute_variables: false,
+            } => format!("< {}", path),
+            PreRequestScript::FromFilepath {
+                path,
+                substitute_variables: true,
+            } => format!("<@ {}", path),
             PreRequestScript::Script(script) => {
                 format!("< {{%{}%}}", script)
             }
@@ -587,6 +1886,27 @@ pub enum ResponseHandler {
     Script(String),
 }
 
+impl ResponseHandler {
+    /// Returns the names of the globals the handler script sets with `client.global.set`, in
+    /// the order of their first occurrence. Only names given as string literal are found, the
+    /// values may be dynamic and are not returned. Handlers read from a file return no names as
+    /// the file is not read.
+    pub fn globals_set(&self) -> Vec<String> {
+        let ResponseHandler::Script(script) = self else {
+            return Vec::new();
+        };
+        let mut names: Vec<String> = Vec::new();
+        for captures in GLOBAL_SET.captures_iter(script) {
+            if let Some(name) = captures.get(1).or_else(|| captures.get(2)) {
+                if !names.iter().any(|existing| existing == name.as_str()) {
+                    names.push(name.as_str().to_string());
+                }
+            }
+        }
+        names
+    }
+}
+
 #[derive(PartialEq, Debug, Clone, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "rspc", derive(Type))]
@@ -599,7 +1919,7 @@ pub enum SaveResponse {
     RewriteFile(std::path::PathBuf),
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Request {
     pub name: Option<String>,
@@ -608,9 +1928,16 @@ pub struct Request {
     pub headers: Vec<Header>,
     pub body: RequestBody,
     pub settings: RequestSettings,
-    pub pre_request_script: Option<PreRequestScript>,
+    pub pre_request_scripts: Vec<PreRequestScript>,
     pub response_handler: Option<ResponseHandler>,
     pub save_response: Option<SaveResponse>,
+    // position of the request within its file starting at 0, requests that could not be parsed
+    // are counted as well
+    pub index: usize,
+    // problems found while parsing which do not fail the request, such as a request line with
+    // inconsistent indentation
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub warnings: Vec<ParseErrorDetails>,
 }
 
 impl Default for Request {
@@ -622,9 +1949,11 @@ impl Default for Request {
             headers: vec![],
             body: RequestBody::None,
             settings: RequestSettings::default(),
-            pre_request_script: None,
+            pre_request_scripts: vec![],
             response_handler: None,
             save_response: None,
+            index: 0,
+            warnings: vec![],
         }
     }
 }
@@ -671,6 +2000,19 @@ pub struct Comment {
     pub kind: CommentKind,
 }
 
+impl Comment {
+    /// Returns true if the comment holds meta information such as `@name=...` or `@no-log`,
+    /// that is its text starts with an `@` directly followed by a word after trimming. An `@`
+    /// elsewhere, for example within an email address, does not make a directive.
+    pub fn is_directive(&self) -> bool {
+        let mut chars = self.value.trim_start().chars();
+        chars.next() == Some('@')
+            && chars
+                .next()
+                .is_some_and(|c| c.is_alphanumeric() || c == '_')
+    }
+}
+
 impl ToString for Comment {
     fn to_string(&self) -> String {
         match self.kind {
@@ -681,7 +2023,9 @@ impl ToString for Comment {
     }
 }
 
-#[derive(PartialEq, Debug, Clone, Eq)]
+// versions are ordered by major then minor version, the derived ordering relies on the order of
+// the fields
+#[derive(PartialEq, Debug, Clone, Eq, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "rspc", derive(Type))]
 pub struct HttpVersion {
@@ -695,6 +2039,14 @@ impl Default for HttpVersion {
     }
 }
 
+impl HttpVersion {
+    /// Returns true if multiple requests can be sent concurrently over one connection, which is
+    /// the case from `HTTP/2.0` on
+    pub fn supports_multiplexing(&self) -> bool {
+        *self >= HttpVersion { major: 2, minor: 0 }
+    }
+}
+
 impl std::str::FromStr for HttpVersion {
     type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -761,6 +2113,7 @@ impl ToString for RequestTarget {
             RequestTarget::Asterisk => "*",
             RequestTarget::Absolute { uri, .. } => uri,
             RequestTarget::RelativeOrigin { uri, .. } => uri,
+            RequestTarget::Data { uri } => uri,
             RequestTarget::InvalidTarget(target) => target,
             RequestTarget::Missing => "",
         }
@@ -783,63 +2136,719 @@ impl Request {
         )
     }
 
-    pub fn get_url(&self) -> String {
-        self.request_line.target.to_string()
+    /// Returns the method of the request, if none is specified the default method `GET` is
+    /// returned.
+    pub fn method(&self) -> HttpMethod {
+        self.request_line.method.get_or_default()
     }
-}
 
-#[derive(PartialEq, Debug)]
-pub struct FileParseResult {
-    pub requests: Vec<Request>,
-    pub errs: Vec<ErrorWithPartial>,
-}
+    /// Returns true if the `Content-Type` header denotes an xml body, which are the media types
+    /// `application/xml`, `text/xml` and types with the suffix `+xml` such as
+    /// `application/soap+xml`
+    pub fn has_xml_body(&self) -> bool {
+        self.headers
+            .iter()
+            .find(|header| header.key.eq_ignore_ascii_case("Content-Type"))
+            .map(|header| {
+                header
+                    .value
+                    .split(';')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_ascii_lowercase()
+            })
+            .is_some_and(|media_type| {
+                media_type == "application/xml"
+                    || media_type == "text/xml"
+                    || media_type.ends_with("+xml")
+            })
+    }
 
-#[derive(PartialEq, Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct PartialRequest {
-    pub name: Option<String>,
-    pub comments: Vec<Comment>,
-    pub settings: RequestSettings,
-    pub request_line: Option<RequestLine>,
-    pub headers: Option<Vec<Header>>,
-    pub body: Option<RequestBody>,
-    pub pre_request_script: Option<PreRequestScript>,
-    pub response_handler: Option<ResponseHandler>,
-    pub save_response: Option<SaveResponse>,
-}
+    /// Returns true if the `Content-Type` header denotes a json body with comments, which are
+    /// the media types `application/jsonc` and `application/json5`. Use
+    /// `RequestBody::strip_json_comments` to get the json that can be sent.
+    pub fn has_jsonc_body(&self) -> bool {
+        self.headers
+            .iter()
+            .find(|header| header.key.eq_ignore_ascii_case("Content-Type"))
+            .map(|header| {
+                header
+                    .value
+                    .split(';')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_ascii_lowercase()
+            })
+            .is_some_and(|media_type| {
+                media_type == "application/jsonc" || media_type == "application/json5"
+            })
+    }
 
-impl From<PartialRequest> for Request {
-    fn from(partial: PartialRequest) -> Self {
-        Request {
-            name: partial.name,
-            comments: partial.comments,
-            request_line: partial.request_line.unwrap_or(RequestLine {
-                method: WithDefault::<HttpMethod>::default(),
-                target: RequestTarget::Missing,
-                http_version: WithDefault::Default(HttpVersion::default()),
-            }),
-            body: partial.body.unwrap_or(RequestBody::None),
-            save_response: partial.save_response,
-            headers: partial.headers.unwrap_or_default(),
-            response_handler: partial.response_handler,
-            settings: partial.settings,
-            pre_request_script: partial.pre_request_script,
-        }
+    /// Returns true if the request is disabled with `@disabled` and should not be run
+    pub fn is_disabled(&self) -> bool {
+        self.settings.disabled
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Returns true if the body should be sent with chunked transfer encoding, either because
+    /// of the `@chunked` directive or a `Transfer-Encoding: chunked` header
+    pub fn is_chunked(&self) -> bool {
+        self.settings.chunked
+            || self.headers.iter().any(|header| {
+                header.key.eq_ignore_ascii_case("Transfer-Encoding")
+                    && header
+                        .value
+                        .split(',')
+                        .any(|coding| coding.trim().eq_ignore_ascii_case("chunked"))
+            })
+    }
 
-    #[test]
-    pub fn test_with_default() {
-        assert_eq!(
-            WithDefault::<HttpVersion>::default(),
-            WithDefault::<HttpVersion>::default()
-        );
+    /// Returns the http version of the request, if none is specified the default version
+    /// `HTTP/1.1` is returned.
+    pub fn http_version(&self) -> HttpVersion {
+        self.request_line.http_version.get_or_default()
+    }
 
-        assert_eq!(
+    /// Returns the description of the request which consists of the regular comments preceding
+    /// it. Other than `get_comment_text` it leaves out request separator comments ('###') and
+    /// comments containing meta directives ('@...'). The name of the request is not contained
+    /// as it is removed from the comments when parsing.
+    pub fn description(&self) -> Option<String> {
+        let description = self
+            .comments
+            .iter()
+            .filter(|comment| {
+                comment.kind != CommentKind::RequestSeparator && !comment.is_directive()
+            })
+            .map(|comment| comment.value.trim_end())
+            .collect::<Vec<&str>>()
+            .join("\n");
+
+        if description.is_empty() {
+            return None;
+        }
+        Some(description)
+    }
+
+    /// Returns the names of all variables (`{{name}}`) used within the target, headers and body
+    /// of the request in the order of their first occurrence. Escaped braces such as
+    /// `\{\{ name \}\}` denote a literal `{{` and are not taken as variables.
+    pub fn variables(&self) -> Vec<String> {
+        let mut texts: Vec<String> = vec![self.request_line.target.to_string()];
+        texts.extend(self.headers.iter().map(|header| header.to_string()));
+        match self.body {
+            // the string representation would url encode the braces
+            RequestBody::UrlEncoded {
+                ref url_encoded_params,
+            } => texts.extend(
+                url_encoded_params
+                    .iter()
+                    .map(|param| format!("{}={}", param.key, param.value)),
+            ),
+            ref body => texts.push(body.to_string()),
+        }
+
+        let mut variables: Vec<String> = Vec::new();
+        for text in texts.iter() {
+            for captures in VARIABLE.captures_iter(text) {
+                let name = captures[1].to_string();
+                if !variables.contains(&name) {
+                    variables.push(name);
+                }
+            }
+        }
+        variables
+    }
+
+    /// Returns the paths of all files the request reads when it is run, in order: pre-request
+    /// scripts, the body or the data of multipart parts and the response handler. The paths are
+    /// returned as written, relative paths have to be resolved against the directory of the
+    /// request file. Files the response is saved to are not included.
+    pub fn external_file_paths(&self) -> Vec<&str> {
+        let mut paths: Vec<&str> = Vec::new();
+        for script in self.pre_request_scripts.iter() {
+            if let PreRequestScript::FromFilepath { path, .. } = script {
+                paths.push(path);
+            }
+        }
+        match self.body {
+            RequestBody::Raw {
+                data: DataSource::FromFilepath(ref path),
+            } => paths.push(path),
+            RequestBody::Multipart { ref parts, .. } => {
+                paths.extend(parts.iter().filter_map(|part| match part.data {
+                    DataSource::FromFilepath(ref path) => Some(path.as_str()),
+                    DataSource::Raw(_) => None,
+                }))
+            }
+            _ => (),
+        }
+        if let Some(ResponseHandler::FromFilepath(ref path)) = self.response_handler {
+            paths.push(path);
+        }
+        paths
+    }
+
+    pub fn get_url(&self) -> String {
+        self.request_line.target.to_string()
+    }
+
+    /// Returns the url the request is sent to. Variables (`{{name}}`) are replaced by their value
+    /// within the given environment, a relative target is prefixed with the host from the
+    /// `Host` header or the variable `host` and `http://` is added if no scheme is present.
+    pub fn url(&self, env: Option<&Environment>) -> Result<http::Uri, UrlError> {
+        let mut unresolved: Vec<String> = Vec::new();
+        let mut substitute = |text: &str| -> String {
+            VARIABLE
+                .replace_all(text, |captures: &regex::Captures| {
+                    match env.and_then(|env| env.get(&captures[1])) {
+                        Some(value) => value.to_string(),
+                        None => {
+                            if !unresolved.contains(&captures[1].to_string()) {
+                                unresolved.push(captures[1].to_string());
+                            }
+                            captures[0].to_string()
+                        }
+                    }
+                })
+                .to_string()
+        };
+
+        let mut url = substitute(&self.request_line.target.to_string());
+        if let RequestTarget::RelativeOrigin { .. } = self.request_line.target {
+            let host = self
+                .headers
+                .iter()
+                .find(|header| header.key.eq_ignore_ascii_case("Host"))
+                .map(|header| header.value.clone())
+                .or_else(|| {
+                    env.and_then(|env| env.get("host"))
+                        .map(|host| host.to_string())
+                });
+            match host {
+                Some(host) => {
+                    url = format!("{}{}", substitute(host.trim_end_matches('/')), url);
+                }
+                None if unresolved.is_empty() => return Err(UrlError::MissingHost(url)),
+                None => (),
+            }
+        }
+
+        if !unresolved.is_empty() {
+            return Err(UrlError::UnresolvedVariables(unresolved));
+        }
+
+        if !url.contains("://") {
+            url = format!("http://{}", url);
+        }
+        url.parse::<http::Uri>()
+            .map_err(|err| UrlError::InvalidUrl {
+                url: url.clone(),
+                reason: err.to_string(),
+            })
+    }
+
+    /// Returns the body as text for a quick preview. Raw bodies are returned as is and url
+    /// encoded bodies as their encoded string such as `key=value&other=1`. Returns None if
+    /// there is no body or the body is read from a file or is a multipart body.
+    pub fn body_text(&self) -> Option<Cow<'_, str>> {
+        match self.body {
+            RequestBody::Empty => Some(Cow::Borrowed("")),
+            RequestBody::Raw {
+                data: DataSource::Raw(ref data),
+            } => Some(Cow::Borrowed(data)),
+            RequestBody::UrlEncoded { .. } => Some(Cow::Owned(self.body.to_string())),
+            RequestBody::None
+            | RequestBody::Raw {
+                data: DataSource::FromFilepath(_),
+            }
+            | RequestBody::Multipart { .. } => None,
+        }
+    }
+
+    /// Returns a one line summary of the request for logs and lists such as
+    /// `POST https://test.com/users (json, 2 headers)` containing the effective method, the
+    /// target, the kind of the body and the number of headers. The kind of the body is one of
+    /// `no body`, `empty`, `json`, `xml`, `text`, `file`, `form` or `multipart`.
+    pub fn summary(&self) -> String {
+        let body_kind = match self.body {
+            RequestBody::None => "no body",
+            RequestBody::Empty => "empty",
+            RequestBody::UrlEncoded { .. } => "form",
+            RequestBody::Multipart { .. } => "multipart",
+            RequestBody::Raw {
+                data: DataSource::FromFilepath(_),
+            } => "file",
+            RequestBody::Raw { .. } => match self.body.guess_content_type().as_deref() {
+                Some("application/json") => "json",
+                Some("application/xml") => "xml",
+                _ => "text",
+            },
+        };
+        let headers = match self.headers.len() {
+            1 => "1 header".to_string(),
+            count => format!("{} headers", count),
+        };
+        format!(
+            "{} {} ({}, {})",
+            self.method().to_string(),
+            self.request_line.target.to_string(),
+            body_kind,
+            headers
+        )
+    }
+
+    /// Returns a hash identifying what the request sends: the effective method and http
+    /// version, the target, the headers and the body. Descriptive parts such as the name,
+    /// comments and settings as well as scripts and handlers are ignored, so requests that only
+    /// differ in those have the same hash. Header names are compared case insensitive. The hash
+    /// is stable within a build of the library but should not be persisted across versions.
+    pub fn identity_hash(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.method().to_string().hash(&mut hasher);
+        self.http_version().to_string().hash(&mut hasher);
+        self.request_line.target.to_string().hash(&mut hasher);
+        for header in self.headers.iter() {
+            header.key.to_ascii_lowercase().hash(&mut hasher);
+            header.value.hash(&mut hasher);
+        }
+        // bodies without content have the same string representation
+        std::mem::discriminant(&self.body).hash(&mut hasher);
+        self.body.to_string().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Returns a copy of the request where a relative target such as `/users` is prefixed with
+    /// the given base url, for example `https://staging.test.com/api`. Exactly one '/' is put
+    /// between base and path, regardless of a trailing slash of the base. Other targets are kept
+    /// as is.
+    pub fn with_base_url(&self, base: &str) -> Request {
+        let RequestTarget::RelativeOrigin { ref uri } = self.request_line.target else {
+            return self.clone();
+        };
+        let base = base.trim_end_matches('/');
+        let target = if uri.starts_with(['/', '?', '#']) {
+            format!("{}{}", base, uri)
+        } else {
+            format!("{}/{}", base, uri)
+        };
+        Request {
+            request_line: RequestLine {
+                target: RequestTarget::from(target.as_str()),
+                ..self.request_line.clone()
+            },
+            ..self.clone()
+        }
+    }
+
+    /// Returns a copy of the request without the content of the body, for example to log the
+    /// request without leaking sensitive data. Bodies referencing a file (`< path`) are kept as
+    /// the path is metadata only, for multipart bodies the parts are kept but their data is
+    /// cleared unless it references a file.
+    pub fn clone_without_body(&self) -> Request {
+        let body = match &self.body {
+            RequestBody::Raw {
+                data: DataSource::FromFilepath(path),
+            } => RequestBody::Raw {
+                data: DataSource::FromFilepath(path.clone()),
+            },
+            RequestBody::Multipart { boundary, parts } => RequestBody::Multipart {
+                boundary: boundary.clone(),
+                parts: parts
+                    .iter()
+                    .map(|part| Multipart {
+                        data: match &part.data {
+                            DataSource::FromFilepath(path) => {
+                                DataSource::FromFilepath(path.clone())
+                            }
+                            DataSource::Raw(_) => DataSource::Raw(String::new()),
+                        },
+                        ..part.clone()
+                    })
+                    .collect(),
+            },
+            _ => RequestBody::None,
+        };
+        Request {
+            body,
+            ..self.clone()
+        }
+    }
+
+    /// Canonicalize trivia of the request so that requests with the same meaning compare equal,
+    /// for example in tests. The method and http version are resolved to explicit values,
+    /// unset flags of the settings become `Some(false)` and the name, comments and the keys and
+    /// values of headers (also of multipart parts) are trimmed. The order of headers as well as
+    /// the body content are kept as is.
+    pub fn normalized(self) -> Request {
+        let trim_headers = |headers: Vec<Header>| {
+            headers
+                .into_iter()
+                .map(|header| Header::new(header.key.trim(), header.value.trim()))
+                .collect::<Vec<Header>>()
+        };
+        let flag = |flag: Option<bool>| Some(flag.unwrap_or(false));
+
+        let body = match self.body {
+            RequestBody::Multipart { boundary, parts } => RequestBody::Multipart {
+                boundary,
+                parts: parts
+                    .into_iter()
+                    .map(|part| Multipart {
+                        headers: trim_headers(part.headers),
+                        ..part
+                    })
+                    .collect(),
+            },
+            body => body,
+        };
+
+        Request {
+            name: self.name.map(|name| name.trim().to_string()),
+            comments: self
+                .comments
+                .into_iter()
+                .map(|comment| Comment {
+                    value: comment.value.trim().to_string(),
+                    ..comment
+                })
+                .collect(),
+            request_line: RequestLine {
+                method: WithDefault::Some(self.request_line.method.unwrap_or_default()),
+                http_version: WithDefault::Some(self.request_line.http_version.unwrap_or_default()),
+                ..self.request_line
+            },
+            headers: trim_headers(self.headers),
+            body,
+            settings: RequestSettings {
+                no_redirect: flag(self.settings.no_redirect),
+                no_log: flag(self.settings.no_log),
+                no_cookie_jar: flag(self.settings.no_cookie_jar),
+                no_proxy: flag(self.settings.no_proxy),
+                raw_body: flag(self.settings.raw_body),
+                ..self.settings
+            },
+            ..self
+        }
+    }
+
+    /// Returns the headers of the request where the values of headers with one of the given
+    /// names, such as `Authorization`, are masked. Names are compared case insensitive.
+    pub fn redacted_headers(&self, names: &[&str]) -> Vec<Header> {
+        self.headers
+            .iter()
+            .map(|header| {
+                if names
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(&header.key))
+                {
+                    Header::new(&header.key, "***")
+                } else {
+                    header.clone()
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the headers as they would be sent, the authored headers followed by inferred
+    /// defaults for headers which are not present (compared case insensitive):
+    /// - `Content-Type` guessed from the body, see `RequestBody::guess_content_type`
+    /// - `Content-Length` of the body, if it can be computed. Files are read relative to
+    ///   `base_dir`, if they cannot be read no length is added. Not added if a
+    ///   `Transfer-Encoding` header is present.
+    /// - `Transfer-Encoding: chunked` instead of the `Content-Length` for a request with the
+    ///   `@chunked` directive
+    ///
+    /// Authored headers take precedence, only a multipart `Content-Type` without boundary is
+    /// completed with the boundary of the body.
+    pub fn effective_headers(&self, base_dir: &std::path::Path) -> Vec<Header> {
+        let mut headers = self.headers.clone();
+        if !self.body.is_present() {
+            return headers;
+        }
+        let has_header = |headers: &[Header], name: &str| {
+            headers
+                .iter()
+                .any(|header| header.key.eq_ignore_ascii_case(name))
+        };
+
+        match headers
+            .iter_mut()
+            .find(|header| header.key.eq_ignore_ascii_case("Content-Type"))
+        {
+            Some(header) => {
+                if let RequestBody::Multipart { ref boundary, .. } = self.body {
+                    if multipart_boundary_param(&header.value).is_none() {
+                        header.value = format!("{}; boundary={}", header.value, boundary);
+                    }
+                }
+            }
+            None => {
+                if let Some(content_type) = self.body.guess_content_type() {
+                    headers.push(Header::new("Content-Type", content_type));
+                }
+            }
+        }
+
+        if self.settings.chunked && !has_header(&headers, "Transfer-Encoding") {
+            headers.push(Header::new("Transfer-Encoding", "chunked"));
+        }
+        if !has_header(&headers, "Content-Length") && !has_header(&headers, "Transfer-Encoding") {
+            if let Ok(bytes) = self.body.as_bytes(base_dir) {
+                headers.push(Header::new("Content-Length", bytes.len().to_string()));
+            }
+        }
+        headers
+    }
+
+    /// Returns true if the request has neither a request target nor any other content, which is
+    /// the case for a request created from a lone request separator ('###'). Comments without
+    /// text do not count as content.
+    pub fn is_empty(&self) -> bool {
+        self.request_line.target == RequestTarget::Missing
+            && self.name.is_none()
+            && self
+                .comments
+                .iter()
+                .all(|comment| comment.value.trim().is_empty())
+            && self.headers.is_empty()
+            && self.body == RequestBody::None
+            && self.settings == RequestSettings::default()
+            && self.pre_request_scripts.is_empty()
+            && self.response_handler.is_none()
+            && self.save_response.is_none()
+    }
+
+    /// Run semantic checks on the request which are not caught when parsing as the request is
+    /// syntactically valid. The problems are returned as warnings, an empty list means no
+    /// problems were found. Checked are:
+    /// - absolute targets without a scheme
+    /// - the asterisk target with a method other than `OPTIONS`
+    /// - headers defined multiple times (compared case insensitive)
+    /// - the multipart boundary of the `Content-Type` header against the one of the body
+    /// - the `Content-Length` header against the length of a body given directly
+    pub fn validate(&self) -> Vec<ParseError> {
+        let mut warnings: Vec<ParseError> = Vec::new();
+
+        match self.request_line.target {
+            RequestTarget::Absolute { ref uri } if !self.request_line.target.has_scheme() => {
+                warnings.push(ParseError::MissingUrlScheme(uri.clone()));
+            }
+            RequestTarget::Asterisk if self.method() != HttpMethod::OPTIONS => {
+                warnings.push(ParseError::AsteriskTargetRequiresOptions(
+                    self.method().to_string(),
+                ));
+            }
+            _ => (),
+        }
+
+        let mut seen: Vec<&str> = Vec::new();
+        let mut duplicates: Vec<&str> = Vec::new();
+        for header in self.headers.iter() {
+            let key = header.key.as_str();
+            if seen.iter().any(|seen| seen.eq_ignore_ascii_case(key)) {
+                if !duplicates.iter().any(|dup| dup.eq_ignore_ascii_case(key)) {
+                    duplicates.push(key);
+                    warnings.push(ParseError::DuplicateHeader(key.to_string()));
+                }
+            } else {
+                seen.push(key);
+            }
+        }
+
+        let header_value = |name: &str| {
+            self.headers
+                .iter()
+                .find(|header| header.key.eq_ignore_ascii_case(name))
+                .map(|header| header.value.as_str())
+        };
+
+        if let RequestBody::Multipart { ref boundary, .. } = self.body {
+            if let Some(header_boundary) =
+                header_value("Content-Type").and_then(multipart_boundary_param)
+            {
+                if let Err(err) =
+                    crate::parser::Parser::is_multipart_boundary_valid(&header_boundary)
+                {
+                    warnings.push(err.error);
+                }
+                if &header_boundary != boundary {
+                    warnings.push(ParseError::MultipartBoundaryMismatch {
+                        header: header_boundary,
+                        body: boundary.clone(),
+                    });
+                }
+            }
+        }
+
+        if let Some(content_length) = header_value("Content-Length") {
+            // the length of data read from a file is not known without reading it
+            let actual = match self.body {
+                RequestBody::None | RequestBody::Empty => Some(0),
+                RequestBody::Raw {
+                    data: DataSource::Raw(ref data),
+                } => Some(data.len()),
+                RequestBody::UrlEncoded { .. } => Some(self.body.to_string().len()),
+                _ => None,
+            };
+            match content_length.trim().parse::<usize>() {
+                Ok(header) => {
+                    if let Some(actual) = actual.filter(|&actual| actual != header) {
+                        warnings.push(ParseError::ContentLengthMismatch { header, actual });
+                    }
+                }
+                Err(_) => {
+                    warnings.push(ParseError::InvalidContentLength(content_length.to_string()))
+                }
+            }
+        }
+
+        warnings
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct FileParseResult {
+    pub requests: Vec<Request>,
+    pub errs: Vec<ErrorWithPartial>,
+}
+
+impl FileParseResult {
+    /// Returns true if any request could not be parsed without errors
+    pub fn has_errors(&self) -> bool {
+        !self.errs.is_empty()
+    }
+
+    /// Returns the number of requests that could not be parsed without errors
+    pub fn error_count(&self) -> usize {
+        self.errs.len()
+    }
+
+    /// Returns the first successfully parsed request with the given name, for example to
+    /// resolve the names a request depends on (`@ref <name>`)
+    pub fn find_request_by_name(&self, name: &str) -> Option<&Request> {
+        self.requests
+            .iter()
+            .find(|request| request.name.as_deref() == Some(name))
+    }
+
+    /// Returns the requests if all of them could be parsed without errors, otherwise the errors.
+    /// The successfully parsed requests are dropped in the error case.
+    #[allow(clippy::result_large_err)]
+    pub fn into_result(self) -> Result<Vec<Request>, Vec<ErrorWithPartial>> {
+        if self.errs.is_empty() {
+            Ok(self.requests)
+        } else {
+            Err(self.errs)
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PartialRequest {
+    pub name: Option<String>,
+    pub comments: Vec<Comment>,
+    pub settings: RequestSettings,
+    pub request_line: Option<RequestLine>,
+    pub headers: Option<Vec<Header>>,
+    pub body: Option<RequestBody>,
+    pub pre_request_scripts: Vec<PreRequestScript>,
+    pub response_handler: Option<ResponseHandler>,
+    pub save_response: Option<SaveResponse>,
+    pub index: usize,
+    // problems which do not fail the request, the request failed because of other errors
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub warnings: Vec<ParseErrorDetails>,
+}
+
+impl PartialRequest {
+    /// Returns true if anything besides empty comments has been parsed for the partial request.
+    /// Partials that are not meaningful stem from trailing request separators ('###') and can be
+    /// dropped.
+    pub fn is_meaningful(&self) -> bool {
+        self.request_line.is_some()
+            || self.name.is_some()
+            || self
+                .comments
+                .iter()
+                .any(|comment| !comment.value.trim().is_empty())
+            || self
+                .headers
+                .as_ref()
+                .is_some_and(|headers| !headers.is_empty())
+            || self
+                .body
+                .as_ref()
+                .is_some_and(|body| body != &RequestBody::None)
+            || self.settings != RequestSettings::default()
+            || !self.pre_request_scripts.is_empty()
+            || self.response_handler.is_some()
+            || self.save_response.is_some()
+    }
+}
+
+impl From<Request> for PartialRequest {
+    fn from(request: Request) -> Self {
+        PartialRequest {
+            name: request.name,
+            comments: request.comments,
+            settings: request.settings,
+            request_line: Some(request.request_line),
+            headers: Some(request.headers),
+            body: Some(request.body),
+            pre_request_scripts: request.pre_request_scripts,
+            response_handler: request.response_handler,
+            save_response: request.save_response,
+            index: request.index,
+            warnings: request.warnings,
+        }
+    }
+}
+
+/// Converts a partially parsed request into a request, every parsed part is kept as is. Parts
+/// that are missing are filled with defaults:
+/// - a missing request line has the default method and http version and a
+///   `RequestTarget::Missing` target
+/// - missing headers are an empty list
+/// - a missing body is `RequestBody::None`
+impl From<PartialRequest> for Request {
+    fn from(partial: PartialRequest) -> Self {
+        Request {
+            name: partial.name,
+            comments: partial.comments,
+            request_line: partial.request_line.unwrap_or(RequestLine {
+                method: WithDefault::<HttpMethod>::default(),
+                target: RequestTarget::Missing,
+                http_version: WithDefault::Default(HttpVersion::default()),
+            }),
+            body: partial.body.unwrap_or(RequestBody::None),
+            save_response: partial.save_response,
+            headers: partial.headers.unwrap_or_default(),
+            response_handler: partial.response_handler,
+            settings: partial.settings,
+            pre_request_scripts: partial.pre_request_scripts,
+            index: partial.index,
+            warnings: partial.warnings,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    pub fn test_with_default() {
+        assert_eq!(
+            WithDefault::<HttpVersion>::default(),
+            WithDefault::<HttpVersion>::default()
+        );
+
+        assert_eq!(
             WithDefault::<HttpMethod>::default(),
             WithDefault::<HttpMethod>::default()
         );
@@ -857,4 +2866,1323 @@ mod tests {
         assert_eq!(WithDefault::Some(1).unwrap_or_default(), 1);
         assert_eq!(WithDefault::Default(1).unwrap_or_default(), 1);
     }
+
+    #[test]
+    pub fn body_as_bytes() {
+        let base_dir = std::env::temp_dir().join("http_rest_file_body_as_bytes");
+        std::fs::create_dir_all(&base_dir).unwrap();
+        std::fs::write(base_dir.join("input.json"), "{\"key\": \"value\"}").unwrap();
+
+        assert_eq!(RequestBody::None.as_bytes(&base_dir).unwrap(), b"");
+
+        let body = RequestBody::Raw {
+            data: DataSource::Raw("some text".to_string()),
+        };
+        assert_eq!(body.as_bytes(&base_dir).unwrap(), b"some text");
+
+        let body = RequestBody::Raw {
+            data: DataSource::FromFilepath("./input.json".to_string()),
+        };
+        assert_eq!(body.as_bytes(&base_dir).unwrap(), b"{\"key\": \"value\"}");
+
+        let body = RequestBody::Raw {
+            data: DataSource::FromFilepath("./missing.json".to_string()),
+        };
+        assert!(body.as_bytes(&base_dir).is_err());
+
+        let body = RequestBody::UrlEncoded {
+            url_encoded_params: vec![
+                UrlEncodedParam::new("first", "a b"),
+                UrlEncodedParam::new("second", "&"),
+            ],
+        };
+        assert_eq!(body.as_bytes(&base_dir).unwrap(), b"first=a+b&second=%26");
+
+        let body = RequestBody::Multipart {
+            boundary: "WebAppBoundary".to_string(),
+            parts: vec![
+                Multipart {
+                    data: DataSource::Raw("Name".to_string()),
+                    disposition: DispositionField::new("element-name"),
+                    headers: vec![Header::new("Content-Type", "text/plain")],
+                },
+                Multipart {
+                    data: DataSource::FromFilepath("./input.json".to_string()),
+                    disposition: DispositionField::new_with_filename("data", Some("data.json")),
+                    headers: vec![Header::new("Content-Type", "application/json")],
+                },
+            ],
+        };
+        let expected = "--WebAppBoundary\r
+Content-Disposition: form-data; name=\"element-name\"\r
+Content-Type: text/plain\r
+\r
+Name\r
+--WebAppBoundary\r
+Content-Disposition: form-data; name=\"data\"; filename=\"data.json\"\r
+Content-Type: application/json\r
+\r
+{\"key\": \"value\"}\r
+--WebAppBoundary--";
+        assert_eq!(
+            String::from_utf8(body.as_bytes(&base_dir).unwrap()).unwrap(),
+            expected
+        );
+
+        // a stale content length of a part is replaced by the length of its data
+        let body = RequestBody::Multipart {
+            boundary: "b".to_string(),
+            parts: vec![Multipart {
+                data: DataSource::Raw("12345".to_string()),
+                disposition: DispositionField::new("n"),
+                headers: vec![Header::new("Content-Length", "1")],
+            }],
+        };
+        assert_eq!(
+            String::from_utf8(body.as_bytes(&base_dir).unwrap()).unwrap(),
+            "--b\r\nContent-Disposition: form-data; name=\"n\"\r\nContent-Length: 5\r\n\r\n12345\r\n--b--"
+        );
+    }
+
+    #[test]
+    pub fn parse_disposition_field() {
+        assert_eq!(
+            DispositionField::parse("form-data; name=\"text\""),
+            Ok(DispositionField::new("text"))
+        );
+        assert_eq!(
+            DispositionField::parse("form-data; name=file; filename=data.json"),
+            Ok(DispositionField::new_with_filename(
+                "file",
+                Some("data.json")
+            ))
+        );
+        assert_eq!(
+            DispositionField::parse("form-data; name=\"file\" ; filename=\"a=b.json\""),
+            Ok(DispositionField::new_with_filename(
+                "file",
+                Some("a=b.json")
+            ))
+        );
+        // only quotes and backslashes are unescaped, other backslashes are kept
+        assert_eq!(
+            DispositionField::parse(r#"form-data; name="file"; filename="C:\dir\a.txt""#),
+            Ok(DispositionField::new_with_filename(
+                "file",
+                Some(r"C:\dir\a.txt")
+            ))
+        );
+        assert_eq!(
+            DispositionField::parse(r#"form-data; name="a\"b\\c""#),
+            Ok(DispositionField::new(r#"a"b\c"#))
+        );
+        assert_eq!(
+            DispositionField::parse(
+                "attachment; filename=\"EURO rates\"; filename*=utf-8''%e2%82%ac%20rates"
+            ),
+            Ok(DispositionField {
+                name: String::new(),
+                filename: Some("EURO rates".to_string()),
+                filename_star: Some("utf-8''%e2%82%ac%20rates".to_string()),
+            })
+        );
+        assert_eq!(
+            DispositionField::parse("form-data; name"),
+            Err(ParseError::MalformedContentDispositionEntries(
+                " name".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    pub fn disposition_to_header_value() {
+        let field = DispositionField::new_with_filename("data", Some("data.json"));
+        assert_eq!(
+            field.to_header_value(),
+            "form-data; name=\"data\"; filename=\"data.json\""
+        );
+        assert_eq!(DispositionField::parse(&field.to_header_value()), Ok(field));
+
+        let field = DispositionField {
+            filename_star: Some("UTF-8''na%C3%AFve.txt".to_string()),
+            ..DispositionField::new_with_filename("my \"file\"", Some("a;b.txt"))
+        };
+        assert_eq!(
+            field.to_header_value(),
+            "form-data; name=\"my \\\"file\\\"\"; filename=\"a;b.txt\"; \
+             filename*=UTF-8''na%C3%AFve.txt"
+        );
+        assert_eq!(DispositionField::parse(&field.to_header_value()), Ok(field));
+
+        // filename* is never quoted, characters which would require quoting are encoded
+        let field = DispositionField {
+            filename_star: Some("UTF-8''my data;1.txt".to_string()),
+            ..DispositionField::new("file")
+        };
+        assert_eq!(
+            field.to_header_value(),
+            "form-data; name=\"file\"; filename*=UTF-8''my%20data%3B1.txt"
+        );
+        let parsed = DispositionField::parse(&field.to_header_value()).unwrap();
+        assert_eq!(parsed.decoded_filename(), Some("my data;1.txt".to_string()));
+
+        assert_eq!(
+            DispositionField::new("").to_header_value(),
+            "form-data; name=\"\""
+        );
+    }
+
+    #[test]
+    pub fn disposition_decoded_filename() {
+        let field = DispositionField {
+            name: "file".to_string(),
+            filename: Some("naive.txt".to_string()),
+            filename_star: Some("UTF-8''na%C3%AFve.txt".to_string()),
+        };
+        assert_eq!(field.decoded_filename(), Some("naïve.txt".to_string()));
+        // raw fields are kept
+        assert_eq!(
+            field.filename_star,
+            Some("UTF-8''na%C3%AFve.txt".to_string())
+        );
+
+        let field = DispositionField {
+            filename_star: Some("iso-8859-1'en'%A3%20rates".to_string()),
+            ..DispositionField::new("file")
+        };
+        assert_eq!(field.decoded_filename(), Some("£ rates".to_string()));
+
+        // fall back to filename if filename* can not be decoded
+        let field = DispositionField {
+            filename_star: Some("UTF-8''broken%C3".to_string()),
+            ..DispositionField::new_with_filename("file", Some("fallback.txt"))
+        };
+        assert_eq!(field.decoded_filename(), Some("fallback.txt".to_string()));
+
+        let field = DispositionField {
+            filename_star: Some("no-charset.txt".to_string()),
+            ..DispositionField::new_with_filename("file", Some("fallback.txt"))
+        };
+        assert_eq!(field.decoded_filename(), Some("fallback.txt".to_string()));
+
+        assert_eq!(DispositionField::new("file").decoded_filename(), None);
+    }
+
+    #[test]
+    pub fn request_target_host_and_port() {
+        let target = RequestTarget::from("https://test.com:8080/get/html?id=123&value=test");
+        assert_eq!(target.host(), Some("test.com"));
+        assert_eq!(target.port(), Some(8080));
+
+        let target = RequestTarget::from("https://test.com/get");
+        assert_eq!(target.host(), Some("test.com"));
+        assert_eq!(target.port(), Some(443));
+
+        let target = RequestTarget::from("http://user:pw@test.com/get");
+        assert_eq!(target.host(), Some("test.com"));
+        assert_eq!(target.port(), Some(80));
+
+        // without scheme http is assumed
+        let target = RequestTarget::from("test.com/api/v1/test");
+        assert_eq!(target.host(), Some("test.com"));
+        assert_eq!(target.port(), Some(80));
+        let target = RequestTarget::from("localhost:3000");
+        assert_eq!(target.host(), Some("localhost"));
+        assert_eq!(target.port(), Some(3000));
+
+        let target = RequestTarget::from("/api/v1/test");
+        assert_eq!(target.host(), None);
+        assert_eq!(target.port(), None);
+        assert_eq!(RequestTarget::Asterisk.host(), None);
+        assert_eq!(RequestTarget::Missing.port(), None);
+    }
+
+    #[test]
+    pub fn request_target_data() {
+        let target = RequestTarget::from("data:text/plain;base64,SGVsbG8=");
+        assert_eq!(
+            target,
+            RequestTarget::Data {
+                uri: "data:text/plain;base64,SGVsbG8=".to_string()
+            }
+        );
+        assert!(target.has_scheme());
+        assert_eq!(target.to_string(), "data:text/plain;base64,SGVsbG8=");
+        assert_eq!(target.data_media_type(), Some("text/plain"));
+        assert_eq!(target.data_payload(), Some(b"Hello".to_vec()));
+        assert_eq!(target.host(), None);
+
+        let target = RequestTarget::from("DATA:,Hello%2C%20World");
+        assert_eq!(
+            target.data_media_type(),
+            Some("text/plain;charset=US-ASCII")
+        );
+        assert_eq!(target.data_payload(), Some(b"Hello, World".to_vec()));
+
+        let target = RequestTarget::from("data:application/json;charset=utf-8;base64,e30");
+        assert_eq!(
+            target.data_media_type(),
+            Some("application/json;charset=utf-8")
+        );
+        assert_eq!(target.data_payload(), Some(b"{}".to_vec()));
+
+        assert_eq!(RequestTarget::from("data:text/plain").data_payload(), None);
+        assert_eq!(
+            RequestTarget::from("data:;base64,a!b=").data_payload(),
+            None
+        );
+        assert_eq!(RequestTarget::from("https://test.com").data_payload(), None);
+
+        let FileParseResult { requests, errs } =
+            crate::Parser::parse("GET data:text/plain;base64,SGVsbG8=", false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(
+            requests[0].request_line.target.data_payload(),
+            Some(b"Hello".to_vec())
+        );
+    }
+
+    #[test]
+    pub fn request_target_join() {
+        let join = |base: &str, relative: &str| RequestTarget::from(base).join(relative);
+        let base = "https://api.example.com/v1";
+
+        assert_eq!(
+            join(base, "/v2/users"),
+            RequestTarget::from("https://api.example.com/v2/users")
+        );
+        assert_eq!(
+            join(base, "https://other.example.com/users?id=1"),
+            RequestTarget::from("https://other.example.com/users?id=1")
+        );
+        assert_eq!(
+            join(base, "//cdn.example.com/logo.png"),
+            RequestTarget::from("https://cdn.example.com/logo.png")
+        );
+        assert_eq!(
+            join(base, "users"),
+            RequestTarget::from("https://api.example.com/users")
+        );
+        assert_eq!(
+            join("https://api.example.com/v1/", "users"),
+            RequestTarget::from("https://api.example.com/v1/users")
+        );
+        assert_eq!(
+            join("https://api.example.com/v1/users/1", "../2/./items"),
+            RequestTarget::from("https://api.example.com/v1/2/items")
+        );
+        assert_eq!(
+            join("https://api.example.com/v1/users", "../../../v2"),
+            RequestTarget::from("https://api.example.com/v2")
+        );
+        // the query is replaced, an empty reference keeps it
+        assert_eq!(
+            join("https://api.example.com/users?page=1#top", "?page=2"),
+            RequestTarget::from("https://api.example.com/users?page=2")
+        );
+        assert_eq!(
+            join("https://api.example.com/users?page=1", "#list"),
+            RequestTarget::from("https://api.example.com/users?page=1#list")
+        );
+        assert_eq!(
+            join("https://api.example.com", "users"),
+            join(base, "/users")
+        );
+
+        // variables are kept as written and targets without scheme stay without scheme
+        assert_eq!(
+            join("https://{{host}}/v1/users", "../items/{{id}}").to_string(),
+            "https://{{host}}/items/{{id}}"
+        );
+        assert_eq!(
+            join("test.com:8080/v1/users", "items"),
+            RequestTarget::from("test.com:8080/v1/items")
+        );
+        assert_eq!(
+            join("/v1/users/1", "../2"),
+            RequestTarget::RelativeOrigin {
+                uri: "/v1/2".to_string()
+            }
+        );
+        assert_eq!(join("*", "/users"), RequestTarget::from("/users"));
+    }
+
+    #[test]
+    pub fn request_target_file_path() {
+        let target = RequestTarget::from("file:///tmp/data.json");
+        assert_eq!(
+            target,
+            RequestTarget::Absolute {
+                uri: "file:///tmp/data.json".to_string()
+            }
+        );
+        assert!(target.has_scheme());
+        assert_eq!(target.path(), "/tmp/data.json");
+        assert_eq!(target.as_file_path(), Some(PathBuf::from("/tmp/data.json")));
+        assert_eq!(
+            RequestTarget::from("FILE:///tmp/data.json"),
+            RequestTarget::Absolute {
+                uri: "FILE:///tmp/data.json".to_string()
+            }
+        );
+
+        let target = RequestTarget::from("file://localhost/tmp/my%20data.json");
+        assert_eq!(
+            target.as_file_path(),
+            Some(PathBuf::from("/tmp/my data.json"))
+        );
+
+        let target = RequestTarget::from("FILE:/tmp/data.json");
+        assert!(target.has_scheme());
+        assert_eq!(target.as_file_path(), Some(PathBuf::from("/tmp/data.json")));
+
+        // windows
+        let target = RequestTarget::from("file:///C:/Users/test/data.json");
+        assert!(target.has_scheme());
+        assert_eq!(
+            target.as_file_path(),
+            Some(PathBuf::from("C:/Users/test/data.json"))
+        );
+        let target = RequestTarget::from("file://server/share/data.json");
+        assert_eq!(
+            target.as_file_path(),
+            Some(PathBuf::from("//server/share/data.json"))
+        );
+
+        assert_eq!(
+            RequestTarget::from("https://test.com/tmp").as_file_path(),
+            None
+        );
+        assert_eq!(RequestTarget::from("/tmp/data.json").as_file_path(), None);
+
+        let FileParseResult { requests, errs } =
+            crate::Parser::parse("GET file:///tmp/data.json", false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(
+            requests[0].request_line.target.as_file_path(),
+            Some(PathBuf::from("/tmp/data.json"))
+        );
+    }
+
+    #[test]
+    pub fn request_target_path_and_query() {
+        let target = RequestTarget::from("https://test.com:8080/get/html?id=123&value=test");
+        assert_eq!(target.path(), "/get/html");
+        assert_eq!(target.query(), Some("id=123&value=test"));
+
+        let target = RequestTarget::from("test.com/api/v1/test");
+        assert_eq!(target.path(), "/api/v1/test");
+        assert_eq!(target.query(), None);
+
+        let target = RequestTarget::from("https://test.com");
+        assert_eq!(target.path(), "/");
+        assert_eq!(target.query(), None);
+
+        let target = RequestTarget::from("https://test.com?id=1#top");
+        assert_eq!(target.path(), "/");
+        assert_eq!(target.query(), Some("id=1"));
+
+        let target = RequestTarget::from("/api/v1/test?id=1");
+        assert_eq!(target.path(), "/api/v1/test");
+        assert_eq!(target.query(), Some("id=1"));
+
+        assert_eq!(RequestTarget::Asterisk.path(), "*");
+        assert_eq!(RequestTarget::Missing.path(), "");
+        assert_eq!(RequestTarget::Missing.query(), None);
+    }
+
+    #[test]
+    pub fn http_method_safe_and_idempotent() {
+        for method in ["GET", "HEAD", "OPTIONS", "TRACE"] {
+            assert!(HttpMethod::new(method).is_safe(), "{method} is safe");
+            assert!(
+                HttpMethod::new(method).is_idempotent(),
+                "{method} is idempotent"
+            );
+        }
+        for method in ["PUT", "DELETE"] {
+            assert!(!HttpMethod::new(method).is_safe(), "{method} is not safe");
+            assert!(
+                HttpMethod::new(method).is_idempotent(),
+                "{method} is idempotent"
+            );
+        }
+        for method in ["POST", "PATCH", "CONNECT", "LINK", "UNLINK", "PROPFIND"] {
+            assert!(!HttpMethod::new(method).is_safe(), "{method} is not safe");
+            assert!(
+                !HttpMethod::new(method).is_idempotent(),
+                "{method} is not idempotent"
+            );
+        }
+    }
+
+    #[test]
+    pub fn request_clone_without_body() {
+        let request = Request {
+            headers: vec![
+                Header::new("authorization", "Bearer secret"),
+                Header::new("Content-Type", "application/json"),
+            ],
+            body: RequestBody::Raw {
+                data: DataSource::Raw("{\"password\": \"secret\"}".to_string()),
+            },
+            ..Request::default()
+        };
+        let cloned = request.clone_without_body();
+        assert_eq!(cloned.body, RequestBody::None);
+        assert_eq!(cloned.headers, request.headers);
+        assert_eq!(
+            cloned.redacted_headers(&["Authorization"]),
+            vec![
+                Header::new("authorization", "***"),
+                Header::new("Content-Type", "application/json"),
+            ]
+        );
+
+        // file references are retained
+        let request = Request {
+            body: RequestBody::Multipart {
+                boundary: "b".to_string(),
+                parts: vec![
+                    Multipart {
+                        data: DataSource::Raw("secret".to_string()),
+                        disposition: DispositionField::new("text"),
+                        headers: vec![],
+                    },
+                    Multipart {
+                        data: DataSource::FromFilepath("./data.json".to_string()),
+                        disposition: DispositionField::new("file"),
+                        headers: vec![],
+                    },
+                ],
+            },
+            ..Request::default()
+        };
+        let RequestBody::Multipart { parts, .. } = request.clone_without_body().body else {
+            panic!("multipart body expected");
+        };
+        assert_eq!(parts[0].data, DataSource::Raw(String::new()));
+        assert_eq!(
+            parts[1].data,
+            DataSource::FromFilepath("./data.json".to_string())
+        );
+    }
+
+    #[test]
+    pub fn http_method_conversion() {
+        assert_eq!(HttpMethod::from(http::Method::GET), HttpMethod::GET);
+        assert_eq!(
+            http::Method::try_from(&HttpMethod::GET).unwrap(),
+            http::Method::GET
+        );
+        assert_eq!(HttpMethod::from(http::Method::PATCH), HttpMethod::PATCH);
+        assert_eq!(
+            http::Method::try_from(&HttpMethod::PATCH).unwrap(),
+            http::Method::PATCH
+        );
+
+        let custom = HttpMethod::CUSTOM("PROPFIND".to_string());
+        let method = http::Method::try_from(&custom).unwrap();
+        assert_eq!(method.as_str(), "PROPFIND");
+        assert_eq!(HttpMethod::from(method), custom);
+
+        assert!(http::Method::try_from(&HttpMethod::CUSTOM("IN VALID".to_string())).is_err());
+    }
+
+    #[test]
+    pub fn request_url() {
+        let request = |target: &str, headers: Vec<Header>| Request {
+            request_line: RequestLine {
+                target: RequestTarget::from(target),
+                ..RequestLine::default()
+            },
+            headers,
+            ..Request::default()
+        };
+
+        let env = Environment::from_iter([("host", "localhost:8080"), ("id", "42")]);
+
+        assert_eq!(
+            request("https://test.com/users/{{id}}", vec![]).url(Some(&env)),
+            Ok("https://test.com/users/42".parse::<http::Uri>().unwrap())
+        );
+        assert_eq!(
+            request("{{host}}/users", vec![]).url(Some(&env)),
+            Ok("http://localhost:8080/users".parse::<http::Uri>().unwrap())
+        );
+        assert_eq!(
+            request("test.com/users", vec![]).url(None),
+            Ok("http://test.com/users".parse::<http::Uri>().unwrap())
+        );
+
+        // relative targets are prefixed with the host
+        assert_eq!(
+            request("/users/{{id}}", vec![]).url(Some(&env)),
+            Ok("http://localhost:8080/users/42"
+                .parse::<http::Uri>()
+                .unwrap())
+        );
+        assert_eq!(
+            request("/users", vec![Header::new("Host", "https://api.test.com")]).url(Some(&env)),
+            Ok("https://api.test.com/users".parse::<http::Uri>().unwrap())
+        );
+        assert_eq!(
+            request("/users", vec![]).url(None),
+            Err(UrlError::MissingHost("/users".to_string()))
+        );
+
+        assert_eq!(
+            request("https://{{domain}}/users/{{id}}/{{other}}", vec![]).url(Some(&env)),
+            Err(UrlError::UnresolvedVariables(vec![
+                "domain".to_string(),
+                "other".to_string()
+            ]))
+        );
+        assert!(matches!(
+            request("https://test.com/{{id}}", vec![])
+                .url(Some(&Environment::from_iter([("id", "a b")]))),
+            Err(UrlError::InvalidUrl { .. })
+        ));
+    }
+
+    #[test]
+    pub fn multipart_constructors() {
+        assert_eq!(
+            Multipart::text("element-name", "Name"),
+            Multipart {
+                data: DataSource::Raw("Name".to_string()),
+                disposition: DispositionField::new("element-name"),
+                headers: vec![]
+            }
+        );
+        assert_eq!(
+            Multipart::file("data", "./files/data.json"),
+            Multipart {
+                data: DataSource::FromFilepath("./files/data.json".to_string()),
+                disposition: DispositionField::new_with_filename("data", Some("data.json")),
+                headers: vec![]
+            }
+        );
+        assert_eq!(
+            Multipart::file_with_type("data", "data.json", "application/json"),
+            Multipart {
+                data: DataSource::FromFilepath("data.json".to_string()),
+                disposition: DispositionField::new_with_filename("data", Some("data.json")),
+                headers: vec![Header::new("Content-Type", "application/json")]
+            }
+        );
+    }
+
+    #[test]
+    pub fn request_body_format_xml() {
+        let xml = concat!(
+            "<?xml version=\"1.0\"?><soap:Envelope xmlns:soap=\"http://a.b/c\"><soap:Body>\n",
+            "  <GetUser id='a>b'>\n<Id>{{id}}</Id><Empty></Empty><Flag/>",
+            "</GetUser></soap:Body></soap:Envelope>"
+        );
+        let body = RequestBody::Raw {
+            data: DataSource::Raw(xml.to_string()),
+        };
+        let expected = r#"<?xml version="1.0"?>
+<soap:Envelope xmlns:soap="http://a.b/c">
+  <soap:Body>
+    <GetUser id='a>b'>
+      <Id>{{id}}</Id>
+      <Empty></Empty>
+      <Flag/>
+    </GetUser>
+  </soap:Body>
+</soap:Envelope>"#;
+        assert_eq!(
+            body.format_xml(2),
+            RequestBody::Raw {
+                data: DataSource::Raw(expected.to_string())
+            }
+        );
+
+        // invalid xml and other bodies are not changed
+        for data in [
+            "<a><b></a></b>",
+            "<a>",
+            "text",
+            "{\"id\": {{id}}}",
+            "<a></a>text",
+        ] {
+            let body = RequestBody::Raw {
+                data: DataSource::Raw(data.to_string()),
+            };
+            assert_eq!(body.format_xml(2), body);
+        }
+        assert_eq!(RequestBody::None.format_xml(2), RequestBody::None);
+
+        let request = |content_type: &str| Request {
+            headers: vec![Header::new("Content-Type", content_type)],
+            ..Request::default()
+        };
+        assert!(request("application/xml").has_xml_body());
+        assert!(request("text/xml; charset=utf-8").has_xml_body());
+        assert!(request("application/soap+xml").has_xml_body());
+        assert!(!request("application/json").has_xml_body());
+    }
+
+    #[test]
+    pub fn request_effective_headers() {
+        let base_dir = std::path::Path::new(".");
+        let request = Request {
+            headers: vec![Header::new("Accept", "*/*")],
+            body: RequestBody::Raw {
+                data: DataSource::Raw("{\"id\": 1}".to_string()),
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            request.effective_headers(base_dir),
+            vec![
+                Header::new("Accept", "*/*"),
+                Header::new("Content-Type", "application/json"),
+                Header::new("Content-Length", "9"),
+            ]
+        );
+
+        // authored headers take precedence
+        let request = Request {
+            headers: vec![
+                Header::new("content-type", "text/plain"),
+                Header::new("content-length", "100"),
+            ],
+            ..request
+        };
+        assert_eq!(request.effective_headers(base_dir), request.headers);
+
+        let request = Request {
+            headers: vec![Header::new("Transfer-Encoding", "chunked")],
+            body: RequestBody::Raw {
+                data: DataSource::Raw("<id>1</id>".to_string()),
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            request.effective_headers(base_dir),
+            vec![
+                Header::new("Transfer-Encoding", "chunked"),
+                Header::new("Content-Type", "application/xml"),
+            ]
+        );
+
+        // the boundary of a multipart content type is completed
+        let request = Request {
+            headers: vec![Header::new("Content-Type", "multipart/form-data")],
+            body: RequestBody::Multipart {
+                boundary: "boundary".to_string(),
+                parts: vec![Multipart::text("text", "value")],
+            },
+            ..Default::default()
+        };
+        let headers = request.effective_headers(base_dir);
+        assert_eq!(
+            headers[0],
+            Header::new("Content-Type", "multipart/form-data; boundary=boundary")
+        );
+        assert_eq!(headers[1].key, "Content-Length");
+
+        // files which cannot be read have no length
+        let request = Request {
+            body: RequestBody::Raw {
+                data: DataSource::FromFilepath("missing/file.json".to_string()),
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            request.effective_headers(base_dir),
+            vec![Header::new("Content-Type", "application/json")]
+        );
+
+        assert_eq!(Request::default().effective_headers(base_dir), vec![]);
+    }
+
+    #[test]
+    pub fn request_body_strip_json_comments() {
+        let body = RequestBody::Raw {
+            data: DataSource::Raw(
+                r#"{
+  // the id of the user
+  "id": 1, // trailing comment
+  "url": "https://test.com/\"//",
+  /* block
+     comment */
+  "name": /* inline */ "test"
+}"#
+                .to_string(),
+            ),
+        };
+        assert_eq!(
+            body.strip_json_comments(),
+            RequestBody::Raw {
+                data: DataSource::Raw(
+                    r#"{
+  "id": 1,
+  "url": "https://test.com/\"//",
+  "name":  "test"
+}"#
+                    .to_string()
+                )
+            }
+        );
+
+        let request = Request {
+            headers: vec![Header::new(
+                "content-type",
+                "application/jsonc; charset=utf-8",
+            )],
+            body,
+            ..Default::default()
+        };
+        assert!(request.has_jsonc_body());
+        // the original body is kept
+        assert!(request.body.to_string().contains("// the id of the user"));
+        assert!(!Request::default().has_jsonc_body());
+
+        let body = RequestBody::Raw {
+            data: DataSource::FromFilepath("./input.jsonc".to_string()),
+        };
+        assert_eq!(body.strip_json_comments(), body);
+    }
+
+    #[test]
+    pub fn request_normalized() {
+        let first = crate::Parser::parse(
+            "GET https://test.com\nAccept:   */*   \nContent-Type: text/plain\n\nbody",
+            false,
+        )
+        .requests
+        .remove(0);
+        let second = Request {
+            request_line: RequestLine {
+                method: WithDefault::Some(HttpMethod::GET),
+                target: RequestTarget::from("https://test.com"),
+                http_version: WithDefault::Some(HttpVersion::default()),
+            },
+            headers: vec![
+                Header::new(" Accept", "*/*"),
+                Header::new("Content-Type", "text/plain"),
+            ],
+            body: RequestBody::Raw {
+                data: DataSource::Raw("body".to_string()),
+            },
+            settings: RequestSettings {
+                no_log: None,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_ne!(first, second);
+        assert_eq!(first.clone().normalized(), second.clone().normalized());
+        assert_eq!(
+            first.clone().normalized().normalized(),
+            first.clone().normalized()
+        );
+
+        let normalized = second.normalized();
+        assert_eq!(
+            normalized.request_line.method,
+            WithDefault::Some(HttpMethod::GET)
+        );
+        assert_eq!(normalized.settings.no_log, Some(false));
+
+        // semantic differences are kept
+        let other = Request {
+            body: RequestBody::Raw {
+                data: DataSource::Raw("body ".to_string()),
+            },
+            ..first.clone()
+        };
+        assert_ne!(other.normalized(), first.normalized());
+    }
+
+    #[test]
+    pub fn request_external_file_paths() {
+        let request = Request {
+            pre_request_scripts: vec![
+                PreRequestScript::FromFilepath {
+                    path: "./pre.js".to_string(),
+                    substitute_variables: false,
+                },
+                PreRequestScript::Script("request.variables.set('a', 'b')".to_string()),
+            ],
+            body: RequestBody::Multipart {
+                boundary: "boundary".to_string(),
+                parts: vec![
+                    Multipart::text("text", "value"),
+                    Multipart::file("data", "./data.json"),
+                ],
+            },
+            response_handler: Some(ResponseHandler::FromFilepath("./handler.js".to_string())),
+            save_response: Some(SaveResponse::RewriteFile("./out.json".into())),
+            ..Default::default()
+        };
+        assert_eq!(
+            request.external_file_paths(),
+            vec!["./pre.js", "./data.json", "./handler.js"]
+        );
+
+        let request = Request {
+            body: RequestBody::Raw {
+                data: DataSource::FromFilepath("./input.json".to_string()),
+            },
+            ..Default::default()
+        };
+        assert_eq!(request.external_file_paths(), vec!["./input.json"]);
+        assert!(Request::default().external_file_paths().is_empty());
+    }
+
+    #[test]
+    pub fn request_body_text() {
+        let request = |body: RequestBody| Request {
+            body,
+            ..Default::default()
+        };
+        let raw = request(RequestBody::Raw {
+            data: DataSource::Raw("{\"id\": 1}".to_string()),
+        });
+        assert_eq!(raw.body_text().as_deref(), Some("{\"id\": 1}"));
+        assert!(matches!(raw.body_text(), Some(Cow::Borrowed(_))));
+
+        let url_encoded = request(RequestBody::UrlEncoded {
+            url_encoded_params: vec![
+                UrlEncodedParam::new("key", "value"),
+                UrlEncodedParam::new("other", "a b"),
+            ],
+        });
+        assert_eq!(
+            url_encoded.body_text().as_deref(),
+            Some("key=value&other=a+b")
+        );
+
+        let file = request(RequestBody::Raw {
+            data: DataSource::FromFilepath("./input.json".to_string()),
+        });
+        assert_eq!(file.body_text(), None);
+
+        let multipart = request(RequestBody::Multipart {
+            boundary: "boundary".to_string(),
+            parts: vec![Multipart::text("field", "value")],
+        });
+        assert_eq!(multipart.body_text(), None);
+
+        assert_eq!(request(RequestBody::None).body_text(), None);
+        assert_eq!(request(RequestBody::Empty).body_text().as_deref(), Some(""));
+    }
+
+    #[test]
+    pub fn request_identity_hash() {
+        let request = Request {
+            name: Some("create user".to_string()),
+            comments: vec![Comment {
+                value: "creates a new user".to_string(),
+                kind: CommentKind::SingleTag,
+            }],
+            request_line: RequestLine {
+                method: WithDefault::Some(HttpMethod::POST),
+                target: RequestTarget::from("https://test.com/users"),
+                http_version: WithDefault::default(),
+            },
+            headers: vec![Header::new("Content-Type", "application/json")],
+            body: RequestBody::Raw {
+                data: DataSource::Raw("{\"id\": 1}".to_string()),
+            },
+            ..Default::default()
+        };
+
+        // descriptive parts are ignored
+        let described_differently = Request {
+            name: None,
+            comments: vec![Comment {
+                value: "another description".to_string(),
+                kind: CommentKind::DoubleSlash,
+            }],
+            settings: RequestSettings {
+                no_log: Some(true),
+                ..Default::default()
+            },
+            headers: vec![Header::new("content-type", "application/json")],
+            ..request.clone()
+        };
+        assert_eq!(
+            request.identity_hash(),
+            described_differently.identity_hash()
+        );
+
+        let different = |other: Request| assert_ne!(request.identity_hash(), other.identity_hash());
+        different(Request {
+            body: RequestBody::Raw {
+                data: DataSource::Raw("{\"id\": 2}".to_string()),
+            },
+            ..request.clone()
+        });
+        different(Request {
+            headers: vec![Header::new("Content-Type", "application/xml")],
+            ..request.clone()
+        });
+        different(Request {
+            request_line: RequestLine {
+                method: WithDefault::Some(HttpMethod::PUT),
+                ..request.request_line.clone()
+            },
+            ..request.clone()
+        });
+        different(Request {
+            request_line: RequestLine {
+                target: RequestTarget::from("https://test.com/items"),
+                ..request.request_line.clone()
+            },
+            ..request.clone()
+        });
+
+        let without_body = Request {
+            body: RequestBody::None,
+            ..request.clone()
+        };
+        let empty_body = Request {
+            body: RequestBody::Empty,
+            ..request.clone()
+        };
+        assert_ne!(without_body.identity_hash(), empty_body.identity_hash());
+    }
+
+    #[test]
+    pub fn request_summary() {
+        let request = Request {
+            request_line: RequestLine {
+                method: WithDefault::Some(HttpMethod::POST),
+                target: RequestTarget::from("https://test.com/users"),
+                http_version: WithDefault::default(),
+            },
+            headers: vec![
+                Header::new("Content-Type", "application/json"),
+                Header::new("Accept", "*/*"),
+            ],
+            body: RequestBody::Raw {
+                data: DataSource::Raw("{\"id\": 1}".to_string()),
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            request.summary(),
+            "POST https://test.com/users (json, 2 headers)"
+        );
+
+        let summary = |body: RequestBody| {
+            Request {
+                body,
+                headers: vec![Header::new("Accept", "*/*")],
+                ..request.clone()
+            }
+            .summary()
+        };
+        let raw = |data: &str| RequestBody::Raw {
+            data: DataSource::Raw(data.to_string()),
+        };
+        assert_eq!(
+            summary(raw("<id>1</id>")),
+            "POST https://test.com/users (xml, 1 header)"
+        );
+        assert_eq!(
+            summary(raw("id=1")),
+            "POST https://test.com/users (text, 1 header)"
+        );
+        assert_eq!(
+            summary(RequestBody::Raw {
+                data: DataSource::FromFilepath("./input.json".to_string())
+            }),
+            "POST https://test.com/users (file, 1 header)"
+        );
+        assert_eq!(
+            summary(RequestBody::UrlEncoded {
+                url_encoded_params: vec![UrlEncodedParam::new("id", "1")]
+            }),
+            "POST https://test.com/users (form, 1 header)"
+        );
+        assert_eq!(
+            summary(RequestBody::Multipart {
+                boundary: "boundary".to_string(),
+                parts: vec![Multipart::text("id", "1")]
+            }),
+            "POST https://test.com/users (multipart, 1 header)"
+        );
+
+        // the effective method is used if none is given
+        let request = Request {
+            request_line: RequestLine {
+                target: RequestTarget::from("/users"),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(request.summary(), "GET /users (no body, 0 headers)");
+    }
+
+    #[test]
+    pub fn request_with_base_url() {
+        let request = Request {
+            request_line: RequestLine {
+                method: WithDefault::Some(HttpMethod::POST),
+                target: RequestTarget::from("/users?id=1"),
+                http_version: WithDefault::default(),
+            },
+            headers: vec![Header::new("Accept", "*/*")],
+            ..Default::default()
+        };
+        let expected = RequestTarget::Absolute {
+            uri: "https://test.com/api/users?id=1".to_string(),
+        };
+        for base in [
+            "https://test.com/api",
+            "https://test.com/api/",
+            "https://test.com/api//",
+        ] {
+            let with_base = request.with_base_url(base);
+            assert_eq!(with_base.request_line.target, expected);
+            assert_eq!(with_base.request_line.method, request.request_line.method);
+            assert_eq!(with_base.headers, request.headers);
+        }
+
+        let absolute = Request {
+            request_line: RequestLine {
+                target: RequestTarget::from("https://prod.com/users"),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(absolute.with_base_url("https://test.com"), absolute);
+        let asterisk = Request {
+            request_line: RequestLine {
+                target: RequestTarget::Asterisk,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(asterisk.with_base_url("https://test.com"), asterisk);
+    }
+
+    #[test]
+    pub fn request_validate() {
+        let request = |method: HttpMethod, target: &str, headers: Vec<Header>, body| Request {
+            request_line: RequestLine {
+                method: WithDefault::Some(method),
+                target: RequestTarget::from(target),
+                ..RequestLine::default()
+            },
+            headers,
+            body,
+            ..Request::default()
+        };
+
+        let valid = request(
+            HttpMethod::POST,
+            "https://test.com/users",
+            vec![
+                Header::new("Content-Type", "application/json"),
+                Header::new("Content-Length", "2"),
+            ],
+            RequestBody::Raw {
+                data: DataSource::Raw("{}".to_string()),
+            },
+        );
+        assert_eq!(valid.validate(), vec![]);
+        assert_eq!(
+            request(HttpMethod::OPTIONS, "*", vec![], RequestBody::None).validate(),
+            vec![]
+        );
+
+        assert_eq!(
+            request(HttpMethod::GET, "test.com/users", vec![], RequestBody::None).validate(),
+            vec![ParseError::MissingUrlScheme("test.com/users".to_string())]
+        );
+        assert_eq!(
+            request(HttpMethod::GET, "*", vec![], RequestBody::None).validate(),
+            vec![ParseError::AsteriskTargetRequiresOptions("GET".to_string())]
+        );
+
+        let headers = vec![
+            Header::new("Accept", "text/html"),
+            Header::new("accept", "application/json"),
+            Header::new("ACCEPT", "*/*"),
+            Header::new("Content-Length", "abc"),
+        ];
+        let body = RequestBody::None;
+        assert_eq!(
+            request(HttpMethod::GET, "https://test.com", headers, body).validate(),
+            vec![
+                ParseError::DuplicateHeader("accept".to_string()),
+                ParseError::InvalidContentLength("abc".to_string())
+            ]
+        );
+
+        let headers = vec![
+            Header::new("Content-Type", "multipart/form-data; boundary=\"a b\""),
+            Header::new("Content-Length", "100"),
+        ];
+        let body = RequestBody::Multipart {
+            boundary: "boundary".to_string(),
+            parts: vec![],
+        };
+        assert_eq!(
+            request(HttpMethod::POST, "https://test.com", headers, body).validate(),
+            vec![
+                ParseError::InvalidMultipartBoundaryCharacter(" ".to_string()),
+                ParseError::MultipartBoundaryMismatch {
+                    header: "a b".to_string(),
+                    body: "boundary".to_string()
+                }
+            ]
+        );
+
+        let headers = vec![Header::new("Content-Length", "10")];
+        let body = RequestBody::Raw {
+            data: DataSource::Raw("12345".to_string()),
+        };
+        assert_eq!(
+            request(HttpMethod::POST, "https://test.com", headers, body).validate(),
+            vec![ParseError::ContentLengthMismatch {
+                header: 10,
+                actual: 5
+            }]
+        );
+    }
+
+    #[test]
+    pub fn disposition_field_quoting() {
+        assert!(!DispositionField::requires_quoting("file_1"));
+        assert!(DispositionField::requires_quoting("my file"));
+        assert!(DispositionField::requires_quoting("a;b"));
+        assert!(DispositionField::requires_quoting(""));
+
+        let name = "say \"hi\"; \\o/";
+        let quoted = DispositionField::quote(name);
+        assert_eq!(quoted, "\"say \\\"hi\\\"; \\\\o/\"");
+        assert_eq!(
+            DispositionField::parse(&format!("form-data; name={}; filename=\"a.txt\"", quoted)),
+            Ok(DispositionField::new_with_filename(name, Some("a.txt")))
+        );
+    }
+
+    #[test]
+    pub fn data_source_accessors() {
+        let raw = DataSource::Raw("text".to_string());
+        assert!(raw.is_raw());
+        assert!(!raw.is_file());
+        assert_eq!(raw.as_raw(), Some("text"));
+        assert_eq!(raw.file_path(), None);
+
+        let file = DataSource::<String>::FromFilepath("./data/input.json".to_string());
+        assert!(file.is_file());
+        assert!(!file.is_raw());
+        assert_eq!(file.as_raw(), None);
+        assert_eq!(
+            file.file_path(),
+            Some(std::path::Path::new("./data/input.json"))
+        );
+    }
+
+    #[test]
+    pub fn data_source_resolve_path() {
+        let base_dir = std::env::temp_dir().join("http_rest_file_resolve_path");
+        std::fs::create_dir_all(base_dir.join("data")).unwrap();
+        std::fs::write(base_dir.join("data").join("input.json"), "{}").unwrap();
+
+        let expected = base_dir.join("data").join("input.json");
+        for path in ["data\\input.json", "data/input.json", ".\\data\\input.json"] {
+            let file = DataSource::<String>::FromFilepath(path.to_string());
+            let resolved = file.resolve_path(&base_dir).unwrap();
+            assert_eq!(resolved.file_name(), expected.file_name());
+            assert_eq!(
+                std::fs::canonicalize(resolved).unwrap(),
+                expected.canonicalize().unwrap()
+            );
+            assert_eq!(file.as_bytes(&base_dir).unwrap(), b"{}");
+        }
+
+        assert_eq!(
+            DataSource::Raw("{}".to_string()).resolve_path(&base_dir),
+            None
+        );
+
+        #[cfg(windows)]
+        assert_eq!(
+            DataSource::<String>::FromFilepath("C:/data/input.json".to_string())
+                .resolve_path(&base_dir),
+            Some(PathBuf::from("C:\\data\\input.json"))
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            DataSource::<String>::FromFilepath("\\data\\input.json".to_string())
+                .resolve_path(&base_dir),
+            Some(PathBuf::from("/data/input.json"))
+        );
+    }
+
+    #[test]
+    pub fn comment_is_directive() {
+        let comment = |value: &str| Comment {
+            value: value.to_string(),
+            kind: CommentKind::SingleTag,
+        };
+        assert!(comment("@name=request").is_directive());
+        assert!(comment("  @no-log").is_directive());
+        assert!(comment("@connection-timeout 5").is_directive());
+
+        assert!(!comment("contact dev@example.com").is_directive());
+        assert!(!comment("@ not a directive").is_directive());
+        assert!(!comment("@").is_directive());
+        assert!(!comment("").is_directive());
+    }
+
+    #[test]
+    pub fn http_rest_file_extension_from_path() {
+        use std::path::Path;
+        assert_eq!(
+            HttpRestFileExtension::from_path(Path::new("test.http")),
+            Some(HttpRestFileExtension::Http)
+        );
+        assert_eq!(
+            HttpRestFileExtension::from_path(Path::new("/home/user/TEST.HTTP")),
+            Some(HttpRestFileExtension::Http)
+        );
+        assert_eq!(
+            HttpRestFileExtension::from_path(Path::new("test.Rest")),
+            Some(HttpRestFileExtension::Rest)
+        );
+        assert_eq!(HttpRestFileExtension::from_path(Path::new("test")), None);
+        assert_eq!(
+            HttpRestFileExtension::from_path(Path::new("test.txt")),
+            None
+        );
+        assert_eq!(HttpRestFileExtension::from_path(Path::new("")), None);
+        // backslashes separate directories on every platform
+        assert_eq!(
+            HttpRestFileExtension::from_path(Path::new("C:\\requests.d\\test.http")),
+            Some(HttpRestFileExtension::Http)
+        );
+        assert_eq!(
+            HttpRestFileExtension::from_path(Path::new("folder.rest\\test")),
+            None
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            let path = Path::new(std::ffi::OsStr::from_bytes(b"test.h\xfftp"));
+            assert_eq!(HttpRestFileExtension::from_path(path), None);
+        }
+
+        // round trip through the string representation
+        for extension in [HttpRestFileExtension::Http, HttpRestFileExtension::Rest] {
+            let path = format!("test.{}", extension.as_str());
+            assert_eq!(
+                HttpRestFileExtension::from_path(Path::new(&path)),
+                Some(extension.clone())
+            );
+            assert_eq!(extension.to_string(), extension.as_str());
+        }
+    }
 }
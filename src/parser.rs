@@ -1,5 +1,6 @@
 use self::model::{Multipart, RequestTarget, WithDefault};
 pub use crate::scanner::Scanner;
+pub use crate::scanner::TokenOptions;
 use crate::{
     error::{ErrorWithPartial, ParseError, ParseErrorDetails},
     model,
@@ -12,15 +13,123 @@ use crate::{
 };
 pub use http::Uri;
 use regex::Regex;
-use std::{fs, str::FromStr, collections::HashMap};
+use std::{collections::HashMap, fs, str::FromStr};
 
 pub const REQUEST_SEPARATOR: &str = "###";
 pub const META_COMMENT_SLASH: &str = "//";
 pub const META_COMMENT_TAG: &str = "#";
 pub const DEFAULT_MULTIPART_BOUNDARY: &str = "--boundary--";
 
+lazy_static::lazy_static! {
+    static ref HEADER: Regex = Regex::new(r"^([^:]+):\s*(.+)\s*").unwrap();
+    // the name is either given as '@name=<name>' or '@name <name>'
+    static ref META_NAME: regex::bytes::Regex =
+        regex::bytes::Regex::new(r"^\s*@name(?:\s*=\s*|\s+)(.*)").unwrap();
+    // a script ends at the first '%}'
+    static ref SCRIPT_END: regex::bytes::Regex = regex::bytes::Regex::new(r"^(.*?)%}").unwrap();
+}
+
 pub struct Parser {}
 
+/// Options to change the behaviour of the parser, the defaults match the JetBrains http client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// Print errors encountered during parsing to the console
+    pub print_errors: bool,
+    /// Allow request bodies given between a `<<< DELIMITER` line and a line only containing the
+    /// delimiter. The body is taken verbatim and may contain empty lines or lines starting with
+    /// `###`.
+    pub heredoc_bodies: bool,
+    /// Only the first empty line after the headers separates them from the body, further empty
+    /// lines at the start of a raw body are kept as part of the body. By default they are
+    /// removed.
+    pub preserve_leading_body_blanks: bool,
+    /// Report `ParseError::InconsistentRequestLineIndentation` as warning of the request if the
+    /// continuation lines of a request line mix tabs and spaces
+    pub warn_inconsistent_indentation: bool,
+    /// Report `ParseError::ImplicitMethod` as warning of the request for request lines without a
+    /// method such as `https://test.com`, the method still defaults to GET
+    pub require_explicit_method: bool,
+    /// Maximum number of parts of a single multipart body, parsing the body fails with
+    /// `ParseError::TooManyMultipartParts` if it contains more parts
+    pub max_multipart_parts: usize,
+    /// Maximum number of bytes of a single multipart body, parsing the body fails with
+    /// `ParseError::MultipartBodyTooLarge` once more bytes have been consumed
+    pub max_multipart_bytes: usize,
+    /// If the boundary of a multipart body differs from the boundary given in its `Content-Type`
+    /// header, use the boundary found in the body instead and report
+    /// `ParseError::MultipartBoundaryMismatch` as warning of the request. The `Content-Type`
+    /// header is kept as written.
+    pub lenient_multipart_boundary: bool,
+    /// Receives the errors instead of the console if `print_errors` is set
+    pub diagnostics_sink: Option<DiagnosticsSink>,
+}
+
+/// Receives every error encountered during parsing together with the error formatted for the
+/// console including its position and the source line
+#[derive(Clone)]
+pub struct DiagnosticsSink(std::sync::Arc<SinkFn>);
+
+type SinkFn = dyn Fn(&ParseErrorDetails, &str) + Send + Sync;
+
+impl DiagnosticsSink {
+    pub fn new<F>(sink: F) -> Self
+    where
+        F: Fn(&ParseErrorDetails, &str) + Send + Sync + 'static,
+    {
+        DiagnosticsSink(std::sync::Arc::new(sink))
+    }
+
+    fn emit(&self, details: &ParseErrorDetails, message: &str) {
+        (self.0)(details, message)
+    }
+}
+
+impl std::fmt::Debug for DiagnosticsSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DiagnosticsSink")
+    }
+}
+
+// sinks are only equal if they are clones of each other
+impl PartialEq for DiagnosticsSink {
+    fn eq(&self, other: &Self) -> bool {
+        std::sync::Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for DiagnosticsSink {}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        ParserOptions {
+            print_errors: false,
+            heredoc_bodies: false,
+            preserve_leading_body_blanks: false,
+            warn_inconsistent_indentation: false,
+            require_explicit_method: false,
+            max_multipart_parts: 1_000,
+            max_multipart_bytes: 16 * 1024 * 1024,
+            lenient_multipart_boundary: false,
+            diagnostics_sink: None,
+        }
+    }
+}
+
+/// Metrics collected while parsing a request file with `Parser::parse_with_metrics`
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ParseMetrics {
+    /// Number of successfully parsed requests
+    pub requests: usize,
+    /// Number of requests that could only be parsed partially
+    pub errors: usize,
+    /// Number of bytes of the scanned input
+    pub bytes_scanned: usize,
+    /// Time it took to parse the input
+    pub duration: std::time::Duration,
+}
+
 type ParseResult<T> = Result<(T, Vec<ParseErrorDetails>), ParseErrorDetails>;
 
 impl Parser {
@@ -28,23 +137,39 @@ impl Parser {
 
     #[allow(dead_code)]
     pub fn has_valid_extension<T: AsRef<std::path::Path>>(path: &T) -> bool {
-        match path.as_ref().extension() {
-            Some(extension) => Parser::REST_FILE_EXTENSIONS.contains(&extension.to_str().unwrap()),
-            _ => false,
-        }
+        HttpRestFileExtension::from_path(path.as_ref()).is_some()
     }
 
-    /// Parse the contents of a file into a `model::HttpRestFile`
+    /// Parse the contents of a file into a `model::HttpRestFile`, errors are printed to the
+    /// console
     /// # Arguments
     /// * `path` - path to a .http or .rest file
     pub fn parse_file(path: &std::path::Path) -> Result<model::HttpRestFile, ParseError> {
+        Parser::parse_file_with_options(
+            path,
+            &ParserOptions {
+                print_errors: true,
+                ..ParserOptions::default()
+            },
+        )
+    }
+
+    /// Parse the contents of a file same as `parse_file` but with the given options
+    /// # Arguments
+    /// * `path` - path to a .http or .rest file
+    /// * `options` - options of the parser
+    pub fn parse_file_with_options(
+        path: &std::path::Path,
+        options: &ParserOptions,
+    ) -> Result<model::HttpRestFile, ParseError> {
         if let Ok(content) = fs::read_to_string(path) {
-            let result = Parser::parse(&content, true);
+            let result = Parser::parse_with_options(&content, options);
             Ok(HttpRestFile {
                 requests: result.requests,
                 errs: result.errs,
                 path: Box::new(path.to_owned()),
                 extension: HttpRestFileExtension::from_path(path),
+                modeline: Parser::modeline(Parser::strip_bom(&content)).map(str::to_string),
             })
         } else {
             Err(ParseError::CouldNotReadRequestFile(path.to_owned()))
@@ -58,23 +183,177 @@ impl Parser {
     /// * `string` - string to parse
     /// * `print_errors` - if set to true prints errors to the console
     pub fn parse(string: &str, print_errors: bool) -> model::FileParseResult {
+        Parser::parse_with_options(
+            string,
+            &ParserOptions {
+                print_errors,
+                ..ParserOptions::default()
+            },
+        )
+    }
+
+    /// Parse the contents of a request file same as `parse_with_options` and additionally
+    /// return metrics such as the number of requests and the time it took to parse them.
+    /// # Arguments
+    /// * `string` - string to parse
+    /// * `options` - options of the parser
+    #[cfg(feature = "metrics")]
+    pub fn parse_with_metrics(
+        string: &str,
+        options: &ParserOptions,
+    ) -> (model::FileParseResult, ParseMetrics) {
+        let start = std::time::Instant::now();
+        let result = Parser::parse_with_options(string, options);
+        let metrics = ParseMetrics {
+            requests: result.requests.len(),
+            errors: result.errs.len(),
+            bytes_scanned: string.len(),
+            duration: start.elapsed(),
+        };
+        (result, metrics)
+    }
+
+    /// Parse the contents of a request file as string same as `parse` but with the given
+    /// options.
+    /// # Arguments
+    /// * `string` - string to parse
+    /// * `options` - options of the parser
+    pub fn parse_with_options(string: &str, options: &ParserOptions) -> model::FileParseResult {
+        let string = Parser::strip_bom(string);
         let mut scanner = Scanner::new(string);
+        if Parser::modeline(string).is_some() {
+            scanner.skip_to_next_line();
+        }
 
         let mut requests: Vec<model::Request> = Vec::new();
         let mut errs: Vec<ErrorWithPartial> = Vec::new();
 
+        Parser::parse_requests(&mut scanner, options, |result| match result {
+            Ok(request) => requests.push(request),
+            Err(err_with_partial) => errs.push(err_with_partial),
+        });
+
+        // references can only be checked once all names of the file are known
+        let names = requests
+            .iter()
+            .filter_map(|request| request.name.clone())
+            .chain(
+                errs.iter()
+                    .filter_map(|err| err.partial_request.name.clone()),
+            )
+            .collect::<Vec<String>>();
+        for request in requests.iter_mut() {
+            let unknown = Parser::unknown_references(&names, &request.settings.depends_on);
+            request.warnings.extend(unknown);
+        }
+        for err in errs.iter_mut() {
+            let partial = &mut err.partial_request;
+            let unknown = Parser::unknown_references(&names, &partial.settings.depends_on);
+            partial.warnings.extend(unknown);
+        }
+
+        if !errs.is_empty() && options.print_errors {
+            match options.diagnostics_sink {
+                Some(ref sink) => {
+                    for details in errs.iter().flat_map(|err| err.details.iter()) {
+                        sink.emit(details, &Parser::pretty_err_string(&scanner, details));
+                    }
+                }
+                None => eprintln!("{}", Parser::get_pretty_print_errs(&scanner, errs.iter())),
+            }
+        }
+        FileParseResult { requests, errs }
+    }
+
+    /// Check if the contents of a request file are valid without keeping the parsed requests.
+    /// Returns the details of all errors encountered, same as the errors of `parse`, an empty
+    /// list means the file is valid. Warnings of the requests are not included.
+    /// # Arguments
+    /// * `string` - string to check
+    pub fn check(string: &str) -> Vec<ParseErrorDetails> {
+        Parser::parse_with_options(string, &ParserOptions::default())
+            .errs
+            .into_iter()
+            .flat_map(|err| err.details)
+            .collect()
+    }
+
+    // the names of `depends_on` which are not within `names`
+    fn unknown_references(names: &[String], depends_on: &[String]) -> Vec<ParseErrorDetails> {
+        depends_on
+            .iter()
+            .filter(|name| !names.contains(name))
+            .map(|name| ParseErrorDetails::from(ParseError::UnknownRequestReference(name.clone())))
+            .collect()
+    }
+
+    // editors may save files with a leading utf-8 byte order mark which is not part of the
+    // content
+    fn strip_bom(string: &str) -> &str {
+        string.strip_prefix('\u{FEFF}').unwrap_or(string)
+    }
+
+    // Returns the first line if it is a shebang ('#!/usr/bin/env http') or an editor modeline
+    // ('# -*- mode: restclient -*-' or '# vim: set ft=http:'), such a line is not a comment of
+    // the first request
+    fn modeline(string: &str) -> Option<&str> {
+        let line = string.lines().next()?.trim_end();
+        let comment = line
+            .strip_prefix(META_COMMENT_SLASH)
+            .or_else(|| line.strip_prefix(META_COMMENT_TAG))?
+            .trim();
+        let is_emacs_modeline = comment.starts_with("-*-") && comment.ends_with("-*-");
+        let is_vim_modeline = comment.starts_with("vim:") || comment.starts_with("vi:");
+        if line.starts_with("#!") || is_emacs_modeline || is_vim_modeline {
+            Some(line)
+        } else {
+            None
+        }
+    }
+
+    // Parse all requests of the scanner and pass the result of each one to `on_result` in order,
+    // the index of each request is set according to its position
+    fn parse_requests<F>(scanner: &mut Scanner, options: &ParserOptions, mut on_result: F)
+    where
+        F: FnMut(Result<model::Request, ErrorWithPartial>),
+    {
+        let mut index = 0;
+        let mut default_version: Option<model::HttpVersion> = None;
+        // everything before the request line of the first request is the file header
+        let mut in_file_header = true;
         loop {
             scanner.skip_empty_lines_and_ws();
 
             if scanner.is_done() {
                 break;
             }
-            match Parser::parse_request(&mut scanner) {
-                Ok(request) => {
-                    requests.push(request);
+            let result = Parser::parse_request_in_file(
+                scanner,
+                options,
+                &mut default_version,
+                in_file_header,
+            );
+            in_file_header = false;
+            match result {
+                // a request separator at the end of the file without anything following it is
+                // not reported as a missing request
+                Err(err_with_partial)
+                    if scanner.is_done()
+                        && !err_with_partial.partial_request.is_meaningful()
+                        && err_with_partial
+                            .details
+                            .iter()
+                            .all(|detail| detail.error == ParseError::MissingRequestTargetLine) => {
+                }
+                Ok(mut request) => {
+                    request.index = index;
+                    index += 1;
+                    on_result(Ok(request));
                 }
-                Err(err_with_partial) => {
-                    errs.push(err_with_partial);
+                Err(mut err_with_partial) => {
+                    err_with_partial.partial_request.index = index;
+                    index += 1;
+                    on_result(Err(err_with_partial));
                 }
             }
             scanner.skip_empty_lines();
@@ -85,13 +364,7 @@ impl Parser {
             }
 
             // go to next ### that should start a request
-            while let Some(line) = scanner.peek_line() {
-                if line.trim_start().starts_with(REQUEST_SEPARATOR) {
-                    break;
-                } else {
-                    scanner.skip_to_next_line();
-                }
-            }
+            scanner.skip_to_next_separator(REQUEST_SEPARATOR);
 
             scanner.skip_empty_lines();
             scanner.skip_ws();
@@ -100,45 +373,118 @@ impl Parser {
                 break;
             }
         }
+    }
+
+    /// Parse the string into a lossless `syntax::SyntaxTree` that keeps every line of the input.
+    /// Other than `parse` no model is created, the tree allows editing single lines while the
+    /// rest of the file stays unchanged.
+    pub fn parse_lossless(string: &str) -> crate::syntax::SyntaxTree {
+        crate::syntax::SyntaxTree::parse(string)
+    }
+
+    /// Parse a string containing a single request. Other than `parse` only the first request is
+    /// parsed, anything after a following `REQUEST_SEPARATOR` is ignored.
+    /// # Arguments
+    /// * `string` - string containing the request
+    #[allow(clippy::result_large_err)]
+    pub fn parse_request_str(string: &str) -> Result<model::Request, ErrorWithPartial> {
+        let mut scanner = Scanner::new(Parser::strip_bom(string));
+        scanner.skip_empty_lines_and_ws();
+        Parser::parse_request(&mut scanner)
+    }
 
-        if !errs.is_empty() && print_errors {
-            eprintln!("{}", Parser::get_pretty_print_errs(&scanner, errs.iter()));
+    /// Parse a block of headers in the form of `<Key>: <Value>`, one per line, for example
+    /// headers copied from a browser. Parsing stops at the first blank line, anything after it
+    /// is ignored. Same as within a request a line that looks like a body (starting with '{',
+    /// '[' or '<') also ends the headers. An invalid header name or value results in an error.
+    /// # Arguments
+    /// * `string` - string containing the headers
+    pub fn parse_headers_str(string: &str) -> Result<Vec<model::Header>, ParseErrorDetails> {
+        let mut scanner = Scanner::new(string);
+        let mut warnings: Vec<ParseErrorDetails> = Vec::new();
+        let headers = Parser::parse_headers(&mut scanner, &mut warnings)?;
+        match warnings.into_iter().next() {
+            Some(err) => Err(err),
+            None => Ok(headers),
         }
-        FileParseResult { requests, errs }
     }
 
     /// Parse a single request either until no further lines are present or a `REQUEST_SEPARATOR`
     /// is encountered
     pub fn parse_request(scanner: &mut Scanner) -> Result<model::Request, ErrorWithPartial> {
+        Parser::parse_request_with_options(scanner, &ParserOptions::default())
+    }
+
+    /// Parse a single request same as `parse_request` but with the given options
+    #[allow(clippy::result_large_err)]
+    pub fn parse_request_with_options(
+        scanner: &mut Scanner,
+        options: &ParserOptions,
+    ) -> Result<model::Request, ErrorWithPartial> {
+        Parser::parse_request_in_file(scanner, options, &mut None, true)
+    }
+
+    // Parse a single request of a file, `default_version` is the version given by the
+    // '@http-version' directive of the file header. It is only updated if the request starts
+    // `in_file_header`, later directives are reported as warning and ignored.
+    #[allow(clippy::result_large_err)]
+    fn parse_request_in_file(
+        scanner: &mut Scanner,
+        options: &ParserOptions,
+        default_version: &mut Option<model::HttpVersion>,
+        in_file_header: bool,
+    ) -> Result<model::Request, ErrorWithPartial> {
         let mut comments = Vec::new();
         let mut name: Option<String> = None;
         let mut parse_errs: Vec<ParseErrorDetails> = Vec::new();
+        // problems which do not fail the request, they are kept with the parsed request
+        let mut warnings: Vec<ParseErrorDetails> = Vec::new();
         let mut settings = RequestSettings::default();
-        let mut pre_request_script: Option<model::PreRequestScript> = None;
+        let mut pre_request_scripts: Vec<model::PreRequestScript> = Vec::new();
 
         scanner.skip_empty_lines();
 
         loop {
-            // preq-request-scrip
+            // pre-request scripts, a request can have multiple which are kept in order
             if scanner.peek().map_or(false, |c| c == &'<') {
-                if let Ok(result) = Parser::parse_pre_request_script(scanner) {
-                    pre_request_script = result;
-                };
+                match Parser::parse_pre_request_script(scanner) {
+                    Ok(Some(result)) => pre_request_scripts.push(result),
+                    Ok(None) => {}
+                    // the rest of the line cannot be parsed, continue with the next one
+                    Err(parse_error) => {
+                        parse_errs.push(parse_error);
+                        scanner.skip_to_next_line();
+                    }
+                }
                 continue;
             }
-            match Parser::parse_meta_comment_line(scanner) {
+            let line_start = scanner.get_cursor();
+            match Parser::parse_meta_comment_line(scanner, &mut warnings) {
                 Some(Ok(SettingsEntry::NameEntry(entry_name))) => {
                     if !entry_name.is_empty() {
                         name = Some(entry_name);
                     }
                     continue;
                 }
+                Some(Ok(SettingsEntry::HttpVersion(version))) => {
+                    if in_file_header {
+                        *default_version = Some(version);
+                    } else {
+                        warnings.push(ParseErrorDetails::new_with_position(
+                            ParseError::MisplacedHttpVersionDirective,
+                            (line_start, Some(scanner.get_cursor())),
+                        ));
+                    }
+                    continue;
+                }
                 Some(Ok(entry)) => {
                     settings.set_entry(&entry);
                     continue;
                 }
+                // invalid meta directives are recorded but parsing of the request continues
                 Some(Err(parse_error)) => {
                     parse_errs.push(parse_error);
+                    continue;
                 }
                 None => (), // ignore
             }
@@ -172,15 +518,24 @@ impl Parser {
                     settings,
                     request_line: None,
                     body: None,
-                    pre_request_script,
+                    pre_request_scripts,
                     save_response: None,
                     headers: None,
                     response_handler: None,
+                    index: 0,
+                    warnings,
                 },
                 details: parse_errs,
             });
         }
 
+        // a '###' comment repeating the name given with @name is redundant and dropped
+        if let Some(ref name) = name {
+            comments.retain(|comment| {
+                comment.kind != CommentKind::RequestSeparator || comment.value.trim() != name
+            });
+        }
+
         // if no name has been found with meta tag @name=, set name from a comment starting with
         // '###' if there is any
         if name.is_none() {
@@ -195,10 +550,29 @@ impl Parser {
             }
         }
 
-        let request_line: Option<RequestLine> = match Parser::parse_request_line(scanner) {
+        let request_line_start = scanner.get_cursor();
+        let parsed_request_line = Parser::parse_request_line(scanner, options, &mut warnings);
+        let request_line: Option<RequestLine> = match parsed_request_line {
             Ok((mut request_line, errs)) => {
                 parse_errs.extend(errs);
-                if pre_request_script.as_ref().is_some_and(|prs| prs.to_string().contains("request.variables.set")) {
+                if options.require_explicit_method && request_line.method.is_default() {
+                    warnings.push(ParseErrorDetails::new_with_position(
+                        ParseError::ImplicitMethod,
+                        (request_line_start, Some(scanner.get_cursor())),
+                    ));
+                }
+                // an explicit version on the request line overrides the default of the file
+                if let (WithDefault::Default(_), Some(version)) =
+                    (&request_line.http_version, default_version.as_ref())
+                {
+                    request_line.http_version = WithDefault::Some(version.clone());
+                }
+                let scripts = pre_request_scripts
+                    .iter()
+                    .map(|prs| prs.to_string())
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                if scripts.contains("request.variables.set") {
                     lazy_static::lazy_static! {
                         static ref VAR_SET: Regex = Regex::new(r#"request\.variables\.set."(?<key>\w+)", "(?<value>\w+)""#).unwrap();
                         static ref HANDLE_BARS: Regex = Regex::new(r"\{\{(\w+)\}\}").unwrap();
@@ -206,10 +580,8 @@ impl Parser {
 
                     let mut kv: HashMap<String, String> = HashMap::new();
 
-                    for captures in VAR_SET.captures_iter(&pre_request_script.clone().unwrap().to_string()) {
-                        let capture = |index| {
-                            captures.get(index).map(|c| c.as_str().to_string())
-                        };
+                    for captures in VAR_SET.captures_iter(&scripts) {
+                        let capture = |index| captures.get(index).map(|c| c.as_str().to_string());
 
                         println!("{captures:?}");
 
@@ -223,22 +595,25 @@ impl Parser {
                             let mut new_uri = uri.clone();
 
                             for captures in HANDLE_BARS.captures_iter(&uri) {
-                                let capture = |index| {
-                                    captures.get(index).map(|c| c.as_str().to_string())
-                                };
+                                let capture =
+                                    |index| captures.get(index).map(|c| c.as_str().to_string());
 
-                                if let Some(var_name) = capture(1) {
+                                if let (Some(placeholder), Some(var_name)) =
+                                    (capture(0), capture(1))
+                                {
                                     if let Some(var) = kv.get(&var_name) {
-                                        new_uri = new_uri.
-                                            replace(&capture(1).unwrap(), var).
-                                            replace("{", "").
-                                            replace("}", "");
+                                        // only replace the placeholder itself, escaped braces
+                                        // '\{\{' are not matched
+                                        new_uri = new_uri.replace(&placeholder, var);
                                     }
                                 }
                             }
 
-                            request_line.target = RequestTarget::Absolute { uri: new_uri };
-                        },
+                            // escaped braces denote literal braces once substituted
+                            request_line.target = RequestTarget::Absolute {
+                                uri: model::unescape_braces(&new_uri),
+                            };
+                        }
                         _ => {}
                     }
                 }
@@ -259,13 +634,15 @@ impl Parser {
                         name,
                         comments,
                         settings,
-                        pre_request_script,
+                        pre_request_scripts,
                         request_line,
                         // no headers nor body parsed
                         headers: vec![],
                         body: RequestBody::None,
                         response_handler: None,
                         save_response: None,
+                        index: 0,
+                        warnings,
                     };
                     return Ok(request_node);
                 } else {
@@ -275,11 +652,13 @@ impl Parser {
                             comments,
                             settings,
                             response_handler: None,
-                            pre_request_script: None,
+                            pre_request_scripts: vec![],
                             request_line: None,
                             headers: None,
                             save_response: None,
                             body: None,
+                            index: 0,
+                            warnings,
                         },
                         details: parse_errs,
                     });
@@ -287,7 +666,7 @@ impl Parser {
             }
         }
 
-        let headers = match Parser::parse_headers(scanner) {
+        let headers = match Parser::parse_headers(scanner, &mut warnings) {
             Ok(headers) => headers,
             Err(parse_err) => {
                 parse_errs.push(parse_err);
@@ -296,25 +675,51 @@ impl Parser {
                         name,
                         comments,
                         settings,
-                        pre_request_script,
+                        pre_request_scripts,
                         request_line,
                         headers: None,
                         body: None,
                         response_handler: None,
                         save_response: None,
+                        index: 0,
+                        warnings,
                     },
                     details: parse_errs,
                 });
             }
         };
 
+        // the headers ended on a line that looks like the body without a blank line before it,
+        // the line is parsed as start of the body
+        if !scanner.is_done() && scanner.peek() != Some(&'\n') {
+            warnings.push(ParseErrorDetails::new_with_position(
+                ParseError::MissingBlankLineBeforeBody(scanner.peek_line().unwrap_or_default()),
+                (scanner.get_cursor(), None),
+            ));
+        }
+
+        let headers_end = scanner.get_cursor();
         scanner.skip_empty_lines();
+        // every skipped character is an empty line, the first one separates headers and body
+        let leading_blanks = (scanner.get_cursor() - headers_end).saturating_sub(1);
 
-        let (body, body_errs) = match Parser::parse_body(scanner, &headers) {
+        let parsed_body = Parser::parse_body(scanner, &headers, &settings, options, &mut warnings);
+        let (mut body, body_errs) = match parsed_body {
             Ok(body) => (body, Vec::<ParseErrorDetails>::new()),
             Err((body, errs)) => (body, errs),
         };
 
+        if options.preserve_leading_body_blanks && leading_blanks > 0 {
+            if let RequestBody::Raw {
+                data: DataSource::Raw(ref mut data),
+            } = body
+            {
+                if !data.is_empty() {
+                    data.insert_str(0, &"\n".repeat(leading_blanks));
+                }
+            }
+        }
+
         if !body_errs.is_empty() {
             parse_errs.extend(body_errs.clone());
         }
@@ -328,12 +733,14 @@ impl Parser {
                         name,
                         comments,
                         settings,
-                        pre_request_script,
+                        pre_request_scripts,
                         request_line,
                         headers: Some(headers),
                         body: Some(body),
                         response_handler: None,
                         save_response: None,
+                        index: 0,
+                        warnings,
                     },
                     details: parse_errs,
                 });
@@ -351,12 +758,14 @@ impl Parser {
                         name,
                         comments,
                         settings,
-                        pre_request_script,
+                        pre_request_scripts,
                         request_line,
                         headers: Some(headers),
                         body: Some(body),
                         response_handler,
                         save_response: None,
+                        index: 0,
+                        warnings,
                     },
                     details: parse_errs,
                 });
@@ -370,12 +779,14 @@ impl Parser {
                     name,
                     comments,
                     settings,
-                    pre_request_script,
+                    pre_request_scripts,
                     request_line,
                     headers: Some(headers),
                     body: Some(body),
                     response_handler,
                     save_response,
+                    index: 0,
+                    warnings,
                 },
                 details: parse_errs,
             });
@@ -389,19 +800,21 @@ impl Parser {
             headers,
             body,
             settings,
-            pre_request_script,
+            pre_request_scripts,
             response_handler,
             save_response,
+            index: 0,
+            warnings,
         };
 
         // if no name set we use the first comment as name
-        // Only do this for comments not containing meta sign @ as these specify the request
-        // settings
+        // Only do this for comments that are no meta directives such as '@name' as these specify
+        // the request settings
         if request_node.name.is_none() && !request_node.comments.is_empty() {
             let name_pos = request_node
                 .comments
                 .iter()
-                .position(|com| !com.value.contains('@'));
+                .position(|com| !com.is_directive());
             if let Some(name_pos) = name_pos {
                 let name_comment = request_node.comments.remove(name_pos);
                 request_node.name = Some(name_comment.value);
@@ -410,6 +823,14 @@ impl Parser {
         Ok(request_node)
     }
 
+    /// Render the errors of a previous parse of `source` in the same format as printed to the
+    /// console by `parse` with `print_errors` set. The positions of the errors are resolved
+    /// against `source` which should be the exact string that has been parsed.
+    pub fn render_errors(source: &str, errs: &[ErrorWithPartial]) -> String {
+        let scanner = Scanner::new(source);
+        Parser::get_pretty_print_errs(&scanner, errs.iter())
+    }
+
     /// Get string for printing errors to the console
     fn get_pretty_print_errs<'a, T>(scanner: &Scanner, errs: T) -> String
     where
@@ -432,7 +853,9 @@ impl Parser {
                 "Position: {}:{}\n",
                 error_context.line, error_context.column
             ));
-            result.push_str(&error_context.context);
+            result.push_str(&error_context.source_line);
+            result.push('\n');
+            result.push_str(&error_context.caret);
         }
         result
     }
@@ -442,10 +865,14 @@ impl Parser {
     fn parse_meta_name(scanner: &mut Scanner) -> Result<Option<String>, ParseErrorDetails> {
         scanner.skip_ws();
 
-        let name_regex = "\\s*@name\\s*=\\s*(.*)";
-        if let Ok(Some(captures)) = scanner.match_regex_forward(name_regex) {
-            let name = captures.first().unwrap().trim().to_string();
-            Ok(Some(name))
+        if let Ok(Some(captures)) = scanner.match_compiled_regex_forward(&META_NAME) {
+            let name = captures.first().unwrap().trim();
+            // matching quotes around the name are removed, quotes within the name are kept
+            let name = match name.as_bytes() {
+                [first @ (b'"' | b'\''), .., last] if first == last => &name[1..(name.len() - 1)],
+                _ => name,
+            };
+            Ok(Some(name.to_string()))
         } else {
             Ok(None)
         }
@@ -472,6 +899,7 @@ impl Parser {
     /// match a comment line after '###', '//' or '##' has been stripped from it
     fn parse_meta_comment_line(
         scanner: &mut Scanner,
+        warnings: &mut Vec<ParseErrorDetails>,
     ) -> Option<Result<SettingsEntry, ParseErrorDetails>> {
         scanner.skip_ws();
 
@@ -482,7 +910,11 @@ impl Parser {
             return None;
         }
 
-        let mut line_scanner = Scanner::new(&peek_line.unwrap());
+        let peek_line = peek_line.unwrap();
+        let line_start = scanner.get_cursor();
+        let line_end = line_start + peek_line.chars().count();
+
+        let mut line_scanner = Scanner::new(&peek_line);
         line_scanner.skip_ws();
 
         if line_scanner.match_str_forward(META_COMMENT_SLASH)
@@ -502,14 +934,71 @@ impl Parser {
                 return None;
             }
 
-            let result: Option<Result<SettingsEntry, ParseErrorDetails>> =
-                match line.unwrap().trim() {
-                    "@no-cookie-jar" => Some(Ok(SettingsEntry::NoCookieJar)),
-                    "@no-redirect" => Some(Ok(SettingsEntry::NoRedirect)),
-                    "@no-log" => Some(Ok(SettingsEntry::NoLog)),
-                    // Non matching meta comment lines are taken as regular comments
-                    _ => None,
-                };
+            let line = line.unwrap();
+            let line = line.trim();
+            // directives with a value have the form '@<directive> <value>'
+            let (directive, value) = line
+                .split_once(WS_CHARS)
+                .map_or((line, ""), |(directive, value)| (directive, value.trim()));
+
+            // an invalid value of a metadata directive is only reported as warning, the line is
+            // kept as a regular comment
+            let mut warn = |err: ParseError| {
+                warnings.push(ParseErrorDetails::new_with_position(
+                    err,
+                    (line_start, Some(line_end)),
+                ));
+                None
+            };
+            let result: Option<Result<SettingsEntry, ParseErrorDetails>> = match (directive, value)
+            {
+                ("@no-cookie-jar", "") => Some(Ok(SettingsEntry::NoCookieJar)),
+                ("@no-redirect", "") => Some(Ok(SettingsEntry::NoRedirect)),
+                ("@no-log", "") => Some(Ok(SettingsEntry::NoLog)),
+                ("@connection-timeout", value) => match Parser::parse_timeout(directive, value) {
+                    Ok(timeout) => Some(Ok(SettingsEntry::ConnectionTimeout(timeout))),
+                    Err(err) => warn(err),
+                },
+                ("@socket-timeout", value) => match Parser::parse_timeout(directive, value) {
+                    Ok(timeout) => Some(Ok(SettingsEntry::SocketTimeout(timeout))),
+                    Err(err) => warn(err),
+                },
+                ("@no-proxy", "") => Some(Ok(SettingsEntry::NoProxy)),
+                ("@raw-body", "") => Some(Ok(SettingsEntry::RawBody)),
+                ("@disabled", "") => Some(Ok(SettingsEntry::Disabled)),
+                ("@chunked", "") => Some(Ok(SettingsEntry::Chunked)),
+                ("@ref", value) if !value.is_empty() => {
+                    Some(Ok(SettingsEntry::Ref(value.to_string())))
+                }
+                ("@auth", value) => match model::AuthMode::from_str(value) {
+                    Ok(mode) => Some(Ok(SettingsEntry::Auth(mode))),
+                    Err(err) => warn(err),
+                },
+                ("@import", value) => match model::Import::from_str(value) {
+                    Ok(import) => Some(Ok(SettingsEntry::Import(import))),
+                    Err(err) => warn(err),
+                },
+                ("@expect", value) => match model::Expectation::from_str(value) {
+                    Ok(expectation) => Some(Ok(SettingsEntry::Expect(expectation))),
+                    Err(err) => warn(err),
+                },
+                ("@use-os-credentials", "") => {
+                    Some(Ok(SettingsEntry::Auth(model::AuthMode::OsCredentials)))
+                }
+                ("@http-version", value) => Some(
+                    model::HttpVersion::from_str(value)
+                        .map(SettingsEntry::HttpVersion)
+                        .map_err(|err| {
+                            ParseErrorDetails::new_with_position(err, (line_start, Some(line_end)))
+                        }),
+                ),
+                ("@proxy", value) => match Parser::parse_proxy(value) {
+                    Ok(proxy) => Some(Ok(SettingsEntry::Proxy(proxy))),
+                    Err(err) => warn(err),
+                },
+                // Non matching meta comment lines are taken as regular comments
+                _ => None,
+            };
 
             if result.is_some() {
                 scanner.skip_to_next_line();
@@ -521,6 +1010,26 @@ impl Parser {
         None
     }
 
+    /// Parse the value of a timeout directive such as `@connection-timeout 10`. The timeout has to
+    /// be given as whole number.
+    fn parse_timeout(directive: &str, value: &str) -> Result<u64, ParseError> {
+        value
+            .parse::<u64>()
+            .map_err(|_| ParseError::InvalidTimeoutValue {
+                setting: directive.to_string(),
+                value: value.to_string(),
+            })
+    }
+
+    /// Parse the value of a `@proxy` directive. The proxy has to be an URL with a scheme and a host
+    /// such as `http://proxy:8080`.
+    fn parse_proxy(value: &str) -> Result<String, ParseError> {
+        match url::Url::parse(value) {
+            Ok(url) if url.has_host() => Ok(value.to_string()),
+            _ => Err(ParseError::InvalidProxyUrl(value.to_string())),
+        }
+    }
+
     /// Parse pre request scripts, which are either a path to a javascript file or blocks of text containing javascript code within '{% %}' blocks
     /// The full script is parsed as a single string if '{% %}' blocks are present otherwise a path is parsed.
     /// See also the `parse_response_handler` which parses similarly code that handles a response.
@@ -531,8 +1040,10 @@ impl Parser {
             return Ok(None);
         };
         let start_pos = scanner.get_pos();
+        // '<@' marks a script file whose variables should be substituted
+        let substitute_variables = scanner.take(&'@');
         scanner.skip_ws();
-        if !scanner.match_str_forward("{%") {
+        if substitute_variables || !scanner.match_str_forward("{%") {
             // if no starting script is found then a handler script should be presnet
             let line = scanner.get_line_and_advance();
             if line.is_none() {
@@ -545,26 +1056,34 @@ impl Parser {
 
                 return Err(details);
             }
-            return Ok(Some(model::PreRequestScript::FromFilepath(
-                line.unwrap().trim().to_string(),
-            )));
+            return Ok(Some(model::PreRequestScript::FromFilepath {
+                path: line.unwrap().trim().to_string(),
+                substitute_variables,
+            }));
         }
 
         let mut found: bool = false;
         let mut lines: Vec<String> = Vec::new();
         loop {
-            if let Ok(Some(result)) = scanner.match_regex_forward("(.*)%}") {
-                if result.len() == 1 {
-                    lines.push(result[0].to_string());
-                    found = true;
-                    break;
-                } else {
+            // the script ends at the first '%}', whitespace or a comment such as '// note' may
+            // follow on the same line and is skipped
+            if let Ok(Some(result)) = scanner.match_compiled_regex_forward(&SCRIPT_END) {
+                lines.push(result[0].to_string());
+                let rest = scanner.peek_line_ref().unwrap_or_default();
+                let trailing = rest.trim();
+                if !trailing.is_empty() && !trailing.starts_with("//") && !trailing.starts_with('#')
+                {
                     let details = ParseErrorDetails::new_with_position(
-                        ParseError::MissingPreRequestScriptClose,
-                        (start_pos.cursor, Some(scanner.get_cursor())),
+                        ParseError::UnexpectedContentAfterPreRequestScript(trailing.to_string()),
+                        (
+                            scanner.get_cursor(),
+                            Some(scanner.get_cursor() + rest.chars().count()),
+                        ),
                     );
                     return Err(details);
                 }
+                found = true;
+                break;
             } else {
                 let line = scanner.get_line_and_advance();
                 if line.is_none() {
@@ -585,6 +1104,28 @@ impl Parser {
         scanner.skip_to_next_line();
         Ok(Some(model::PreRequestScript::Script(lines.join("\n"))))
     }
+
+    // A target can be quoted such as '"https://test.com/a b"' to contain whitespace, the quotes
+    // are already removed by the tokenizer. Whitespace within the target is percent encoded so
+    // the target is a valid uri, unquoted targets never contain whitespace and are kept as is.
+    fn parse_request_target(target: &str) -> RequestTarget {
+        if !target.contains(char::is_whitespace) {
+            return RequestTarget::from(target);
+        }
+        let mut encoded = String::with_capacity(target.len());
+        for c in target.chars() {
+            if c.is_whitespace() {
+                let mut buf = [0; 4];
+                for byte in c.encode_utf8(&mut buf).bytes() {
+                    encoded.push_str(&format!("%{:02X}", byte));
+                }
+            } else {
+                encoded.push(c);
+            }
+        }
+        RequestTarget::from(&encoded[..])
+    }
+
     // @TODO: create a macro that generates a match statement for each enum variant
     fn match_request_method(str: &str) -> model::HttpMethod {
         // if not one of the well known methods then it is a custom method
@@ -592,7 +1133,13 @@ impl Parser {
     }
 
     /// Parse a request line of the form '[method required-whitespace] request-target [required-whitespace http-version]'
-    fn parse_request_line(scanner: &mut Scanner) -> ParseResult<model::RequestLine> {
+    fn parse_request_line(
+        scanner: &mut Scanner,
+        options: &ParserOptions,
+        warnings: &mut Vec<ParseErrorDetails>,
+    ) -> ParseResult<model::RequestLine> {
+        // start of the request line itself, `line_start` below is taken after advancing
+        let request_line_start = scanner.get_cursor();
         let mut line = match scanner.get_line_and_advance() {
             Some(line) => line,
             _ => String::new(),
@@ -600,7 +1147,8 @@ impl Parser {
 
         let line_start = scanner.get_pos();
         // request line can be split over multiple lines but all lines following need to be
-        // indented
+        // indented. Indented lines are always part of the target, also if they start with '#'
+        // such as '    #section' or '    &tag=#important', only trailing comments are removed
         let line_iterator: LineIterator = scanner.iter_at_pos();
 
         let (indented_lines, line_end): (Vec<String>, usize) =
@@ -610,18 +1158,39 @@ impl Parser {
 
         scanner.set_pos(line_end);
 
+        let mut errs: Vec<ParseErrorDetails> = Vec::new();
+
+        // all continuation lines should be indented the same way, either with tabs or with spaces
+        if options.warn_inconsistent_indentation {
+            let indentations = indented_lines
+                .iter()
+                .flat_map(|l| l.chars().take_while(|c| *c == ' ' || *c == '\t'))
+                .collect::<std::collections::HashSet<char>>();
+            if indentations.len() > 1 {
+                warnings.push(ParseErrorDetails::new_with_position(
+                    ParseError::InconsistentRequestLineIndentation,
+                    (line_start.cursor, Some(line_end)),
+                ));
+            }
+        }
+
         if !indented_lines.is_empty() {
             line.push_str(
                 &indented_lines
                     .iter()
-                    .map(|l| l.trim().to_owned())
+                    .map(|l| Parser::strip_trailing_comment(l.trim()).to_owned())
                     .collect::<Vec<String>>()
                     .join(""),
             );
         }
 
         let line_scanner = Scanner::new(&line);
-        let tokens: Vec<String> = line_scanner.get_tokens();
+        // a quoted element such as '"HTTP/1.1"' is one token and a trailing comment is not
+        // counted as elements of the request line
+        let tokens: Vec<String> = line_scanner.get_tokens_with(&TokenOptions {
+            quoted: true,
+            comments: true,
+        });
 
         // It can be that the request line is missing but there are still headers
         if tokens.len() >= 2 && tokens[0].contains(':') {
@@ -635,9 +1204,24 @@ impl Parser {
 
         let (request_line, err): (model::RequestLine, Option<ParseErrorDetails>) = match &tokens[..]
         {
+            // a lone well known method such as 'POST' is not a target without scheme
+            [method]
+                if !matches!(
+                    Parser::match_request_method(method),
+                    model::HttpMethod::CUSTOM(_)
+                ) =>
+            {
+                return Err(ParseErrorDetails::new_with_position(
+                    ParseError::MissingRequestTargetAfterMethod(method.clone()),
+                    (
+                        request_line_start,
+                        Some(request_line_start + method.chars().count()),
+                    ),
+                ));
+            }
             [target_str] => (
                 model::RequestLine {
-                    target: RequestTarget::from(&target_str[..]),
+                    target: Parser::parse_request_target(target_str),
                     method: model::WithDefault::default(),
                     http_version: model::WithDefault::default(),
                 },
@@ -645,7 +1229,7 @@ impl Parser {
             ),
             [method, target_str] => (
                 model::RequestLine {
-                    target: RequestTarget::from(&target_str[..]),
+                    target: Parser::parse_request_target(target_str),
                     method: WithDefault::Some(Parser::match_request_method(method)),
                     http_version: WithDefault::default(),
                 },
@@ -662,7 +1246,7 @@ impl Parser {
                 let line_end = line_start.cursor + tokens.len();
                 (
                     model::RequestLine {
-                        target: RequestTarget::from(&target_str[..]),
+                        target: Parser::parse_request_target(target_str),
                         method: WithDefault::Some(Parser::match_request_method(method)),
                         http_version,
                     },
@@ -697,7 +1281,7 @@ impl Parser {
 
                 (
                     model::RequestLine {
-                        target: RequestTarget::from(&target_str[..]),
+                        target: Parser::parse_request_target(target_str),
                         method: WithDefault::Some(Parser::match_request_method(method)),
                         http_version: WithDefault::from(http_version),
                     },
@@ -706,25 +1290,42 @@ impl Parser {
             }
         };
 
-        let mut errs: Vec<ParseErrorDetails> = Vec::new();
         if let Some(err) = err {
             errs.push(err);
         }
         Ok((request_line, errs))
     }
 
+    /// Remove a comment starting with '#' or '//' after whitespace at the end of a continuation
+    /// line of the request target. A '#' without preceding whitespace such as in `?tag=#1`
+    /// belongs to the target.
+    fn strip_trailing_comment(line: &str) -> &str {
+        let comment_start = line.char_indices().find(|(index, c)| {
+            WS_CHARS.contains(c)
+                && (line[index + 1..].starts_with('#') || line[index + 1..].starts_with("//"))
+        });
+        match comment_start {
+            Some((index, _)) => line[..index].trim_end(),
+            None => line,
+        }
+    }
+
     /// Parse a regular comment either starts with '###' or with '//' or '#'
     /// Both '//' and '#' comments may contain meta information, in this case they are not parsed
     /// as regular comments. If a '###' comment occurs alone without any other comments, then it
     /// signifies the name of a request and will be transformed afterwards and not taken as regular
     /// comment.
-    /// Note that '###' can also be a request separator
+    /// Note that '###' can also be a request separator, any run of three or more '#' is treated as
+    /// one.
     fn parse_comment(scanner: &mut Scanner) -> Result<Option<model::Comment>, ParseErrorDetails> {
         scanner.skip_empty_lines();
         // comments can be indented
         scanner.skip_ws();
 
         if scanner.match_str_forward(CommentKind::RequestSeparator.string_repr()) {
+            // separators can be longer than three characters such as '#####', the additional
+            // '#' are part of the separator and not of the comment
+            while scanner.take(&'#') {}
             return Parser::parse_comment_line(scanner, CommentKind::RequestSeparator);
         }
 
@@ -741,24 +1342,38 @@ impl Parser {
     }
 
     /// Parse http headers, they can either belong to a request or each multipart part can also
-    /// contain headers. This function is used to parse both cases.
-    fn parse_headers(scanner: &mut Scanner) -> Result<Vec<model::Header>, ParseErrorDetails> {
+    /// contain headers. This function is used to parse both cases. Invalid header names or
+    /// values are added to `warnings` and the header is kept so parsing can continue.
+    fn parse_headers(
+        scanner: &mut Scanner,
+        warnings: &mut Vec<ParseErrorDetails>,
+    ) -> Result<Vec<model::Header>, ParseErrorDetails> {
         let mut headers: Vec<model::Header> = Vec::new();
 
-        let header_regex = regex::Regex::from_str("^([^:]+):\\s*(.+)\\s*").unwrap();
-
         loop {
             if scanner.is_done() {
                 return Ok(headers);
             }
 
-            // newline after requestline and headers ends header section
-            if let Some(&'\n') = scanner.peek() {
+            // newline after requestline and headers ends header section, also with a CRLF line
+            // ending such as within the wire format of a multipart body
+            if scanner.peek() == Some(&'\n') || scanner.peek_line_ref() == Some("\r") {
+                return Ok(headers);
+            }
+
+            // header names cannot start with these characters, the line is probably the start
+            // of the body (json, xml or a file reference) with a missing blank line before it.
+            // The caller decides how to handle the missing line.
+            if scanner
+                .peek_line()
+                .is_some_and(|line| line.trim_start().starts_with(['{', '[', '<']))
+            {
                 return Ok(headers);
             }
 
+            let line_start = scanner.get_cursor();
             let line = scanner.get_line_and_advance().unwrap();
-            let captures = header_regex.captures(&line);
+            let captures = HEADER.captures(&line);
 
             if captures.is_none() {
                 let err_details = ParseErrorDetails::new_with_position(
@@ -770,11 +1385,29 @@ impl Parser {
             let captures = captures.unwrap();
             match (captures.get(1), captures.get(2)) {
                 (Some(key_match), Some(value_match)) => {
-                    //@TODO: validate header fields
-                    headers.push(model::Header {
+                    let header = model::Header {
                         key: key_match.as_str().to_string(),
                         value: value_match.as_str().to_string(),
-                    })
+                    };
+                    let position = (line_start, Some(scanner.get_cursor()));
+                    if !Parser::is_valid_header_name(&header.key) {
+                        warnings.push(ParseErrorDetails::new_with_position(
+                            ParseError::InvalidHeaderName(header.key.clone()),
+                            position,
+                        ));
+                    }
+                    // lines are already split at '\n', so only a bare carriage return or an
+                    // escaped line break such as '\r\n' written within the value can inject
+                    // another header. A carriage return at the end of the line belongs to a CRLF
+                    // line ending.
+                    let value = header.value.trim_end_matches('\r');
+                    if value.contains('\r') || value.contains(r"\n") || value.contains(r"\r") {
+                        warnings.push(ParseErrorDetails::new_with_position(
+                            ParseError::InvalidHeaderValue(header.key.clone()),
+                            position,
+                        ));
+                    }
+                    headers.push(header);
                 }
                 _ => {
                     let err_details = ParseErrorDetails::new_with_position(
@@ -787,38 +1420,79 @@ impl Parser {
         }
     }
 
+    /// Checks that a header name is a token as defined by
+    /// https://www.rfc-editor.org/rfc/rfc9110#section-5.6.2, braces are allowed as well so the
+    /// name may contain variables such as `X-{{name}}`.
+    fn is_valid_header_name(name: &str) -> bool {
+        !name.is_empty()
+            && name
+                .bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~{}".contains(&byte))
+    }
+
     /// Parse the body of an http request. Can either be multipart or contain some kind of data.
     /// The Jetbrains client trims the data so trailing newlines or whitespace is also ignored when
     /// parsing here
     fn parse_body(
         scanner: &mut Scanner,
         headers: &[Header],
+        settings: &RequestSettings,
+        options: &ParserOptions,
+        warnings: &mut Vec<ParseErrorDetails>,
     ) -> Result<RequestBody, (RequestBody, Vec<ParseErrorDetails>)> {
         let mut parse_errs: Vec<ParseErrorDetails> = Vec::new();
+
+        // with '@raw-body' the rest of the request is the body, regardless of the content type
+        if let Some(true) = settings.raw_body {
+            return Ok(Parser::parse_raw_body(scanner, false));
+        }
+
+        if options.heredoc_bodies {
+            if let Some(body) = Parser::parse_heredoc_body(scanner, &mut parse_errs) {
+                return if parse_errs.is_empty() {
+                    Ok(body)
+                } else {
+                    Err((body, parse_errs))
+                };
+            }
+        }
         let content_type = headers
             .iter()
-            .find(|header| {
-                header.key == "Content-Type" //&& header.value.starts_with("multipart/form-data")
-            })
+            .find(|header| header.key.eq_ignore_ascii_case("Content-Type"))
             .map(|header| header.value.as_str());
 
         let body = match content_type {
-            Some(content_type) if content_type.starts_with("multipart/form-data") => {
+            Some(content_type)
+                if content_type
+                    .get(..19)
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case("multipart/form-data")) =>
+            {
                 Parser::parse_content_type_multipart_form_data(
                     scanner,
                     content_type,
+                    options,
                     &mut parse_errs,
+                    warnings,
                 )
                 .unwrap_or(RequestBody::None)
             }
-            Some("application/x-www-form-urlencoded") => Parser::parse_body_urlencoded(scanner),
+            // parameters such as the charset do not change how the body is parsed, media types
+            // are case insensitive
+            Some(content_type)
+                if content_type
+                    .split(';')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .eq_ignore_ascii_case("application/x-www-form-urlencoded") =>
+            {
+                Parser::parse_body_urlencoded(scanner)
+            }
             _ => {
-                let body = Parser::parse_raw_body(scanner);
+                let body = Parser::parse_raw_body(scanner, true);
                 // if we have a content-type then we just have an empty body instead of none
                 if content_type.is_some() && matches!(body, RequestBody::None) {
-                    RequestBody::Raw {
-                        data: DataSource::Raw(String::new()),
-                    }
+                    RequestBody::Empty
                 } else {
                     body
                 }
@@ -835,45 +1509,64 @@ impl Parser {
     fn parse_content_type_multipart_form_data(
         scanner: &mut Scanner,
         content_type: &str,
+        options: &ParserOptions,
         parse_errs: &mut Vec<ParseErrorDetails>,
+        warnings: &mut Vec<ParseErrorDetails>,
     ) -> Option<RequestBody> {
-        let boundary_regex =
-            regex::Regex::from_str("multipart/form-data\\s*(;\\s*boundary\\s*=\\s*(.+))?").unwrap();
-        let captures = boundary_regex.captures(content_type);
-
-        let mut boundary = DEFAULT_MULTIPART_BOUNDARY.to_string();
-
-        if let Some(captures) = captures {
-            let boundary_match = captures.get(2);
-
-            // either with or without quotes
-            if boundary_match.is_none() {
+        let header_boundary = model::multipart_boundary_param(content_type);
+        let boundary = match header_boundary.clone() {
+            Some(boundary) => boundary,
+            None => {
                 parse_errs.push(ParseErrorDetails::new_with_position(
                     ParseError::MissingMultipartHeaderBoundaryDefinition(
                         DEFAULT_MULTIPART_BOUNDARY.to_string(),
                     ),
                     (scanner.get_cursor(), None),
                 ));
+                DEFAULT_MULTIPART_BOUNDARY.to_string()
             }
-            boundary = boundary_match
-                .map(|o| o.as_str())
-                .unwrap_or(DEFAULT_MULTIPART_BOUNDARY)
-                .to_string();
-            if boundary.starts_with('"') && boundary.ends_with('"') {
-                boundary = boundary[1..(boundary.len() - 1)].to_string();
+        };
+
+        if let Err(boundary_err) = Parser::is_multipart_boundary_valid(&boundary) {
+            parse_errs.push(boundary_err);
+        }
+
+        let mut boundary = boundary;
+        if options.lenient_multipart_boundary {
+            scanner.skip_empty_lines();
+            if let Some(body_boundary) = Parser::find_body_boundary(scanner.remaining(), &boundary)
+            {
+                let line_end = scanner.get_cursor()
+                    + scanner
+                        .peek_line_ref()
+                        .map_or(0, |line| line.chars().count());
+                warnings.push(ParseErrorDetails::new_with_position(
+                    ParseError::MultipartBoundaryMismatch {
+                        header: boundary,
+                        body: body_boundary.clone(),
+                    },
+                    (scanner.get_cursor(), Some(line_end)),
+                ));
+                boundary = body_boundary;
             }
-        } else {
+        }
+
+        // a body which does not start with the boundary of the header has no parts at all, a
+        // missing boundary definition is already reported above
+        scanner.skip_empty_lines();
+        let boundary_line = format!("--{}", boundary);
+        if header_boundary.is_some()
+            && !scanner.is_done()
+            && !scanner.remaining().starts_with(&boundary_line)
+        {
             parse_errs.push(ParseErrorDetails::new_with_position(
-                ParseError::MissingMultipartHeaderBoundaryDefinition(
-                    DEFAULT_MULTIPART_BOUNDARY.to_string(),
-                ),
+                ParseError::MissingMultipartStartingBoundary,
                 (scanner.get_cursor(), None),
             ));
+            return None;
         }
-        if let Err(boundary_err) = Parser::is_multipart_boundary_valid(&boundary) {
-            parse_errs.push(boundary_err);
-        }
-        match Parser::parse_multipart_body(scanner, &boundary, parse_errs) {
+
+        match Parser::parse_multipart_body(scanner, &boundary, options, parse_errs, warnings) {
             Ok(multipart_body) => Some(multipart_body),
             Err(err) => {
                 parse_errs.push(err);
@@ -882,6 +1575,25 @@ impl Parser {
         }
     }
 
+    // Returns the boundary of the first line of a multipart body if it differs from the boundary
+    // of the header. The boundary is only taken if the body is also closed with it before the
+    // next request.
+    fn find_body_boundary(body: &str, boundary: &str) -> Option<String> {
+        let mut lines = body.lines().map(str::trim_end);
+        let body_boundary = lines.next()?.strip_prefix("--")?;
+        if body_boundary == boundary
+            || body_boundary.is_empty()
+            || body_boundary.contains(char::is_whitespace)
+        {
+            return None;
+        }
+        let end_boundary = format!("--{}--", body_boundary);
+        lines
+            .take_while(|line| !line.starts_with(REQUEST_SEPARATOR))
+            .any(|line| line == end_boundary)
+            .then(|| body_boundary.to_string())
+    }
+
     fn parse_body_urlencoded(scanner: &mut Scanner) -> RequestBody {
         let mut url_encoded_params: Vec<UrlEncodedParam> = Vec::new();
         if let Some(line) = scanner.peek_line() {
@@ -904,50 +1616,89 @@ impl Parser {
         RequestBody::UrlEncoded { url_encoded_params }
     }
 
-    fn parse_raw_body(scanner: &mut Scanner) -> RequestBody {
+    /// Parse a body enclosed in a heredoc, starting with a line `<<< DELIMITER` and ending with a
+    /// line only containing `DELIMITER`. All lines between are taken verbatim as body.
+    fn parse_heredoc_body(
+        scanner: &mut Scanner,
+        parse_errs: &mut Vec<ParseErrorDetails>,
+    ) -> Option<RequestBody> {
+        let delimiter = match scanner.peek_line() {
+            Some(line) if line.trim_start().starts_with("<<<") => {
+                line.trim_start()[3..].trim().to_string()
+            }
+            _ => return None,
+        };
+        let start_pos = scanner.get_pos();
+        scanner.skip_to_next_line();
+
+        let mut lines: Vec<String> = Vec::new();
+        loop {
+            match scanner.get_line_and_advance() {
+                Some(line) if !delimiter.is_empty() && line.trim_end() == delimiter => break,
+                Some(line) => lines.push(line),
+                None => {
+                    parse_errs.push(ParseErrorDetails::new_with_position(
+                        ParseError::MissingHeredocBodyEnd(delimiter),
+                        (start_pos.cursor, Some(scanner.get_cursor())),
+                    ));
+                    break;
+                }
+            }
+        }
+        Some(RequestBody::Raw {
+            data: DataSource::Raw(lines.join("\n")),
+        })
+    }
+
+    // Parse the body as text until the next request separator. If `detect_handlers` is set a line
+    // starting with '>' or '>>' ends the body as well.
+    fn parse_raw_body(scanner: &mut Scanner, detect_handlers: bool) -> RequestBody {
         if scanner.is_done() {
             return RequestBody::None;
         }
 
         let start_pos = scanner.get_pos();
-        loop {
-            let peek_line = scanner.peek_line();
-            if peek_line.is_none() {
-                break;
-            }
-            let peek_line = peek_line.unwrap();
+        while let Some(peek_line) = scanner.peek_line_ref() {
             // new request starts
             if peek_line.starts_with(REQUEST_SEPARATOR) {
                 break;
             }
+            let is_redirect = peek_line.starts_with(">>");
+            let is_handler = peek_line.starts_with('>');
 
-            // response handler
-            if peek_line.starts_with('>') {
-                // if previous line is empty then do not parse it as body before response
-                // handler, when serializing we put an additional new line for clarity that
-                // should not be part of the body
-                if scanner
-                    .get_prev_line()
-                    .map_or(false, |l| l.trim().is_empty())
-                {
-                    scanner.step_to_previous_line_start();
-                }
-                break;
+            if !detect_handlers {
+                scanner.skip_to_next_line();
+                continue;
             }
 
-            // output handler / redirect also ends body
-            if peek_line.starts_with(">>") {
+            // output handler / redirect ends body, has to be checked before the response handler
+            // as it also starts with '>'
+            if is_redirect {
                 // if previous line is empty then do not parse it as body before redirect
                 // when serializing we add an additional newline before the redirect for
                 // clarity which should not be part of the body
-                if scanner
-                    .get_prev_line()
-                    .map_or(false, |l| l.trim().is_empty())
+                if scanner.get_pos() != start_pos
+                    && scanner.get_prev_line().is_some_and(|l| l.trim().is_empty())
                 {
                     scanner.step_to_previous_line_start();
                 }
                 break;
             }
+
+            // response handler, only if it starts a new section after an empty line or directly
+            // at the start of the body, otherwise the line is part of the body such as a quote
+            if is_handler {
+                let prev_line_empty = scanner.get_prev_line().is_some_and(|l| l.trim().is_empty());
+                if prev_line_empty || scanner.get_pos() == start_pos {
+                    // if previous line is empty then do not parse it as body before response
+                    // handler, when serializing we put an additional new line for clarity that
+                    // should not be part of the body
+                    if prev_line_empty && scanner.get_pos() != start_pos {
+                        scanner.step_to_previous_line_start();
+                    }
+                    break;
+                }
+            }
             scanner.skip_to_next_line();
         }
         let mut end_pos = scanner.get_pos();
@@ -971,25 +1722,58 @@ impl Parser {
         }
     }
 
-    /// Parse a multipart http body
+    /// Parse a multipart http body. The number of parts and the bytes consumed are bounded by
+    /// the `options` so malformed or malicious input fails early with an error.
     fn parse_multipart_body(
         scanner: &mut Scanner,
         boundary: &str,
+        options: &ParserOptions,
         parse_errs: &mut Vec<ParseErrorDetails>,
+        warnings: &mut Vec<ParseErrorDetails>,
     ) -> Result<RequestBody, ParseErrorDetails> {
         scanner.skip_empty_lines();
 
         let mut parts: Vec<Multipart> = Vec::new();
+        let start_cursor = scanner.get_cursor();
+        let start_bytes = scanner.consumed().len();
+        // compiled once for all parts of the body, the boundary is escaped so it is always valid
+        let boundary_regex =
+            regex::bytes::Regex::new(&format!("^{}", regex::escape(&format!("--{}", boundary))))
+                .unwrap();
 
         let mut errors: Vec<ParseErrorDetails> = Vec::new();
         loop {
-            let multipart = Parser::parse_multipart_part(scanner, boundary, parse_errs);
+            let multipart = Parser::parse_multipart_part(
+                scanner,
+                boundary,
+                &boundary_regex,
+                (start_bytes, options),
+                parse_errs,
+                warnings,
+            );
             if let Err(err) = multipart {
+                if matches!(err.error, ParseError::MultipartBodyTooLarge(_)) {
+                    return Err(err);
+                }
                 errors.push(err);
                 break;
             }
             let multipart = multipart.unwrap();
             parts.push(multipart);
+
+            if parts.len() > options.max_multipart_parts {
+                return Err(ParseErrorDetails::new_with_position(
+                    ParseError::TooManyMultipartParts(options.max_multipart_parts),
+                    (start_cursor, Some(scanner.get_cursor())),
+                ));
+            }
+            if scanner.consumed().len() - start_bytes > options.max_multipart_bytes {
+                return Err(ParseErrorDetails::new_with_position(
+                    ParseError::MultipartBodyTooLarge(options.max_multipart_bytes),
+                    (start_cursor, Some(scanner.get_cursor())),
+                ));
+            }
+
             if scanner.is_done() {
                 break;
             }
@@ -1019,17 +1803,21 @@ impl Parser {
         })
     }
 
-    /// Parse a single block of a multipart body
+    /// Parse a single block of a multipart body. `body_bounds` are the number of bytes consumed
+    /// before the body started and the options bounding it, reading the content of the part
+    /// fails as soon as the body exceeds `max_multipart_bytes`, also if the part is never closed.
     fn parse_multipart_part(
         scanner: &mut Scanner,
         boundary: &str,
+        boundary_regex: &regex::bytes::Regex,
+        body_bounds: (usize, &ParserOptions),
         parse_errs: &mut Vec<ParseErrorDetails>,
+        warnings: &mut Vec<ParseErrorDetails>,
     ) -> Result<model::Multipart, ParseErrorDetails> {
         let boundary_line = format!("--{}", boundary);
         let multipart_end_line = format!("--{}--", boundary);
 
-        let escaped_boundary = regex::escape(&boundary_line);
-        let first_boundary = scanner.match_regex_forward(&escaped_boundary);
+        let first_boundary = scanner.match_compiled_regex_forward(boundary_regex);
         if first_boundary.is_err() {
             return Err(ParseErrorDetails::new_with_position(
                 ParseError::MissingMultipartStartingBoundary,
@@ -1041,7 +1829,7 @@ impl Parser {
 
         let start_pos = scanner.get_pos();
 
-        let part_headers = Parser::parse_headers(scanner).map_err(|err| {
+        let mut part_headers = Parser::parse_headers(scanner, warnings).map_err(|err| {
             ParseErrorDetails::new_with_position(
                 ParseError::InvalidSingleMultipartHeaders {
                     header_parse_err: Box::new(err.error.clone()),
@@ -1050,9 +1838,15 @@ impl Parser {
                 (scanner.get_cursor(), None),
             )
         })?;
+        // parts framed with CRLF such as from `RequestBody::as_bytes`
+        for header in part_headers.iter_mut() {
+            if header.value.ends_with('\r') {
+                header.value.pop();
+            }
+        }
         let end_pos = scanner.get_pos();
 
-        let (field, part_headers) = match &part_headers[..] {
+        let (field, part_headers, disposition_value) = match &part_headers[..] {
             [] => {
                 return Err(ParseErrorDetails::new_with_position(
                     ParseError::MissingSingleMultipartContentDispositionHeader,
@@ -1060,7 +1854,10 @@ impl Parser {
                 ));
             }
             [disposition_part, part_headers @ ..] => {
-                if disposition_part.key != "Content-Disposition" {
+                if !disposition_part
+                    .key
+                    .eq_ignore_ascii_case("Content-Disposition")
+                {
                     return Err(ParseErrorDetails::new_with_position(
                         ParseError::WrongMultipartContentDispositionHeader(
                             disposition_part.key.clone(),
@@ -1068,9 +1865,7 @@ impl Parser {
                         (start_pos.cursor, Some(end_pos.cursor)),
                     ));
                 }
-                let parts: Vec<&str> = disposition_part.value.split(';').collect();
-                let mut parts_iter = parts.iter();
-                let disposition_type = parts_iter.next().unwrap().trim();
+                let disposition_type = disposition_part.value.split(';').next().unwrap().trim();
                 if disposition_type != "form-data" {
                     // only form-data is valid in http context, other disposition types may exist
                     // for other applications (email mime types...)
@@ -1081,32 +1876,29 @@ impl Parser {
                         (start_pos.cursor, Some(end_pos.cursor)),
                     ));
                 }
-                let mut disposition_field = DispositionField::new_with_filename("", None::<String>);
-                for current in parts_iter {
-                    match current.split('=').map(|p| p.trim()).collect::<Vec<&str>>()[..] {
-                        [key, mut value] => {
-                            if value.starts_with('"') && value.ends_with('"') {
-                                value = &value[1..(value.len() - 1)];
-                            }
-                            if key == "filename" {
-                                disposition_field.filename = Some(value.to_string());
-                            } else if key == "filename*" {
-                                disposition_field.filename_star = Some(value.to_string());
-                            } else if key == "name" {
-                                disposition_field.name = value.to_string();
-                            }
-                        }
-                        _ => {
-                            return Err(ParseErrorDetails::from(
-                                ParseError::MalformedContentDispositionEntries(current.to_string()),
-                            ))
-                        }
-                    }
-                }
-                (disposition_field, part_headers)
+                let disposition_field = DispositionField::parse(&disposition_part.value)
+                    .map_err(ParseErrorDetails::from)?;
+                (
+                    disposition_field,
+                    part_headers,
+                    disposition_part.value.clone(),
+                )
             }
         };
 
+        // an unquoted name is only valid if it consists of token characters, it is quoted when
+        // the request is serialized again
+        let unquoted_name = model::split_header_params(&disposition_value)
+            .into_iter()
+            .filter_map(|param| param.split_once('='))
+            .any(|(key, value)| key.trim() == "name" && !value.trim().starts_with('"'));
+        if unquoted_name && DispositionField::requires_quoting(&field.name) {
+            parse_errs.push(ParseErrorDetails::new_with_position(
+                ParseError::MultipartNameRequiresQuoting(field.name.clone()),
+                (start_pos.cursor, Some(end_pos.cursor)),
+            ));
+        }
+
         if field.name.is_empty() {
             let msg = format!(
                 "[{}]",
@@ -1122,7 +1914,7 @@ impl Parser {
             ));
         }
 
-        if !scanner.match_str_forward("\n") {
+        if !scanner.match_str_forward("\n") && !scanner.match_str_forward("\r\n") {
             return Err(ParseErrorDetails::new_with_position(
                 ParseError::SingleMultipartMissingEmptyLine,
                 (scanner.get_cursor(), None),
@@ -1156,39 +1948,50 @@ impl Parser {
                 data: DataSource::FromFilepath(file_path.to_string()), // @TODO: when to read in data from file?
             })
         } else {
-            let mut text = String::new();
+            // the line break before the boundary belongs to the boundary delimiter, every other
+            // line break is part of the content
+            let mut content = String::new();
+            let mut num_lines = 0;
 
             loop {
-                let peek_line = scanner.peek_line();
-                if peek_line.is_none() {
+                let Some(peek_line) = scanner.peek_line_ref() else {
                     return Err(ParseErrorDetails {
                         error: ParseError::MultipartShouldBeEndedWithBoundary(multipart_end_line),
                         ..Default::default()
                     });
                 };
-                let peek_line = peek_line.unwrap();
+                // editors may leave trailing whitespace after the boundary
+                let peek_line = peek_line.trim_end();
                 if peek_line == boundary_line || peek_line == multipart_end_line {
+                    // the CRLF before the boundary belongs to the delimiter as well
+                    if content.ends_with('\r') {
+                        content.pop();
+                    }
                     return Ok(Multipart {
                         disposition: field,
                         headers: part_headers.to_owned(),
-                        data: DataSource::Raw(text),
+                        data: DataSource::Raw(content),
                     });
                 }
-                let next = scanner.get_line_and_advance().unwrap();
-                text += &next;
-                // only add a new line if more text will appear
-                if !scanner
-                    .peek_line()
-                    .map_or(false, |pl| pl.starts_with(&boundary_line))
-                {
-                    text += "\n";
+                if num_lines > 0 {
+                    content.push('\n');
+                }
+                content.push_str(scanner.get_line_and_advance_ref().unwrap());
+                num_lines += 1;
+
+                let (start_bytes, options) = body_bounds;
+                if scanner.consumed().len() - start_bytes > options.max_multipart_bytes {
+                    return Err(ParseErrorDetails::new_with_position(
+                        ParseError::MultipartBodyTooLarge(options.max_multipart_bytes),
+                        (start_pos.cursor, Some(scanner.get_cursor())),
+                    ));
                 }
             }
         }
     }
 
     /// Checks whether a multipart boundary is valid or not according to: https://www.rfc-editor.org/rfc/rfc2046#section-5.1.1
-    fn is_multipart_boundary_valid(boundary: &str) -> Result<(), ParseErrorDetails> {
+    pub(crate) fn is_multipart_boundary_valid(boundary: &str) -> Result<(), ParseErrorDetails> {
         let boundary_len = boundary.len();
         if !(1..=70).contains(&boundary_len) {
             return Err(ParseErrorDetails {
@@ -1255,7 +2058,7 @@ impl Parser {
             let mut lines: Vec<String> = Vec::new();
             let mut found = false;
             loop {
-                if let Ok(Some(matches)) = scanner.match_regex_forward("(.*)%}") {
+                if let Ok(Some(matches)) = scanner.match_compiled_regex_forward(&SCRIPT_END) {
                     for m in matches {
                         found = true;
                         lines.push(m.to_string());
@@ -1362,15 +2165,36 @@ https://httpbin.org
             headers: Vec::new(),
             body: model::RequestBody::None,
             settings: RequestSettings::default(),
-            pre_request_script: None,
+            pre_request_scripts: vec![],
             response_handler: None,
             save_response: None,
+            index: 0,
+            warnings: vec![],
         }];
 
         assert!(parsed.errs.is_empty());
         assert_eq!(parsed.requests, expected);
     }
 
+    #[test]
+    pub fn name_longer_separator() {
+        let str = "
+#### first request
+GET https://httpbin.org/first
+#####   second request
+GET https://httpbin.org/second
+#####
+GET https://httpbin.org/third
+";
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].name, Some("first request".to_string()));
+        assert_eq!(requests[1].name, Some("second request".to_string()));
+        assert_eq!(requests[2].name, None);
+        assert!(requests.iter().all(|request| request.comments.is_empty()));
+    }
+
     #[test]
     pub fn name_with_at() {
         let str = "
@@ -1391,9 +2215,11 @@ https://httpbin.org
             headers: Vec::new(),
             body: model::RequestBody::None,
             settings: RequestSettings::default(),
-            pre_request_script: None,
+            pre_request_scripts: vec![],
             response_handler: None,
             save_response: None,
+            index: 0,
+            warnings: vec![],
         }];
 
         assert!(parsed.errs.is_empty());
@@ -1451,9 +2277,11 @@ CUSTOMVERB https://httpbin.org
             headers: Vec::new(),
             body: model::RequestBody::None,
             settings: RequestSettings::default(),
-            pre_request_script: None,
+            pre_request_scripts: vec![],
             response_handler: None,
             save_response: None,
+            index: 0,
+            warnings: vec![],
         }];
 
         assert!(parsed.errs.is_empty());
@@ -1480,9 +2308,11 @@ POST https://httpbin.org
             headers: Vec::new(),
             body: model::RequestBody::None,
             settings: RequestSettings::default(),
-            pre_request_script: None,
+            pre_request_scripts: vec![],
             response_handler: None,
             save_response: None,
+            index: 0,
+            warnings: vec![],
         }];
 
         assert!(parsed.errs.is_empty());
@@ -1509,9 +2339,11 @@ POST https://httpbin.org
             headers: Vec::new(),
             body: model::RequestBody::None,
             settings: RequestSettings::default(),
-            pre_request_script: None,
+            pre_request_scripts: vec![],
             response_handler: None,
             save_response: None,
+            index: 0,
+            warnings: vec![],
         }];
 
         // whitespace before or after name should be removed
@@ -1545,6 +2377,137 @@ POST https://httpbin.org
         );
     }
 
+    #[test]
+    pub fn multi_line_description() {
+        let str = "
+### Create user
+// Creates a new user.
+// Requires an admin token.
+# @unknown-directive
+# @no-log
+POST https://httpbin.org
+";
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].name, Some("Create user".to_string()));
+        assert_eq!(
+            requests[0].description(),
+            Some("Creates a new user.\nRequires an admin token.".to_string())
+        );
+
+        let FileParseResult { requests, errs } = Parser::parse("### Only a name\nGET /", false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests[0].description(), None);
+    }
+
+    #[test]
+    pub fn name_from_comment_containing_email() {
+        let str = "
+# @no-log
+# contact dev@example.com
+GET https://httpbin.org
+Accept: */*
+";
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].name,
+            Some("contact dev@example.com".to_string())
+        );
+        assert_eq!(requests[0].settings.no_log, Some(true));
+    }
+
+    #[test]
+    pub fn parse_single_request_str() {
+        let str = r#"GET https://x.com
+Accept: application/json
+Content-Type: text/plain
+
+some body
+"#;
+        let request = Parser::parse_request_str(str).expect("request is valid");
+        assert_eq!(request.method(), HttpMethod::GET);
+        assert_eq!(
+            request.request_line.target,
+            RequestTarget::from("https://x.com")
+        );
+        assert_eq!(
+            request.headers,
+            vec![
+                Header::new("Accept", "application/json"),
+                Header::new("Content-Type", "text/plain")
+            ]
+        );
+        assert_eq!(
+            request.body,
+            model::RequestBody::Raw {
+                data: DataSource::Raw("some body".to_string())
+            }
+        );
+
+        let request = Parser::parse_request_str("\n  GET https://x.com").unwrap();
+        assert_eq!(
+            request.request_line.target,
+            RequestTarget::from("https://x.com")
+        );
+
+        let err = Parser::parse_request_str("# only a comment").unwrap_err();
+        assert_eq!(err.details[0].error, ParseError::MissingRequestTargetLine);
+    }
+
+    #[test]
+    pub fn match_named_request_methods() {
+        assert_eq!(Parser::match_request_method("TRACE"), HttpMethod::TRACE);
+        assert_eq!(Parser::match_request_method("CONNECT"), HttpMethod::CONNECT);
+        assert_eq!(Parser::match_request_method("LINK"), HttpMethod::LINK);
+        assert_eq!(Parser::match_request_method("UNLINK"), HttpMethod::UNLINK);
+        assert_eq!(
+            Parser::match_request_method("PROPFIND"),
+            HttpMethod::CUSTOM("PROPFIND".to_string())
+        );
+
+        for method in ["TRACE", "CONNECT", "LINK", "UNLINK"] {
+            let request = Parser::parse_request_str(&format!("{method} https://test.com")).unwrap();
+            assert_eq!(request.method().to_string(), method);
+            assert!(!matches!(request.method(), HttpMethod::CUSTOM(_)));
+        }
+    }
+
+    #[test]
+    pub fn render_errors_after_parse() {
+        let str = "GET https://test.com HTTP/abc\n";
+        let FileParseResult { errs, .. } = Parser::parse(str, false);
+        assert_eq!(errs.len(), 1);
+
+        let rendered = Parser::render_errors(str, &errs);
+        assert_eq!(
+            rendered,
+            Parser::get_pretty_print_errs(&Scanner::new(str), errs.iter())
+        );
+        assert!(rendered.starts_with(&format!(
+            "Error: {}\n",
+            ParseError::InvalidHttpVersion("HTTP/abc".to_string())
+        )));
+        assert!(rendered.contains("Position: "));
+
+        assert_eq!(Parser::render_errors(str, &[]), "");
+    }
+
+    #[test]
+    pub fn render_errors_with_caret() {
+        let str = "# @http-version abc\nGET https://test.com\n";
+        let FileParseResult { errs, .. } = Parser::parse(str, false);
+        let rendered = Parser::render_errors(str, &errs);
+        let expected = format!(
+            "Error: {}\nPosition: 0:0\n# @http-version abc\n^{}",
+            ParseError::InvalidHttpVersion("abc".to_string()),
+            "~".repeat(18)
+        );
+        assert_eq!(rendered, expected);
+    }
+
     #[test]
     pub fn parse_meta_name_line() {
         let str = "@name  =  actual request name";
@@ -1553,6 +2516,40 @@ POST https://httpbin.org
             .expect("can parse name line without error")
             .expect("parse returns something");
         assert_eq!(name, "actual request name".to_string());
+
+        let mut scanner = Scanner::new("@name actual request name");
+        let name = Parser::parse_meta_name(&mut scanner)
+            .expect("can parse name line without error")
+            .expect("parse returns something");
+        assert_eq!(name, "actual request name".to_string());
+
+        let mut scanner = Scanner::new("@names");
+        assert_eq!(Parser::parse_meta_name(&mut scanner), Ok(None));
+    }
+
+    #[test]
+    pub fn parse_meta_name_quoted() {
+        let name = |str: &str| Parser::parse_meta_name(&mut Scanner::new(str));
+        assert_eq!(
+            name("@name = \"Create User\""),
+            Ok(Some("Create User".to_string()))
+        );
+        assert_eq!(
+            name("@name 'Create User'"),
+            Ok(Some("Create User".to_string()))
+        );
+        assert_eq!(
+            name("@name = Say \"Hi\""),
+            Ok(Some("Say \"Hi\"".to_string()))
+        );
+        assert_eq!(name("@name = \"Mixed'"), Ok(Some("\"Mixed'".to_string())));
+
+        let FileParseResult { requests, errs } = Parser::parse(
+            "# @name = \"Create User\"\nPOST https://test.com/users",
+            false,
+        );
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests[0].name, Some("Create User".to_string()));
     }
 
     #[test]
@@ -1623,7 +2620,7 @@ POST https://httpbin.org
             false,
         );
         assert_eq!(requests.len(), 1);
-        let request = &requests[0];
+        let request = requests[0].clone();
         assert_eq!(request.request_line.target, expected_target);
         assert_eq!(
             request.request_line.method,
@@ -1737,590 +2734,2110 @@ POST https://httpbin.org
     }
 
     #[test]
-    pub fn validate_http_version() {
-        let version = model::HttpVersion::from_str("HTTP/1.1").expect("Version 1.1 to be valid");
-        assert_eq!(version, model::HttpVersion { major: 1, minor: 1 });
+    pub fn effective_method_and_http_version() {
+        let FileParseResult { requests, errs } = Parser::parse("https://test.com", false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests[0].method(), HttpMethod::GET);
+        assert_eq!(
+            requests[0].http_version(),
+            HttpVersion { major: 1, minor: 1 }
+        );
 
-        let version = model::HttpVersion::from_str("HTTP/1.2").expect("Version 1.2 to be valid");
-        assert_eq!(version, model::HttpVersion { major: 1, minor: 2 });
+        let FileParseResult { requests, errs } =
+            Parser::parse("DELETE https://test.com HTTP/2.0", false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests[0].method(), HttpMethod::DELETE);
+        assert_eq!(
+            requests[0].http_version(),
+            HttpVersion { major: 2, minor: 0 }
+        );
+    }
 
-        let version = model::HttpVersion::from_str("HTTP/2.0").expect("Version 2.0 to be valid");
-        assert_eq!(version, model::HttpVersion { major: 2, minor: 0 });
+    #[test]
+    pub fn parse_chunked_directive() {
+        let str = r####"
+# @chunked
+POST https://test.com/upload
+Content-Type: text/plain
 
-        let version = model::HttpVersion::from_str("HTTP/2.1").expect("Version 2.1 to be valid");
-        assert_eq!(version, model::HttpVersion { major: 2, minor: 1 });
+first chunk
 
-        assert!(model::HttpVersion::from_str("invalid").is_err());
+###
+POST https://test.com/plain
+Content-Type: text/plain
+
+content
+"####;
+
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].settings.chunked);
+        assert!(requests[0].is_chunked());
+        assert!(!requests[1].is_chunked());
+
+        let headers = requests[0].effective_headers(std::path::Path::new("."));
+        assert!(headers.contains(&Header::new("Transfer-Encoding", "chunked")));
+        assert!(!headers.iter().any(|header| header.key == "Content-Length"));
+
+        let serialized = crate::Serializer::serialize_request(&requests[0]);
+        assert!(serialized.starts_with("# @chunked\n"));
+        let FileParseResult { requests, errs } = Parser::parse(&serialized, false);
+        assert_eq!(errs, vec![]);
+        assert!(requests[0].is_chunked());
     }
 
     #[test]
-    pub fn request_target_multiline() {
-        let str = r#####"
-GET https://test.com:8080
-    /get
-    /html
-    ?id=123
-    &value=test
+    pub fn parse_expect_directive() {
+        let str = r####"
+# @expect status 201
+# @expect header Content-Type application/json
+// @expect header Location
+# @expect body-contains "id": 1
+POST https://test.com/users
 
-        "#####;
-        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
+###
+# @expect status created
+GET https://test.com/users
+"####;
+
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
         assert_eq!(errs, vec![]);
-        assert_eq!(requests.len(), 1);
-        let request = requests.remove(0);
+        assert_eq!(requests.len(), 2);
         assert_eq!(
-            request.request_line.target,
-            RequestTarget::Absolute {
-                uri: "https://test.com:8080/get/html?id=123&value=test".to_owned()
-            }
+            requests[0].settings.expectations,
+            vec![
+                model::Expectation::Status(201),
+                model::Expectation::Header {
+                    name: "Content-Type".to_string(),
+                    value: Some("application/json".to_string())
+                },
+                model::Expectation::Header {
+                    name: "Location".to_string(),
+                    value: None
+                },
+                model::Expectation::BodyContains("\"id\": 1".to_string()),
+            ]
         );
-        assert_eq!(request.request_line.http_version, WithDefault::default());
+
+        let serialized = crate::Serializer::serialize_request(&requests[0]);
+        let FileParseResult {
+            requests: reparsed, ..
+        } = Parser::parse(&serialized, false);
         assert_eq!(
-            request.request_line.method,
-            WithDefault::Some(HttpMethod::GET)
+            reparsed[0].settings.expectations,
+            requests[0].settings.expectations
         );
-    }
-
-    #[test]
-    pub fn request_target_multiline_no_method() {
-        let str = r#####"
-https://test.com:8080
-    /get
-    /html
-    ?id=123
-    &value=test
 
-        "#####;
-        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
-        assert_eq!(errs, vec![]);
-        assert_eq!(requests.len(), 1);
-        let request = requests.remove(0);
+        // malformed expectations are reported as warning and not recorded
+        assert_eq!(requests[1].warnings.len(), 1);
         assert_eq!(
-            request.request_line.target,
-            RequestTarget::Absolute {
-                uri: "https://test.com:8080/get/html?id=123&value=test".to_owned()
-            }
+            requests[1].warnings[0].error,
+            ParseError::InvalidExpectation("status created".to_string())
         );
-        assert_eq!(request.request_line.http_version, WithDefault::default());
-        assert_eq!(request.request_line.method, WithDefault::default());
+        assert_eq!(requests[1].settings.expectations, vec![]);
+        assert_eq!(requests[1].comments[0].value, "@expect status created");
+        for invalid in ["status 42", "header", "body-contains", "unknown 1", ""] {
+            assert_eq!(
+                invalid.parse::<model::Expectation>(),
+                Err(ParseError::InvalidExpectation(invalid.to_string()))
+            );
+        }
     }
 
     #[test]
-    pub fn request_target_multiline_with_version() {
-        let str = r#####"
-GET https://test.com:8080
-    /get
-    /html
-    ?id=123
-    &value=test HTTP/2.1
+    pub fn parse_import_directive() {
+        let str = r####"
+# @import ./common.http#Auth
+// @import shared/users.rest#Create User
+GET https://test.com/users
 
-        "#####;
-        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
+###
+# @import ./common.http
+GET https://test.com/missing
+"####;
+
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
         assert_eq!(errs, vec![]);
-        assert_eq!(requests.len(), 1);
-        let request = requests.remove(0);
+        assert_eq!(requests.len(), 2);
         assert_eq!(
-            request.request_line.target,
-            RequestTarget::Absolute {
-                uri: "https://test.com:8080/get/html?id=123&value=test".to_owned()
-            }
+            requests[0].settings.imports,
+            vec![
+                model::Import {
+                    path: "./common.http".to_string(),
+                    request_name: "Auth".to_string()
+                },
+                model::Import {
+                    path: "shared/users.rest".to_string(),
+                    request_name: "Create User".to_string()
+                },
+            ]
         );
         assert_eq!(
-            request.request_line.http_version,
-            WithDefault::Some(HttpVersion { major: 2, minor: 1 })
+            requests[0].settings.serialized(),
+            "# @import ./common.http#Auth\n# @import shared/users.rest#Create User\n"
         );
+
+        // a missing request name is reported as warning and the import is not recorded
+        assert_eq!(requests[1].warnings.len(), 1);
         assert_eq!(
-            request.request_line.method,
-            WithDefault::Some(HttpMethod::GET)
+            requests[1].warnings[0].error,
+            ParseError::MissingImportRequestName("./common.http".to_string())
+        );
+        assert_eq!(requests[1].settings.imports, vec![]);
+        assert_eq!(requests[1].comments[0].value, "@import ./common.http");
+
+        assert_eq!(
+            "#Auth".parse::<model::Import>(),
+            Err(ParseError::InvalidImport("#Auth".to_string()))
+        );
+        assert_eq!(
+            "".parse::<model::Import>(),
+            Err(ParseError::InvalidImport("".to_string()))
         );
     }
 
     #[test]
-    pub fn parse_simple_headers() {
-        let str = "Key1: Value1
-Key2: Value2
-Key3: Value3
-";
-        let mut scanner = Scanner::new(str);
-        let parsed = Parser::parse_headers(&mut scanner);
+    pub fn parse_auth_directive() {
+        let str = r####"
+# @auth ntlm
+GET https://test.com/ntlm
 
-        let parsed = parsed.expect("No error for simple headers");
+###
+// @auth Basic
+GET https://test.com/basic
 
-        assert_eq!(parsed.len(), 3);
-        assert_eq!(parsed[0], Header::new("Key1", "Value1"));
-        assert_eq!(parsed[1], Header::new("Key2", "Value2"));
-        assert_eq!(parsed[2], Header::new("Key3", "Value3"));
-    }
+###
+# @use-os-credentials
+GET https://test.com/os
 
-    #[test]
-    pub fn parse_headers_with_colon() {
-        let str = r###"Host: localhost:8080
-Custom: ::::::
+###
+# @auth kerberos-v9
+GET https://test.com/unknown
+"####;
 
-        "###;
-        let mut scanner = Scanner::new(str);
-        let parsed = Parser::parse_headers(&mut scanner).unwrap();
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[0].settings.auth, Some(model::AuthMode::Ntlm));
+        assert_eq!(requests[1].settings.auth, Some(model::AuthMode::Basic));
+        assert_eq!(
+            requests[2].settings.auth,
+            Some(model::AuthMode::OsCredentials)
+        );
+        assert_eq!(
+            requests[0].settings.serialized(),
+            "# @auth ntlm\n".to_string()
+        );
 
-        assert_eq!(parsed.len(), 2);
-        assert_eq!(parsed[0], Header::new("Host", "localhost:8080"));
-        assert_eq!(parsed[1], Header::new("Custom", "::::::"));
+        // an unknown mode is reported as warning and the line is kept as comment
+        let request = &requests[3];
+        assert_eq!(request.warnings.len(), 1);
+        assert_eq!(
+            request.warnings[0].error,
+            ParseError::UnknownAuthMode("kerberos-v9".to_string())
+        );
+        assert_eq!(request.settings.auth, None);
+        assert_eq!(request.comments[0].value, "@auth kerberos-v9");
+        assert_eq!(
+            request.request_line.target,
+            RequestTarget::from("https://test.com/unknown")
+        );
     }
 
     #[test]
-    pub fn parse_with_multipart_body_file() {
-        let str = r####"
-# With Multipart Body
-POST https://test.com/multipart
-Content-Type: multipart/form-data; boundary="--test_boundary"
+    pub fn parse_request_references() {
+        let str = r#####"
+### Login
+POST https://test.com/login
 
-----test_boundary
-Content-Disposition: form-data; name="part1_name"
+### Profile
+# @ref Login
+GET https://test.com/profile
 
-< path/to/file
-----test_boundary--
-"####;
+### Orders
+# @ref Profile
+// @ref Login
+GET https://test.com/orders
+"#####;
+        let result = Parser::parse(str, false);
+        assert_eq!(result.errs, vec![]);
+        let FileParseResult { ref requests, .. } = result;
+        assert_eq!(requests[0].settings.depends_on, Vec::<String>::new());
+        assert_eq!(requests[1].settings.depends_on, vec!["Login"]);
+        assert_eq!(requests[2].settings.depends_on, vec!["Profile", "Login"]);
+        assert_eq!(requests[1].comments, vec![]);
+        for name in requests[2].settings.depends_on.iter() {
+            assert!(result.find_request_by_name(name).is_some());
+        }
+        assert_eq!(result.find_request_by_name("Missing"), None);
+        assert_eq!(
+            requests[2].settings.serialized(),
+            "# @ref Profile\n# @ref Login\n"
+        );
+        assert_eq!(Parser::check(str), vec![]);
 
-        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
-        assert_eq!(errs, vec![]);
-        assert_eq!(requests.len(), 1);
-        let request = requests.remove(0);
+        let str = r#####"
+### Profile
+# @ref Login
+GET https://test.com/profile
 
+### Orders
+GET https://test.com/orders
+"#####;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 2);
         assert_eq!(
-            request.headers,
-            vec![Header::new(
-                "Content-Type",
-                "multipart/form-data; boundary=\"--test_boundary\""
+            requests[0].warnings,
+            vec![ParseErrorDetails::from(
+                ParseError::UnknownRequestReference("Login".to_string())
             )]
         );
-
+        assert_eq!(requests[0].settings.depends_on, vec!["Login".to_string()]);
+        assert_eq!(requests[1].warnings, vec![]);
+        // unknown references are warnings, the file is still valid
+        assert_eq!(Parser::check(str), vec![]);
+
+        // references of requests that failed are checked as well
+        let FileParseResult { errs, .. } =
+            Parser::parse(&str.replace("GET https://test.com/profile", "GET"), false);
         assert_eq!(
-            request.body,
-            model::RequestBody::Multipart {
-                boundary: "--test_boundary".to_string(),
-                parts: vec![Multipart {
-                    disposition: DispositionField::new_with_filename("part1_name", None::<String>),
-                    data: DataSource::FromFilepath("path/to/file".to_string()),
-                    headers: vec![]
-                }]
-            }
-        )
+            errs[0].partial_request.warnings,
+            vec![ParseErrorDetails::from(
+                ParseError::UnknownRequestReference("Login".to_string())
+            )]
+        );
     }
 
     #[test]
-    pub fn parse_with_multipart_body_text() {
-        let str = r####"
-# With Multipart Body
-POST https://test.com/multipart
-Content-Type: multipart/form-data; boundary="--test.?)()test"
-
-----test.?)()test
-Content-Disposition: form-data; name="text"
+    pub fn parse_require_explicit_method() {
+        let str = r#####"
+https://test.com/implicit
 
-some text
+###
+GET https://test.com/explicit
+"#####;
+        // by default GET is assumed without any error
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(requests.len(), 2);
+        assert_eq!(errs, vec![]);
 
-----test.?)()test
-Content-Disposition: form-data; name="text"
+        let options = ParserOptions {
+            require_explicit_method: true,
+            ..ParserOptions::default()
+        };
+        let FileParseResult { requests, errs } = Parser::parse_with_options(str, &options);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].warnings.len(), 1);
+        assert_eq!(requests[0].warnings[0].error, ParseError::ImplicitMethod);
+        assert_eq!(requests[1].warnings, vec![]);
+        // the request is still complete with the default method
+        let request = requests[0].clone();
+        assert!(request.request_line.method.is_default());
+        assert_eq!(
+            request.request_line.method.unwrap_or_default(),
+            HttpMethod::GET
+        );
+        assert_eq!(
+            request.request_line.target,
+            RequestTarget::from("https://test.com/implicit")
+        );
+    }
 
-more content
+    #[test]
+    pub fn parse_request_line_only_method() {
+        let FileParseResult { requests, errs } =
+            Parser::parse("POST\nContent-Type: application/json\n\n{}", false);
+        assert_eq!(requests, vec![]);
+        assert_eq!(errs.len(), 1);
+        assert_eq!(
+            errs[0].details[0].error,
+            ParseError::MissingRequestTargetAfterMethod("POST".to_string())
+        );
+        assert_eq!(errs[0].details[0].start_pos, Some(0));
+        assert_eq!(errs[0].details[0].end_pos, Some(4));
+        assert_eq!(errs[0].partial_request.request_line, None);
 
+        let FileParseResult { errs, .. } =
+            Parser::parse("GET https://test.com\n\n###\nPOST\n", false);
+        assert_eq!(errs[0].details[0].start_pos, Some(26));
 
-----test.?)()test--
-"####;
+        // a single token which is no method is still the target
+        let FileParseResult { requests, errs } = Parser::parse("test.com/post", false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(
+            requests[0].request_line.target,
+            RequestTarget::from("test.com/post")
+        );
+        assert_eq!(requests[0].request_line.method, WithDefault::default());
+    }
 
-        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
+    #[test]
+    pub fn parse_request_line_tokens() {
+        let str = r#"GET https://test.com/a%20b "HTTP/2" # trailing comment
+Accept: */*"#;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
         assert_eq!(errs, vec![]);
-        assert_eq!(requests.len(), 1);
-        let request = requests.remove(0);
+        assert_eq!(
+            requests[0].request_line,
+            RequestLine {
+                method: WithDefault::Some(HttpMethod::GET),
+                target: RequestTarget::from("https://test.com/a%20b"),
+                http_version: WithDefault::Some(model::HttpVersion { major: 2, minor: 0 }),
+            }
+        );
+        assert_eq!(requests[0].headers, vec![Header::new("Accept", "*/*")]);
 
+        let FileParseResult { errs, .. } =
+            Parser::parse("GET https://test.com HTTP/1.1 extra", false);
         assert_eq!(
-            request.headers,
-            vec![Header::new(
-                "Content-Type",
-                "multipart/form-data; boundary=\"--test.?)()test\""
-            )]
+            errs[0].details[0].error,
+            ParseError::TooManyElementsOnRequestLine("extra".to_string())
         );
+    }
 
+    #[test]
+    pub fn parse_quoted_request_target() {
+        let str = r#"GET "https://example.com/a b?q=c d" HTTP/1.1
+Accept: */*"#;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
         assert_eq!(
-            request.body,
-            model::RequestBody::Multipart {
-                boundary: "--test.?)()test".to_string(),
-                parts: vec![
-                    Multipart {
-                        disposition: DispositionField::new("text"),
-                        headers: vec![],
-                        data: DataSource::Raw("some text\n".to_string()),
-                    },
-                    Multipart {
-                        disposition: DispositionField::new("text"),
-                        headers: vec![],
-                        data: DataSource::Raw("more content\n\n".to_string()),
-                    }
-                ]
+            requests[0].request_line,
+            RequestLine {
+                method: WithDefault::Some(HttpMethod::GET),
+                target: RequestTarget::Absolute {
+                    uri: "https://example.com/a%20b?q=c%20d".to_string()
+                },
+                http_version: WithDefault::Some(model::HttpVersion { major: 1, minor: 1 }),
             }
-        )
+        );
+        let uri = requests[0].request_line.target.to_string();
+        assert_eq!(uri.parse::<http::Uri>().unwrap().path(), "/a%20b");
+
+        // quotes without whitespace are removed, unquoted targets are unchanged
+        for str in [
+            "GET \"https://example.com/a%20b\"",
+            "GET https://example.com/a%20b",
+            "\"https://example.com/a%20b\"",
+        ] {
+            let FileParseResult { requests, errs } = Parser::parse(str, false);
+            assert_eq!(errs, vec![]);
+            assert_eq!(
+                requests[0].request_line.target,
+                RequestTarget::Absolute {
+                    uri: "https://example.com/a%20b".to_string()
+                }
+            );
+        }
+
+        // the serialized target is percent encoded and parsed the same again
+        let serialized = crate::Serializer::serialize_request(&requests[0]);
+        assert!(serialized.contains("https://example.com/a%20b?q=c%20d"));
+        let FileParseResult {
+            requests: reparsed, ..
+        } = Parser::parse(&serialized, false);
+        assert_eq!(reparsed[0].request_line, requests[0].request_line);
     }
 
     #[test]
-    pub fn parse_multipart_with_content_types() {
-        let str = r#####"
-### Send a form with the text and file fields
-POST https://httpbin.org/post
-Content-Type: multipart/form-data; boundary=WebAppBoundary
-
---WebAppBoundary
-Content-Disposition: form-data; name="element-name"
-Content-Type: text/plain
+    pub fn validate_http_version() {
+        let version = model::HttpVersion::from_str("HTTP/1.1").expect("Version 1.1 to be valid");
+        assert_eq!(version, model::HttpVersion { major: 1, minor: 1 });
 
-Name
---WebAppBoundary
-Content-Disposition: form-data; name="data"; filename="data.json"
-Content-Type: application/json
+        let version = model::HttpVersion::from_str("HTTP/1.2").expect("Version 1.2 to be valid");
+        assert_eq!(version, model::HttpVersion { major: 1, minor: 2 });
 
-< ./request-form-data.json
---WebAppBoundary--
-        "#####;
+        let version = model::HttpVersion::from_str("HTTP/2.0").expect("Version 2.0 to be valid");
+        assert_eq!(version, model::HttpVersion { major: 2, minor: 0 });
 
-        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
-        assert_eq!(errs, vec![]);
-        assert_eq!(requests.len(), 1);
+        let version = model::HttpVersion::from_str("HTTP/2.1").expect("Version 2.1 to be valid");
+        assert_eq!(version, model::HttpVersion { major: 2, minor: 1 });
 
-        let request = requests.remove(0);
+        assert!(model::HttpVersion::from_str("invalid").is_err());
 
+        let http_1_0 = model::HttpVersion::from_str("HTTP/1.0").unwrap();
+        let http_1_1 = model::HttpVersion::from_str("HTTP/1.1").unwrap();
+        let http_2 = model::HttpVersion::from_str("HTTP/2.0").unwrap();
+        let http_3 = model::HttpVersion::from_str("HTTP/3").unwrap();
+        assert!(http_2 > http_1_1);
+        assert!(http_1_1 > http_1_0);
+        assert!(http_3 > model::HttpVersion { major: 2, minor: 1 });
+        assert_eq!(http_1_1.clone().max(http_1_0.clone()), http_1_1);
         assert_eq!(
-            request.headers,
-            vec![Header::new(
-                "Content-Type",
-                "multipart/form-data; boundary=WebAppBoundary"
-            )]
+            model::HttpVersion::default().cmp(&http_1_1),
+            std::cmp::Ordering::Equal
         );
 
-        assert_eq!(
-            request.body,
-            model::RequestBody::Multipart {
-                boundary: "WebAppBoundary".to_string(),
-                parts: vec![
-                    Multipart {
-                        data: DataSource::Raw("Name".to_string()),
-                        disposition: DispositionField::new("element-name"),
-                        headers: vec![Header {
-                            key: "Content-Type".to_string(),
-                            value: "text/plain".to_string()
-                        }]
-                    },
-                    Multipart {
-                        data: DataSource::FromFilepath("./request-form-data.json".to_string()),
-                        disposition: DispositionField::new_with_filename("data", Some("data.json")),
-                        headers: vec![Header {
-                            key: "Content-Type".to_string(),
-                            value: "application/json".to_string()
-                        }]
-                    }
-                ]
-            }
-        )
+        assert!(!http_1_0.supports_multiplexing());
+        assert!(!http_1_1.supports_multiplexing());
+        assert!(http_2.supports_multiplexing());
+        assert!(http_3.supports_multiplexing());
     }
 
     #[test]
-    pub fn parse_multipart_binary() {
+    pub fn request_target_multiline() {
         let str = r#####"
-POST /upload HTTP/1.1
-Host: localhost:8080
-Content-Type: multipart/form-data; boundary=/////////////////////////////
-Content-Length: 676
-
---/////////////////////////////
-Content-Disposition: form-data; name="file"; filename="binaryfile.tar.gz"
-Content-Type: application/x-gzip
-Content-Transfer-Encoding: base64
+GET https://test.com:8080
+    /get
+    /html
+    ?id=123
+    &value=test
 
-H4sIAGiNIU8AA+3R0W6CMBQGYK59iobLZantRDG73osUOGqnFNJWM2N897UghG1ZdmWWLf93U/jP4bRAq8q92hJ/dY1J7kQEqyyLq8yXYrp2ltkqkTKXYiEykYc++ZTLVcLEvQ40dXReWcYSV1pdnL/v+6n+R11mjKVG1ZQ+s3TT2FpXqjhQ+hjzE1mnGxNLkgu+7tOKWjIVmVKTC6XL9ZaeXj4VQhwKWzL+cI4zwgQuuhkh3mhTad/Hkssh3im3027X54JnQ360R/M19OT8kC7SEN7Ooi2VvrEfznHQRWzl83gxttZKmzGehzPRW/+W8X+3fvL8sFet9sS6m3EIma02071MU3Uf9KHrmV1/+y8DAAAAAAAAAAAAAAAAAAAAAMB/9A6txIuJACgAAA==
---/////////////////////////////--
         "#####;
-
         let FileParseResult { mut requests, errs } = Parser::parse(str, false);
         assert_eq!(errs, vec![]);
         assert_eq!(requests.len(), 1);
         let request = requests.remove(0);
-
         assert_eq!(
-            request.headers,
-            vec![
-                Header::new("Host", "localhost:8080"),
-                Header::new(
-                    "Content-Type",
-                    r#"multipart/form-data; boundary=/////////////////////////////"#
-                ),
-                Header::new("Content-Length", "676")
-            ]
+            request.request_line.target,
+            RequestTarget::Absolute {
+                uri: "https://test.com:8080/get/html?id=123&value=test".to_owned()
+            }
+        );
+        assert_eq!(request.request_line.target.host(), Some("test.com"));
+        assert_eq!(request.request_line.target.port(), Some(8080));
+        assert_eq!(request.request_line.target.path(), "/get/html");
+        assert_eq!(
+            request.request_line.target.query(),
+            Some("id=123&value=test")
+        );
+        assert_eq!(request.request_line.http_version, WithDefault::default());
+        assert_eq!(
+            request.request_line.method,
+            WithDefault::Some(HttpMethod::GET)
         );
+    }
 
-        // @TODO check content
+    #[test]
+    pub fn request_target_multiline_trailing_comment() {
+        let str = "
+GET https://test.com:8080
+    /get
+    /html
+    ?id=123 # the id
+    &value=test // the value
+
+        ";
+        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        let request = requests.remove(0);
         assert_eq!(
-            request.body,
-            model::RequestBody::Multipart {
-                boundary: r#"/////////////////////////////"#.to_string(),
-                parts: vec![model::Multipart {
-                    disposition: DispositionField::new_with_filename("file", Some("binaryfile.tar.gz")),
-                    headers: vec![
-                        Header {
-                            key: "Content-Type".to_string(),
-                            value: "application/x-gzip".to_string()
-                        },
-                        Header {
-                            key: "Content-Transfer-Encoding".to_string(),
-                            value: "base64".to_string()
-                        }
-                    ],
-                    data: DataSource::Raw("H4sIAGiNIU8AA+3R0W6CMBQGYK59iobLZantRDG73osUOGqnFNJWM2N897UghG1ZdmWWLf93U/jP4bRAq8q92hJ/dY1J7kQEqyyLq8yXYrp2ltkqkTKXYiEykYc++ZTLVcLEvQ40dXReWcYSV1pdnL/v+6n+R11mjKVG1ZQ+s3TT2FpXqjhQ+hjzE1mnGxNLkgu+7tOKWjIVmVKTC6XL9ZaeXj4VQhwKWzL+cI4zwgQuuhkh3mhTad/Hkssh3im3027X54JnQ360R/M19OT8kC7SEN7Ooi2VvrEfznHQRWzl83gxttZKmzGehzPRW/+W8X+3fvL8sFet9sS6m3EIma02071MU3Uf9KHrmV1/+y8DAAAAAAAAAAAAAAAAAAAAAMB/9A6txIuJACgAAA==".to_string())
-                }]
+            request.request_line.target,
+            RequestTarget::Absolute {
+                uri: "https://test.com:8080/get/html?id=123&value=test".to_owned()
             }
-        )
+        );
     }
 
     #[test]
-    pub fn parse_json_body() {
-        let str = r#####"
-GET http://localhost/api/json/get?id=12345
-Authorization: Basic dev-user dev-password
-Content-Type: application/json
-
-{
-    "key": "my-dev-value"
-}"#####;
+    pub fn request_target_multiline_inconsistent_indentation() {
+        let str = "
+GET https://test.com:8080
+    /get
+\t/html
+    ?id=123
+";
+        let target = RequestTarget::Absolute {
+            uri: "https://test.com:8080/get/html?id=123".to_owned(),
+        };
+        // mixed indentation is not reported by default
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests[0].warnings, vec![]);
+        assert_eq!(requests[0].request_line.target, target);
 
-        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
+        let options = ParserOptions {
+            warn_inconsistent_indentation: true,
+            ..ParserOptions::default()
+        };
+        let FileParseResult { requests, errs } = Parser::parse_with_options(str, &options);
         assert_eq!(errs, vec![]);
         assert_eq!(requests.len(), 1);
-
-        let request = requests.remove(0);
-
+        assert_eq!(requests[0].warnings.len(), 1);
         assert_eq!(
-            request.headers,
-            vec![
-                Header::new("Authorization", r#"Basic dev-user dev-password"#),
-                Header::new("Content-Type", "application/json")
-            ]
+            requests[0].warnings[0].error,
+            ParseError::InconsistentRequestLineIndentation
         );
+        // the target is still joined
+        assert_eq!(requests[0].request_line.target, target);
+    }
+
+    #[test]
+    pub fn request_target_multiline_no_method() {
+        let str = r#####"
+https://test.com:8080
+    /get
+    /html
+    ?id=123
+    &value=test
 
+        "#####;
+        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        let request = requests.remove(0);
         assert_eq!(
-            request.body,
-            model::RequestBody::Raw {
-                data: DataSource::Raw(
-                    r#"{
-    "key": "my-dev-value"
-}"#
-                    .to_string()
-                )
+            request.request_line.target,
+            RequestTarget::Absolute {
+                uri: "https://test.com:8080/get/html?id=123&value=test".to_owned()
             }
-        )
+        );
+        assert_eq!(request.request_line.http_version, WithDefault::default());
+        assert_eq!(request.request_line.method, WithDefault::default());
     }
 
     #[test]
-    pub fn parse_json_body_fileinput() {
+    pub fn request_target_multiline_with_version() {
         let str = r#####"
-POST http://example.com/api/add
-Content-Type: application/json
-
-< ./input.json
+GET https://test.com:8080
+    /get
+    /html
+    ?id=123
+    &value=test HTTP/2.1
 
         "#####;
-
         let FileParseResult { mut requests, errs } = Parser::parse(str, false);
         assert_eq!(errs, vec![]);
         assert_eq!(requests.len(), 1);
-
         let request = requests.remove(0);
-
         assert_eq!(
-            request.headers,
-            vec![Header::new("Content-Type", "application/json")]
+            request.request_line.target,
+            RequestTarget::Absolute {
+                uri: "https://test.com:8080/get/html?id=123&value=test".to_owned()
+            }
         );
-
-        // @TODO check content
         assert_eq!(
-            request.body,
-            model::RequestBody::Raw {
-                data: DataSource::FromFilepath("./input.json".to_string())
-            }
-        )
+            request.request_line.http_version,
+            WithDefault::Some(HttpVersion { major: 2, minor: 1 })
+        );
+        assert_eq!(
+            request.request_line.method,
+            WithDefault::Some(HttpMethod::GET)
+        );
     }
 
     #[test]
-    pub fn parse_url_form_encoded_end_of_file() {
-        let str = r####"# @name=Create Checkout Session
-POST {{base_url}}/create_checkout_session?a=aa
-Content-Type: application/x-www-form-urlencoded
+    pub fn parse_simple_headers() {
+        let str = "Key1: Value1
+Key2: Value2
+Key3: Value3
+";
+        let mut scanner = Scanner::new(str);
+        let parsed = Parser::parse_headers(&mut scanner, &mut Vec::new());
 
-abc=def&ghi=jkl"####;
-        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
+        let parsed = parsed.expect("No error for simple headers");
+
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed[0], Header::new("Key1", "Value1"));
+        assert_eq!(parsed[1], Header::new("Key2", "Value2"));
+        assert_eq!(parsed[2], Header::new("Key3", "Value3"));
+    }
+
+    #[test]
+    pub fn parse_body_missing_blank_line() {
+        let str = r#"POST https://test.com
+Content-Type: application/json
+{
+  "id": 1
+}
+
+###
+POST https://test.com
+Content-Type: application/json
+< ./input.json
+"#;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
         assert_eq!(errs, vec![]);
-        assert_eq!(requests.len(), 1);
-        let request = requests.remove(0);
+        assert_eq!(requests.len(), 2);
 
+        let request = requests[0].clone();
+        assert_eq!(
+            request
+                .warnings
+                .iter()
+                .map(|details| &details.error)
+                .collect::<Vec<_>>(),
+            vec![&ParseError::MissingBlankLineBeforeBody("{".to_string())]
+        );
         assert_eq!(
             request.headers,
-            vec![Header::new(
-                "Content-Type",
-                "application/x-www-form-urlencoded"
-            )]
+            vec![Header::new("Content-Type", "application/json")]
+        );
+        assert_eq!(
+            request.body,
+            RequestBody::Raw {
+                data: DataSource::Raw("{\n  \"id\": 1\n}".to_string())
+            }
         );
 
+        let request = &requests[1];
+        assert_eq!(
+            request.warnings[0].error,
+            ParseError::MissingBlankLineBeforeBody("< ./input.json".to_string())
+        );
         assert_eq!(
             request.body,
-            RequestBody::UrlEncoded {
-                url_encoded_params: vec![
-                    UrlEncodedParam::new("abc", "def"),
-                    UrlEncodedParam::new("ghi", "jkl"),
-                ]
+            RequestBody::Raw {
+                data: DataSource::FromFilepath("./input.json".to_string())
             }
-        )
+        );
+
+        // other lines are still invalid headers
+        let FileParseResult { errs, .. } = Parser::parse(
+            "POST https://test.com
+Content-Type: text/plain
+body",
+            false,
+        );
+        assert_eq!(
+            errs[0].details[0].error,
+            ParseError::InvalidHeaderField("body".to_string())
+        );
     }
 
     #[test]
-    pub fn parse_url_form_encoded() {
-        let str = r####"
-POST https://test.com/formEncoded
-Content-Type: application/x-www-form-urlencoded
+    pub fn parse_multiline_target_with_hash_in_query() {
+        let str = r#####"
+GET https://test.com/api
+    ?id=1
+    &tag=#important
+    #section=2
+    &note=#1 # trailing comment
+Accept: */*
+"#####;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(
+            requests[0].request_line.target,
+            RequestTarget::Absolute {
+                uri: "https://test.com/api?id=1&tag=#important#section=2&note=#1".to_string()
+            }
+        );
+        assert_eq!(requests[0].comments, vec![]);
+        assert_eq!(requests[0].headers, vec![Header::new("Accept", "*/*")]);
+    }
 
-firstKey=firstValue&secondKey=secondValue&empty=
-"####;
+    #[test]
+    pub fn parse_with_diagnostics_sink() {
+        let diagnostics: std::sync::Arc<std::sync::Mutex<Vec<(ParseError, String)>>> =
+            Default::default();
+        let captured = diagnostics.clone();
+        let options = ParserOptions {
+            print_errors: true,
+            diagnostics_sink: Some(DiagnosticsSink::new(move |details, message| {
+                captured
+                    .lock()
+                    .unwrap()
+                    .push((details.error.clone(), message.to_string()));
+            })),
+            ..ParserOptions::default()
+        };
 
-        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
-        assert_eq!(errs, vec![]);
-        assert_eq!(requests.len(), 1);
-        let request = requests.remove(0);
+        let str = "GET https://test.com\n\n###\n# @http-version abc\nGET https://test.com/other\n";
+        let FileParseResult { errs, .. } = Parser::parse_with_options(str, &options);
+        assert_eq!(errs.len(), 1);
 
+        let diagnostics = diagnostics.lock().unwrap();
+        assert_eq!(diagnostics.len(), 1);
         assert_eq!(
-            request.headers,
-            vec![Header::new(
-                "Content-Type",
-                "application/x-www-form-urlencoded"
-            )]
+            diagnostics[0].0,
+            ParseError::InvalidHttpVersion("abc".to_string())
         );
+        assert!(diagnostics[0].1.starts_with("Error: "));
+        assert!(diagnostics[0].1.contains("\n# @http-version abc\n"));
+        assert_eq!(options.clone(), options);
+    }
+
+    #[test]
+    pub fn parse_file_with_options() {
+        let dir = std::env::temp_dir().join("http_rest_file_with_options");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("options.http");
+        std::fs::write(
+            &path,
+            "POST https://test.com\nContent-Type: text/plain\n\n<<< EOF\nbody\n\n### kept\nEOF\n\
+             ###\nGET\n",
+        )
+        .unwrap();
 
+        let options = ParserOptions {
+            print_errors: false,
+            heredoc_bodies: true,
+            ..ParserOptions::default()
+        };
+        let file = Parser::parse_file_with_options(&path, &options).unwrap();
+        assert_eq!(file.requests.len(), 1);
+        assert_eq!(file.errs.len(), 1);
+        assert_eq!(file.extension, Some(HttpRestFileExtension::Http));
         assert_eq!(
-            request.body,
-            RequestBody::UrlEncoded {
-                url_encoded_params: vec![
-                    UrlEncodedParam::new("firstKey", "firstValue"),
-                    UrlEncodedParam::new("secondKey", "secondValue"),
-                    UrlEncodedParam::new("empty", ""),
-                ]
+            file.requests[0].body,
+            RequestBody::Raw {
+                data: DataSource::Raw("body\n\n### kept".to_string())
             }
-        )
+        );
+
+        assert_eq!(
+            Parser::parse_file_with_options(&dir.join("missing.http"), &options),
+            Err(ParseError::CouldNotReadRequestFile(
+                dir.join("missing.http")
+            ))
+        );
     }
 
     #[test]
-    pub fn parse_multiple_requests() {
-        let str = r#####"
-POST http://example.com/api/add
-Content-Type: application/json
+    pub fn edit_http_rest_file() {
+        let dir = std::env::temp_dir().join("http_rest_file_edit");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("edit.http");
+        std::fs::write(
+            &path,
+            "### first\nGET https://test.com/first\n\n### second\nGET https://test.com/second",
+        )
+        .unwrap();
+        let mut file = Parser::parse_file(&path).unwrap();
 
-< ./input.json
-###
+        file.push_request(Request {
+            name: Some("third".to_string()),
+            request_line: RequestLine {
+                method: WithDefault::Some(HttpMethod::POST),
+                target: RequestTarget::from("https://test.com/third"),
+                http_version: WithDefault::default(),
+            },
+            ..Default::default()
+        });
+        assert_eq!(file.requests[2].index, 2);
+        file.requests_mut()[0]
+            .headers
+            .push(Header::new("Accept", "*/*"));
+
+        let removed = file.remove_request(1).unwrap();
+        assert_eq!(removed.name, Some("second".to_string()));
+        assert_eq!(file.remove_request(2), None);
+        assert_eq!(
+            file.requests
+                .iter()
+                .map(|request| request.index)
+                .collect::<Vec<usize>>(),
+            vec![0, 1]
+        );
 
-GET https://example.com/first
-###
-GET https://example.com/second
+        crate::Serializer::serialize_to_file(&file).unwrap();
+        let parsed = Parser::parse_file(&path).unwrap();
+        assert_eq!(parsed.errs, vec![]);
+        assert_eq!(parsed.requests.len(), 2);
+        assert_eq!(
+            parsed.requests[0].headers,
+            vec![Header::new("Accept", "*/*")]
+        );
+        assert_eq!(parsed.requests[1].name, Some("third".to_string()));
+        assert_eq!(parsed.requests[1].method(), HttpMethod::POST);
+    }
 
+    #[test]
+    pub fn http_rest_file_enabled_requests() {
+        let str = r#####"
+### first
+GET https://test.com/first
 
-###
-        "#####;
+### second
+# @disabled
+GET https://test.com/second
 
-        let FileParseResult { requests, errs } = dbg!(Parser::parse(str, false));
-        println!("errs: {:?}", errs);
-        assert_eq!(errs.len(), 1);
-        assert_eq!(requests.len(), 3);
+### third
+GET https://test.com/third
+"#####;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        let file = model::HttpRestFile {
+            requests,
+            errs,
+            path: Box::new(std::path::PathBuf::from("requests.http")),
+            extension: Some(HttpRestFileExtension::Http),
+            modeline: None,
+        };
 
-        // @TODO check content
+        let names = |requests: Vec<&Request>| {
+            requests
+                .into_iter()
+                .map(|request| request.name.clone().unwrap())
+                .collect::<Vec<String>>()
+        };
         assert_eq!(
-            requests,
-            vec![
-                model::Request {
-                    name: None,
-                    comments: vec![],
-                    headers: vec![Header {
-                        key: "Content-Type".to_string(),
-                        value: "application/json".to_string()
+            names(file.enabled_requests().collect()),
+            vec!["first", "third"]
+        );
+        assert_eq!(
+            names(file.all_requests().collect()),
+            vec!["first", "second", "third"]
+        );
+    }
+
+    #[test]
+    pub fn parse_with_shebang_or_modeline() {
+        for first_line in [
+            "#!/usr/bin/env http",
+            "# -*- mode: restclient -*-",
+            "// vim: set ft=http:",
+        ] {
+            let rest = "# comment\n# second comment\nGET https://test.com";
+            let str = format!("{}\n{}", first_line, rest);
+            assert_eq!(Parser::parse(&str, false), Parser::parse(rest, false));
+            assert_eq!(Parser::modeline(&str), Some(first_line));
+
+            let str = format!("{}\nGET https://test.com", first_line);
+            let FileParseResult { requests, errs } = Parser::parse(&str, false);
+            assert_eq!(errs, vec![]);
+            assert_eq!(requests[0].comments, vec![]);
+        }
+        assert_eq!(Parser::modeline("# comment\nGET https://test.com"), None);
+        assert_eq!(Parser::modeline("GET https://test.com"), None);
+
+        let dir = std::env::temp_dir().join("http_rest_file_modeline");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("modeline.http");
+        std::fs::write(&path, "#!/usr/bin/env http\nGET https://test.com").unwrap();
+        let file = Parser::parse_file(&path).unwrap();
+        assert_eq!(file.modeline, Some("#!/usr/bin/env http".to_string()));
+        assert_eq!(file.requests[0].comments, vec![]);
+
+        std::fs::write(&path, "").unwrap();
+        crate::Serializer::serialize_to_file(&file).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("#!/usr/bin/env http\nGET https://test.com"));
+    }
+
+    #[test]
+    pub fn parse_with_byte_order_mark() {
+        let str = "### name\nGET https://x.com";
+        let with_bom = format!("\u{FEFF}{}", str);
+        let result = Parser::parse(&with_bom, false);
+        assert_eq!(result, Parser::parse(str, false));
+        assert_eq!(result.requests[0].name, Some("name".to_string()));
+        assert_eq!(result.requests[0].comments, vec![]);
+
+        assert_eq!(Parser::check(&with_bom), vec![]);
+        assert_eq!(
+            Parser::parse_request_str(&with_bom).unwrap(),
+            Parser::parse_request_str(str).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn parse_invalid_headers() {
+        let str = "POST https://test.com\nBad Name: value\nX-Injected: first\rEvil: second\n\
+                   X-Escaped: first\\r\\nEvil: second\nAccept: */*\n\n{}";
+
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        let request = requests[0].clone();
+        let warnings = request
+            .warnings
+            .iter()
+            .map(|details| details.error.clone())
+            .collect::<Vec<ParseError>>();
+        assert_eq!(
+            warnings,
+            vec![
+                ParseError::InvalidHeaderName("Bad Name".to_string()),
+                ParseError::InvalidHeaderValue("X-Injected".to_string()),
+                ParseError::InvalidHeaderValue("X-Escaped".to_string()),
+            ]
+        );
+        assert_eq!(request.warnings[0].start_pos, Some(22));
+
+        // parsing continues after the invalid headers
+        assert_eq!(request.headers.len(), 4);
+        assert_eq!(request.headers[3], Header::new("Accept", "*/*"));
+        assert!(request.body.is_present());
+
+        // carriage returns of line endings and variables in names are valid
+        let str = "POST https://test.com\r\nX-{{name}}: value\r\nAccept: */*\r\n";
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests[0].warnings, vec![]);
+
+        assert!(matches!(
+            Parser::parse_headers_str("Bad Name: value"),
+            Err(ParseErrorDetails {
+                error: ParseError::InvalidHeaderName(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    pub fn parse_headers_str() {
+        let str = "Host: localhost:8080\nAccept: */*\n\nIgnored: after blank line\n";
+        assert_eq!(
+            Parser::parse_headers_str(str),
+            Ok(vec![
+                Header::new("Host", "localhost:8080"),
+                Header::new("Accept", "*/*")
+            ])
+        );
+        assert_eq!(Parser::parse_headers_str(""), Ok(vec![]));
+
+        let err = Parser::parse_headers_str("Host: localhost\ninvalid\n").unwrap_err();
+        assert_eq!(
+            err.error,
+            ParseError::InvalidHeaderField("invalid".to_string())
+        );
+    }
+
+    #[test]
+    pub fn parse_headers_with_colon() {
+        let str = r###"Host: localhost:8080
+Custom: ::::::
+
+        "###;
+        let mut scanner = Scanner::new(str);
+        let parsed = Parser::parse_headers(&mut scanner, &mut Vec::new()).unwrap();
+
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0], Header::new("Host", "localhost:8080"));
+        assert_eq!(parsed[1], Header::new("Custom", "::::::"));
+    }
+
+    #[test]
+    pub fn parse_with_multipart_body_file() {
+        let str = r####"
+# With Multipart Body
+POST https://test.com/multipart
+Content-Type: multipart/form-data; boundary="--test_boundary"
+
+----test_boundary
+Content-Disposition: form-data; name="part1_name"
+
+< path/to/file
+----test_boundary--
+"####;
+
+        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        let request = requests.remove(0);
+
+        assert_eq!(
+            request.headers,
+            vec![Header::new(
+                "Content-Type",
+                "multipart/form-data; boundary=\"--test_boundary\""
+            )]
+        );
+
+        assert_eq!(
+            request.body,
+            model::RequestBody::Multipart {
+                boundary: "--test_boundary".to_string(),
+                parts: vec![Multipart {
+                    disposition: DispositionField::new_with_filename("part1_name", None::<String>),
+                    data: DataSource::FromFilepath("path/to/file".to_string()),
+                    headers: vec![]
+                }]
+            }
+        )
+    }
+
+    #[test]
+    pub fn parse_multipart_quoted_boundary_with_semicolon() {
+        let str = r####"
+POST https://test.com/multipart
+Content-Type: multipart/form-data; boundary="a;b"
+
+--a;b
+Content-Disposition: form-data; name="part1_name"
+
+< path/to/file
+--a;b--
+"####;
+
+        let FileParseResult { requests, mut errs } = Parser::parse(str, false);
+        assert_eq!(requests.len(), 0);
+        assert_eq!(errs.len(), 1);
+        let err = errs.remove(0);
+        // ';' is not allowed within a boundary, but the whole quoted value is used
+        assert_eq!(
+            err.details
+                .into_iter()
+                .map(|details| details.error)
+                .collect::<Vec<ParseError>>(),
+            vec![ParseError::InvalidMultipartBoundaryCharacter(
+                ";".to_string()
+            )]
+        );
+        assert_eq!(
+            err.partial_request.body,
+            Some(model::RequestBody::Multipart {
+                boundary: "a;b".to_string(),
+                parts: vec![Multipart {
+                    disposition: DispositionField::new("part1_name"),
+                    data: DataSource::FromFilepath("path/to/file".to_string()),
+                    headers: vec![]
+                }]
+            })
+        );
+
+        // further parameters after an unquoted boundary are not part of it
+        let str = r####"
+POST https://test.com/multipart
+Content-Type: multipart/form-data; boundary=simple; charset=utf-8
+
+--simple
+Content-Disposition: form-data; name="part1_name"
+
+< path/to/file
+--simple--
+"####;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert!(matches!(
+            &requests[0].body,
+            model::RequestBody::Multipart { boundary, .. } if boundary == "simple"
+        ));
+    }
+
+    #[test]
+    pub fn parse_heredoc_body() {
+        let str = r####"
+POST https://test.com/text
+Content-Type: text/plain
+
+<<< EOF
+first line
+
+### not a new request
+EOF
+
+> {% client.log("done") %}
+
+###
+GET https://test.com/next
+"####;
+        let options = ParserOptions {
+            heredoc_bodies: true,
+            ..ParserOptions::default()
+        };
+        let FileParseResult { requests, errs } = Parser::parse_with_options(str, &options);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 2);
+        assert_eq!(
+            requests[0].body,
+            model::RequestBody::Raw {
+                data: DataSource::Raw("first line\n\n### not a new request".to_string())
+            }
+        );
+        assert_eq!(
+            requests[0].response_handler,
+            Some(ResponseHandler::Script(
+                " client.log(\"done\") ".to_string()
+            ))
+        );
+        assert_eq!(
+            requests[1].request_line.target,
+            RequestTarget::from("https://test.com/next")
+        );
+
+        // without the option the heredoc is not recognized
+        let FileParseResult { requests, .. } = Parser::parse(str, false);
+        assert_ne!(
+            requests[0].body,
+            model::RequestBody::Raw {
+                data: DataSource::Raw("first line\n\n### not a new request".to_string())
+            }
+        );
+    }
+
+    #[test]
+    pub fn parse_heredoc_body_missing_end() {
+        let str = "POST https://test.com/text\n\n<<< EOF\nsome text\n";
+        let options = ParserOptions {
+            heredoc_bodies: true,
+            ..ParserOptions::default()
+        };
+        let FileParseResult { requests, errs } = Parser::parse_with_options(str, &options);
+        assert_eq!(requests.len(), 0);
+        assert_eq!(
+            errs[0].details[0].error,
+            ParseError::MissingHeredocBodyEnd("EOF".to_string())
+        );
+        assert_eq!(
+            errs[0].partial_request.body,
+            Some(model::RequestBody::Raw {
+                data: DataSource::Raw("some text".to_string())
+            })
+        );
+    }
+
+    #[test]
+    pub fn parse_with_multipart_body_text() {
+        let str = r####"
+# With Multipart Body
+POST https://test.com/multipart
+Content-Type: multipart/form-data; boundary="--test.?)()test"
+
+----test.?)()test
+Content-Disposition: form-data; name="text"
+
+some text
+
+----test.?)()test
+Content-Disposition: form-data; name="text"
+
+more content
+
+
+----test.?)()test--
+"####;
+
+        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        let request = requests.remove(0);
+
+        assert_eq!(
+            request.headers,
+            vec![Header::new(
+                "Content-Type",
+                "multipart/form-data; boundary=\"--test.?)()test\""
+            )]
+        );
+
+        assert_eq!(
+            request.body,
+            model::RequestBody::Multipart {
+                boundary: "--test.?)()test".to_string(),
+                parts: vec![
+                    Multipart {
+                        disposition: DispositionField::new("text"),
+                        headers: vec![],
+                        data: DataSource::Raw("some text\n".to_string()),
+                    },
+                    Multipart {
+                        disposition: DispositionField::new("text"),
+                        headers: vec![],
+                        data: DataSource::Raw("more content\n\n".to_string()),
+                    }
+                ]
+            }
+        )
+    }
+
+    #[test]
+    pub fn multipart_as_bytes_parses_back() {
+        let str = r####"
+POST https://test.com/multipart
+Content-Type: multipart/form-data; boundary="--test.?)()test"
+
+----test.?)()test
+Content-Disposition: form-data; name="text"
+
+some text
+
+----test.?)()test
+Content-Disposition: form-data; name="text"
+
+more content
+
+
+----test.?)()test--
+"####;
+
+        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        let request = requests.remove(0);
+
+        // every delimiter line and the line breaks around part headers are framed with CRLF, the
+        // line break before a boundary belongs to the delimiter and not to the part data
+        let bytes = request.body.as_bytes(std::path::Path::new(".")).unwrap();
+        let wire = String::from_utf8(bytes).unwrap();
+        assert_eq!(
+            wire,
+            "----test.?)()test\r\nContent-Disposition: form-data; name=\"text\"\r\n\r\nsome text\n\r\n----test.?)()test\r\nContent-Disposition: form-data; name=\"text\"\r\n\r\nmore content\n\n\r\n----test.?)()test--"
+        );
+
+        let reparsed = format!(
+            "POST https://test.com/multipart\nContent-Type: multipart/form-data; boundary=\"--test.?)()test\"\n\n{}\n",
+            wire
+        );
+        // the CRLF framed bytes are parsed as is and result in the same bytes again
+        let FileParseResult { mut requests, errs } = Parser::parse(&reparsed, false);
+        assert_eq!(errs, vec![]);
+        let body = requests.remove(0).body;
+        assert_eq!(body, request.body);
+        assert_eq!(
+            body.as_bytes(std::path::Path::new(".")).unwrap(),
+            wire.as_bytes()
+        );
+    }
+
+    #[test]
+    pub fn multipart_part_content_round_trip() {
+        let request = |content: &str| {
+            format!(
+                "POST https://test.com\nContent-Type: multipart/form-data; boundary=b\n\n\
+                 --b\nContent-Disposition: form-data; name=\"text\"\n\n{}\n--b--\n",
+                content
+            )
+        };
+
+        for content in [
+            "",
+            "\n",
+            "no newline",
+            "newline\n",
+            "two\nlines\n\n",
+            "--b-x",
+        ] {
+            let FileParseResult { mut requests, errs } = Parser::parse(&request(content), false);
+            assert_eq!(errs, vec![]);
+            let body = requests.remove(0).body;
+            assert_eq!(
+                body,
+                RequestBody::Multipart {
+                    boundary: "b".to_string(),
+                    parts: vec![Multipart::text("text", content)]
+                },
+                "content: {:?}",
+                content
+            );
+            // serializing the body results in the same content again
+            let reparsed = format!(
+                "POST https://test.com\nContent-Type: multipart/form-data; boundary=b\n\n{}\n",
+                body.to_string()
+            );
+            let FileParseResult { mut requests, errs } = Parser::parse(&reparsed, false);
+            assert_eq!(errs, vec![]);
+            assert_eq!(requests.remove(0).body, body);
+        }
+    }
+
+    #[test]
+    pub fn parse_multipart_with_content_types() {
+        let str = r#####"
+### Send a form with the text and file fields
+POST https://httpbin.org/post
+Content-Type: multipart/form-data; boundary=WebAppBoundary
+
+--WebAppBoundary
+Content-Disposition: form-data; name="element-name"
+Content-Type: text/plain; charset=utf-8
+
+Name
+--WebAppBoundary
+Content-Disposition: form-data; name="data"; filename="data.json"
+Content-Type: application/json
+
+< ./request-form-data.json
+--WebAppBoundary
+Content-Disposition: form-data; name="plain"
+
+text
+--WebAppBoundary--
+        "#####;
+
+        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+
+        let request = requests.remove(0);
+
+        assert_eq!(
+            request.headers,
+            vec![Header::new(
+                "Content-Type",
+                "multipart/form-data; boundary=WebAppBoundary"
+            )]
+        );
+
+        assert_eq!(
+            request.body,
+            model::RequestBody::Multipart {
+                boundary: "WebAppBoundary".to_string(),
+                parts: vec![
+                    Multipart {
+                        headers: vec![Header::new("Content-Type", "text/plain; charset=utf-8")],
+                        ..Multipart::text("element-name", "Name")
+                    },
+                    Multipart {
+                        disposition: DispositionField::new_with_filename("data", Some("data.json")),
+                        ..Multipart::file_with_type(
+                            "data",
+                            "./request-form-data.json",
+                            "application/json"
+                        )
+                    },
+                    Multipart::text("plain", "text"),
+                ]
+            }
+        );
+        assert_eq!(
+            request.body.part_names(),
+            vec!["element-name", "data", "plain"]
+        );
+        assert_eq!(
+            request.body.multipart_parts().map(|parts| parts.len()),
+            Some(3)
+        );
+        assert_eq!(RequestBody::None.multipart_parts(), None);
+        assert!(RequestBody::Empty.part_names().is_empty());
+
+        let RequestBody::Multipart { parts, .. } = request.body else {
+            panic!("expected multipart body");
+        };
+        assert_eq!(parts[0].content_type(), "text/plain");
+        assert_eq!(parts[0].charset(), Some("utf-8".to_string()));
+        assert_eq!(parts[1].content_type(), "application/json");
+        assert_eq!(parts[1].charset(), None);
+        // without header the default content type is used
+        assert_eq!(parts[2].content_type(), "text/plain");
+        assert_eq!(parts[2].charset(), None);
+    }
+
+    #[test]
+    pub fn parse_multipart_boundary_trailing_whitespace() {
+        let str = "POST https://httpbin.org/post
+Content-Type: multipart/form-data; boundary=WebAppBoundary
+
+--WebAppBoundary
+Content-Disposition: form-data; name=\"first\"
+
+first value
+--WebAppBoundary  \t
+Content-Disposition: form-data; name=\"second\"
+
+second value
+--WebAppBoundary--   
+
+### next
+GET https://httpbin.org/get
+";
+
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 2);
+        assert_eq!(
+            requests[0].body,
+            RequestBody::Multipart {
+                boundary: "WebAppBoundary".to_string(),
+                parts: vec![
+                    Multipart::text("first", "first value"),
+                    Multipart::text("second", "second value"),
+                ]
+            }
+        );
+    }
+
+    #[test]
+    pub fn parse_multipart_case_insensitive_headers() {
+        let str = r#####"POST https://httpbin.org/post
+content-type: Multipart/Form-Data; boundary=WebAppBoundary
+
+--WebAppBoundary
+content-disposition: form-data; name="text"
+
+value
+--WebAppBoundary--
+"#####;
+
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(
+            requests[0].body,
+            RequestBody::Multipart {
+                boundary: "WebAppBoundary".to_string(),
+                parts: vec![Multipart::text("text", "value")]
+            }
+        );
+    }
+
+    #[test]
+    pub fn parse_multipart_binary() {
+        let str = r#####"
+POST /upload HTTP/1.1
+Host: localhost:8080
+Content-Type: multipart/form-data; boundary=/////////////////////////////
+Content-Length: 676
+
+--/////////////////////////////
+Content-Disposition: form-data; name="file"; filename="binaryfile.tar.gz"
+Content-Type: application/x-gzip
+Content-Transfer-Encoding: base64
+
+H4sIAGiNIU8AA+3R0W6CMBQGYK59iobLZantRDG73osUOGqnFNJWM2N897UghG1ZdmWWLf93U/jP4bRAq8q92hJ/dY1J7kQEqyyLq8yXYrp2ltkqkTKXYiEykYc++ZTLVcLEvQ40dXReWcYSV1pdnL/v+6n+R11mjKVG1ZQ+s3TT2FpXqjhQ+hjzE1mnGxNLkgu+7tOKWjIVmVKTC6XL9ZaeXj4VQhwKWzL+cI4zwgQuuhkh3mhTad/Hkssh3im3027X54JnQ360R/M19OT8kC7SEN7Ooi2VvrEfznHQRWzl83gxttZKmzGehzPRW/+W8X+3fvL8sFet9sS6m3EIma02071MU3Uf9KHrmV1/+y8DAAAAAAAAAAAAAAAAAAAAAMB/9A6txIuJACgAAA==
+--/////////////////////////////--
+        "#####;
+
+        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        let request = requests.remove(0);
+
+        assert_eq!(
+            request.headers,
+            vec![
+                Header::new("Host", "localhost:8080"),
+                Header::new(
+                    "Content-Type",
+                    r#"multipart/form-data; boundary=/////////////////////////////"#
+                ),
+                Header::new("Content-Length", "676")
+            ]
+        );
+
+        // @TODO check content
+        assert_eq!(
+            request.body,
+            model::RequestBody::Multipart {
+                boundary: r#"/////////////////////////////"#.to_string(),
+                parts: vec![model::Multipart {
+                    disposition: DispositionField::new_with_filename("file", Some("binaryfile.tar.gz")),
+                    headers: vec![
+                        Header {
+                            key: "Content-Type".to_string(),
+                            value: "application/x-gzip".to_string()
+                        },
+                        Header {
+                            key: "Content-Transfer-Encoding".to_string(),
+                            value: "base64".to_string()
+                        }
+                    ],
+                    data: DataSource::Raw("H4sIAGiNIU8AA+3R0W6CMBQGYK59iobLZantRDG73osUOGqnFNJWM2N897UghG1ZdmWWLf93U/jP4bRAq8q92hJ/dY1J7kQEqyyLq8yXYrp2ltkqkTKXYiEykYc++ZTLVcLEvQ40dXReWcYSV1pdnL/v+6n+R11mjKVG1ZQ+s3TT2FpXqjhQ+hjzE1mnGxNLkgu+7tOKWjIVmVKTC6XL9ZaeXj4VQhwKWzL+cI4zwgQuuhkh3mhTad/Hkssh3im3027X54JnQ360R/M19OT8kC7SEN7Ooi2VvrEfznHQRWzl83gxttZKmzGehzPRW/+W8X+3fvL8sFet9sS6m3EIma02071MU3Uf9KHrmV1/+y8DAAAAAAAAAAAAAAAAAAAAAMB/9A6txIuJACgAAA==".to_string())
+                }]
+            }
+        )
+    }
+
+    #[test]
+    pub fn parse_json_body() {
+        let str = r#####"
+GET http://localhost/api/json/get?id=12345
+Authorization: Basic dev-user dev-password
+Content-Type: application/json
+
+{
+    "key": "my-dev-value"
+}"#####;
+
+        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+
+        let request = requests.remove(0);
+
+        assert_eq!(
+            request.headers,
+            vec![
+                Header::new("Authorization", r#"Basic dev-user dev-password"#),
+                Header::new("Content-Type", "application/json")
+            ]
+        );
+
+        assert_eq!(
+            request.body,
+            model::RequestBody::Raw {
+                data: DataSource::Raw(
+                    r#"{
+    "key": "my-dev-value"
+}"#
+                    .to_string()
+                )
+            }
+        )
+    }
+
+    #[test]
+    pub fn parse_json_body_fileinput() {
+        let str = r#####"
+POST http://example.com/api/add
+Content-Type: application/json
+
+< ./input.json
+
+        "#####;
+
+        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+
+        let request = requests.remove(0);
+
+        assert_eq!(
+            request.headers,
+            vec![Header::new("Content-Type", "application/json")]
+        );
+
+        // @TODO check content
+        assert_eq!(
+            request.body,
+            model::RequestBody::Raw {
+                data: DataSource::FromFilepath("./input.json".to_string())
+            }
+        )
+    }
+
+    #[test]
+    pub fn parse_url_form_encoded_end_of_file() {
+        let str = r####"# @name=Create Checkout Session
+POST {{base_url}}/create_checkout_session?a=aa
+Content-Type: application/x-www-form-urlencoded
+
+abc=def&ghi=jkl"####;
+        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        let request = requests.remove(0);
+
+        assert_eq!(
+            request.headers,
+            vec![Header::new(
+                "Content-Type",
+                "application/x-www-form-urlencoded"
+            )]
+        );
+
+        assert_eq!(
+            request.body,
+            RequestBody::UrlEncoded {
+                url_encoded_params: vec![
+                    UrlEncodedParam::new("abc", "def"),
+                    UrlEncodedParam::new("ghi", "jkl"),
+                ]
+            }
+        )
+    }
+
+    #[test]
+    pub fn parse_url_form_encoded() {
+        let str = r####"
+POST https://test.com/formEncoded
+Content-Type: application/x-www-form-urlencoded
+
+firstKey=firstValue&secondKey=secondValue&empty=
+"####;
+
+        let FileParseResult { mut requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        let request = requests.remove(0);
+
+        assert_eq!(
+            request.headers,
+            vec![Header::new(
+                "Content-Type",
+                "application/x-www-form-urlencoded"
+            )]
+        );
+
+        assert_eq!(
+            request.body,
+            RequestBody::UrlEncoded {
+                url_encoded_params: vec![
+                    UrlEncodedParam::new("firstKey", "firstValue"),
+                    UrlEncodedParam::new("secondKey", "secondValue"),
+                    UrlEncodedParam::new("empty", ""),
+                ]
+            }
+        )
+    }
+
+    #[test]
+    pub fn parse_multiple_requests() {
+        let str = r#####"
+POST http://example.com/api/add
+Content-Type: application/json
+
+< ./input.json
+###
+
+GET https://example.com/first
+###
+GET https://example.com/second
+
+
+###
+        "#####;
+
+        let FileParseResult { requests, errs } = dbg!(Parser::parse(str, false));
+        println!("errs: {:?}", errs);
+        assert_eq!(errs.len(), 0);
+        assert_eq!(requests.len(), 3);
+
+        // @TODO check content
+        assert_eq!(
+            requests,
+            vec![
+                model::Request {
+                    name: None,
+                    comments: vec![],
+                    headers: vec![Header {
+                        key: "Content-Type".to_string(),
+                        value: "application/json".to_string()
                     }],
                     body: model::RequestBody::Raw {
                         data: DataSource::FromFilepath("./input.json".to_string())
                     },
-                    request_line: model::RequestLine {
-                        http_version: WithDefault::default(),
-                        method: WithDefault::Some(HttpMethod::POST),
-                        target: model::RequestTarget::Absolute {
-                            uri: "http://example.com/api/add".to_string()
-                        }
+                    request_line: model::RequestLine {
+                        http_version: WithDefault::default(),
+                        method: WithDefault::Some(HttpMethod::POST),
+                        target: model::RequestTarget::Absolute {
+                            uri: "http://example.com/api/add".to_string()
+                        }
+                    },
+                    settings: RequestSettings::default(),
+                    pre_request_scripts: vec![],
+                    response_handler: None,
+                    save_response: None,
+                    index: 0,
+                    warnings: vec![],
+                },
+                model::Request {
+                    name: None,
+                    comments: vec![],
+                    headers: vec![],
+                    body: model::RequestBody::None,
+                    request_line: model::RequestLine {
+                        http_version: WithDefault::default(),
+                        method: WithDefault::Some(HttpMethod::GET),
+                        target: model::RequestTarget::Absolute {
+                            uri: "https://example.com/first".to_string()
+                        }
+                    },
+                    settings: RequestSettings::default(),
+                    pre_request_scripts: vec![],
+                    response_handler: None,
+                    save_response: None,
+                    index: 1,
+                    warnings: vec![],
+                },
+                model::Request {
+                    name: None,
+                    comments: vec![],
+                    headers: vec![],
+                    body: model::RequestBody::None,
+                    request_line: model::RequestLine {
+                        http_version: WithDefault::default(),
+                        method: WithDefault::Some(HttpMethod::GET),
+                        target: model::RequestTarget::Absolute {
+                            uri: "https://example.com/second".to_string()
+                        }
+                    },
+                    settings: RequestSettings::default(),
+                    pre_request_scripts: vec![],
+                    response_handler: None,
+                    save_response: None,
+                    index: 2,
+                    warnings: vec![],
+                }
+            ],
+        );
+    }
+
+    #[test]
+    pub fn parse_meta_directives() {
+        let str = r#####"
+### The Request
+# @no-redirect
+// @no-log
+// @name= RequestName
+# @no-cookie-jar
+GET https://httpbin.org
+"#####;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0],
+            Request {
+                name: Some("RequestName".to_string()),
+                headers: vec![],
+                comments: vec![Comment {
+                    value: "The Request".to_string(),
+                    kind: CommentKind::RequestSeparator
+                }],
+                settings: RequestSettings {
+                    no_redirect: Some(true),
+                    no_log: Some(true),
+                    no_cookie_jar: Some(true),
+                    ..RequestSettings::default()
+                },
+                request_line: RequestLine {
+                    method: WithDefault::Some(HttpMethod::GET),
+                    target: RequestTarget::from("https://httpbin.org"),
+                    http_version: WithDefault::default()
+                },
+                body: model::RequestBody::None,
+                pre_request_scripts: vec![],
+                response_handler: None,
+                save_response: None,
+                index: 0,
+                warnings: vec![],
+            }
+        );
+    }
+
+    #[test]
+    pub fn parse_timeout_directives() {
+        let str = r#####"
+### The Request
+# @connection-timeout 10
+// @socket-timeout   30
+GET https://httpbin.org
+"#####;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].settings,
+            RequestSettings {
+                connection_timeout: Some(10),
+                socket_timeout: Some(30),
+                ..RequestSettings::default()
+            }
+        );
+        assert_eq!(requests[0].comments, vec![]);
+    }
+
+    #[test]
+    pub fn parse_file_http_version_directive() {
+        let str = r#####"
+# @http-version HTTP/2
+###
+GET https://test.com/first
+
+###
+GET https://test.com/second HTTP/1.1
+
+###
+GET https://test.com/third
+"#####;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 3);
+        let http_2 = HttpVersion { major: 2, minor: 0 };
+        assert_eq!(
+            requests[0].request_line.http_version,
+            WithDefault::Some(http_2.clone())
+        );
+        assert_eq!(
+            requests[1].request_line.http_version,
+            WithDefault::Some(HttpVersion { major: 1, minor: 1 })
+        );
+        assert_eq!(requests[2].http_version(), http_2);
+        assert_eq!(requests[0].comments, vec![]);
+
+        let FileParseResult { requests, errs } = Parser::parse(
+            "# @http-version HTTP
+GET https://test.com",
+            false,
+        );
+        assert_eq!(requests, vec![]);
+        assert_eq!(
+            errs[0].details[0].error,
+            ParseError::InvalidHttpVersion("HTTP".to_string())
+        );
+        let request: Request = errs[0].partial_request.clone().into();
+        assert_eq!(request.request_line.http_version, WithDefault::default());
+
+        // only the file header may set the version, later directives are ignored
+        let str = r#####"GET https://test.com/first
+
+###
+# @http-version HTTP/2
+GET https://test.com/second
+
+###
+GET https://test.com/third
+"#####;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[1].warnings.len(), 1);
+        assert_eq!(
+            requests[1].warnings[0].error,
+            ParseError::MisplacedHttpVersionDirective
+        );
+        assert!(requests
+            .iter()
+            .all(|request| request.request_line.http_version == WithDefault::default()));
+    }
+
+    #[test]
+    pub fn parse_invalid_timeout_directive() {
+        let str = r#####"# @connection-timeout 10s
+# @socket-timeout
+GET https://httpbin.org
+"#####;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].warnings,
+            vec![
+                ParseErrorDetails::new_with_position(
+                    ParseError::InvalidTimeoutValue {
+                        setting: "@connection-timeout".to_string(),
+                        value: "10s".to_string()
+                    },
+                    (0, Some(25))
+                ),
+                ParseErrorDetails::new_with_position(
+                    ParseError::InvalidTimeoutValue {
+                        setting: "@socket-timeout".to_string(),
+                        value: "".to_string()
                     },
-                    settings: RequestSettings::default(),
-                    pre_request_script: None,
-                    response_handler: None,
-                    save_response: None,
+                    (26, Some(43))
+                )
+            ]
+        );
+        // the rest of the request is still parsed, the lines are kept as comments
+        let request = &requests[0];
+        assert_eq!(
+            request.request_line.target,
+            RequestTarget::from("https://httpbin.org")
+        );
+        assert_eq!(request.settings, RequestSettings::default());
+        assert_eq!(request.comments[0].value, "@connection-timeout 10s");
+        assert_eq!(request.comments[1].value, "@socket-timeout");
+    }
+
+    #[test]
+    pub fn parse_proxy_directives() {
+        let str = r#####"
+# @proxy http://proxy:8080
+GET https://httpbin.org
+###
+// @no-proxy
+GET https://httpbin.org
+"#####;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 2);
+        assert_eq!(
+            requests[0].settings,
+            RequestSettings {
+                proxy: Some("http://proxy:8080".to_string()),
+                ..RequestSettings::default()
+            }
+        );
+        assert_eq!(
+            requests[1].settings,
+            RequestSettings {
+                no_proxy: Some(true),
+                ..RequestSettings::default()
+            }
+        );
+    }
+
+    #[test]
+    pub fn parse_invalid_proxy_directive() {
+        let str = r#####"# @proxy proxy 8080
+GET https://httpbin.org
+"#####;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].warnings,
+            vec![ParseErrorDetails::new_with_position(
+                ParseError::InvalidProxyUrl("proxy 8080".to_string()),
+                (0, Some(19))
+            )]
+        );
+        assert_eq!(requests[0].settings.proxy, None);
+        assert_eq!(requests[0].comments[0].value, "@proxy proxy 8080");
+    }
+
+    #[test]
+    pub fn parse_pre_request_script_single_line() {
+        let str = r#####"
+### Request
+< {%     request.variables.set("firstname", "John") %}
+// @no-log
+GET https://httpbin.org
+"#####;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0],
+            Request {
+                name: Some("Request".to_string()),
+                headers: vec![],
+                comments: vec![],
+                settings: RequestSettings {
+                    no_redirect: Some(false),
+                    no_log: Some(true),
+                    no_cookie_jar: Some(false),
+                    ..RequestSettings::default()
                 },
-                model::Request {
-                    name: None,
-                    comments: vec![],
-                    headers: vec![],
-                    body: model::RequestBody::None,
-                    request_line: model::RequestLine {
-                        http_version: WithDefault::default(),
-                        method: WithDefault::Some(HttpMethod::GET),
-                        target: model::RequestTarget::Absolute {
-                            uri: "https://example.com/first".to_string()
-                        }
-                    },
-                    settings: RequestSettings::default(),
-                    pre_request_script: None,
-                    response_handler: None,
-                    save_response: None,
+                request_line: RequestLine {
+                    method: WithDefault::Some(HttpMethod::GET),
+                    target: RequestTarget::from("https://httpbin.org"),
+                    http_version: WithDefault::default()
                 },
-                model::Request {
-                    name: None,
-                    comments: vec![],
-                    headers: vec![],
-                    body: model::RequestBody::None,
-                    request_line: model::RequestLine {
-                        http_version: WithDefault::default(),
-                        method: WithDefault::Some(HttpMethod::GET),
-                        target: model::RequestTarget::Absolute {
-                            uri: "https://example.com/second".to_string()
-                        }
-                    },
-                    settings: RequestSettings::default(),
-                    pre_request_script: None,
-                    response_handler: None,
-                    save_response: None
-                }
-            ],
+                body: model::RequestBody::None,
+                pre_request_scripts: vec![model::PreRequestScript::Script(
+                    r#"     request.variables.set("firstname", "John") "#.to_string()
+                )],
+                response_handler: None,
+                save_response: None,
+                index: 0,
+                warnings: vec![],
+            }
         );
     }
 
     #[test]
-    pub fn parse_meta_directives() {
+    pub fn parse_pre_request_script_trailing_comment() {
         let str = r#####"
-### The Request
-# @no-redirect
+< {% a(); %}  // note
+< {%
+    b();
+%} # note with %}
+GET https://httpbin.org
+"#####;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(
+            requests[0].pre_request_scripts,
+            vec![
+                model::PreRequestScript::Script(" a(); ".to_string()),
+                model::PreRequestScript::Script("\n    b();\n".to_string()),
+            ]
+        );
+        assert_eq!(requests[0].comments, vec![]);
+        assert_eq!(
+            requests[0].request_line.target,
+            RequestTarget::from("https://httpbin.org")
+        );
+
+        // anything else after the closing '%}' is reported instead of being dropped
+        let str = "< {% a(); %} GET https://test.com\nGET https://httpbin.org\n";
+        let FileParseResult { errs, .. } = Parser::parse(str, false);
+        assert_eq!(errs.len(), 1);
+        assert_eq!(
+            errs[0].details[0],
+            ParseErrorDetails::new_with_position(
+                ParseError::UnexpectedContentAfterPreRequestScript(
+                    "GET https://test.com".to_string()
+                ),
+                (12, Some(33))
+            )
+        );
+    }
+
+    #[test]
+    pub fn parse_multiple_pre_request_scripts() {
+        let str = r#####"
+### Request
+< ./setup.js
+< {% request.variables.set("firstname", "John") %}
+GET https://httpbin.org
+"#####;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].pre_request_scripts,
+            vec![
+                model::PreRequestScript::FromFilepath {
+                    path: "./setup.js".to_string(),
+                    substitute_variables: false
+                },
+                model::PreRequestScript::Script(
+                    r#" request.variables.set("firstname", "John") "#.to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            requests[0].request_line.target,
+            RequestTarget::from("https://httpbin.org")
+        );
+    }
+
+    #[test]
+    pub fn parse_pre_request_script_file_substitution_marker() {
+        let str = r#####"
+<@ ./with-variables.js
+< ./verbatim.js
+GET https://httpbin.org
+"#####;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].pre_request_scripts,
+            vec![
+                model::PreRequestScript::FromFilepath {
+                    path: "./with-variables.js".to_string(),
+                    substitute_variables: true
+                },
+                model::PreRequestScript::FromFilepath {
+                    path: "./verbatim.js".to_string(),
+                    substitute_variables: false
+                },
+            ]
+        );
+        assert_eq!(
+            requests[0]
+                .pre_request_scripts
+                .iter()
+                .map(|script| script.to_string())
+                .collect::<Vec<String>>(),
+            vec!["<@ ./with-variables.js", "< ./verbatim.js"]
+        );
+    }
+
+    #[test]
+    pub fn parse_pre_request_script_multiple_lines() {
+        let str = r#####"
+### Request
+< {%
+ const signature = crypto.hmac.sha256()
+        .withTextSecret(request.environment.get("secret")) // get variable from http-client.private.env.json
+        .updateWithText(request.body.tryGetSubstituted())
+        .digest().toHex();
+    request.variables.set("signature", signature)
+
+    const hash = crypto.sha256()
+        .updateWithText(request.body.tryGetSubstituted())
+        .digest().toHex();
+    request.variables.set("hash", hash)
+%}
 // @no-log
-// @name= RequestName
-# @no-cookie-jar
 GET https://httpbin.org
 "#####;
+
+        let pre_request_script = r#####"
+ const signature = crypto.hmac.sha256()
+        .withTextSecret(request.environment.get("secret")) // get variable from http-client.private.env.json
+        .updateWithText(request.body.tryGetSubstituted())
+        .digest().toHex();
+    request.variables.set("signature", signature)
+
+    const hash = crypto.sha256()
+        .updateWithText(request.body.tryGetSubstituted())
+        .digest().toHex();
+    request.variables.set("hash", hash)
+"#####;
+
         let FileParseResult { requests, errs } = Parser::parse(str, false);
         assert_eq!(errs, vec![]);
         assert_eq!(requests.len(), 1);
         assert_eq!(
             requests[0],
             Request {
-                name: Some("RequestName".to_string()),
+                name: Some("Request".to_string()),
                 headers: vec![],
-                comments: vec![Comment {
-                    value: "The Request".to_string(),
-                    kind: CommentKind::RequestSeparator
-                }],
+                comments: vec![],
                 settings: RequestSettings {
-                    no_redirect: Some(true),
+                    no_redirect: Some(false),
                     no_log: Some(true),
-                    no_cookie_jar: Some(true),
+                    no_cookie_jar: Some(false),
+                    ..RequestSettings::default()
                 },
                 request_line: RequestLine {
                     method: WithDefault::Some(HttpMethod::GET),
@@ -2328,21 +4845,74 @@ GET https://httpbin.org
                     http_version: WithDefault::default()
                 },
                 body: model::RequestBody::None,
-                pre_request_script: None,
+                pre_request_scripts: vec![model::PreRequestScript::Script(
+                    pre_request_script.to_string()
+                )],
+                response_handler: None,
+                save_response: None,
+                index: 0,
+                warnings: vec![],
+            }
+        );
+    }
+
+    #[test]
+    pub fn parse_pre_request_script_variable_rename() {
+        let str = r#####"
+### Request
+< {% request.variables.set("firstname", "John") %}
+// @no-log
+GET https://httpbin.org/{{firstname}}
+"#####;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0],
+            Request {
+                name: Some("Request".to_string()),
+                headers: vec![],
+                comments: vec![],
+                settings: RequestSettings {
+                    no_redirect: Some(false),
+                    no_log: Some(true),
+                    no_cookie_jar: Some(false),
+                    ..RequestSettings::default()
+                },
+                request_line: RequestLine {
+                    method: WithDefault::Some(HttpMethod::GET),
+                    target: RequestTarget::from("https://httpbin.org/John"),
+                    http_version: WithDefault::default()
+                },
+                body: model::RequestBody::None,
+                pre_request_scripts: vec![model::PreRequestScript::Script(
+                    r#" request.variables.set("firstname", "John") "#.to_string()
+                )],
                 response_handler: None,
-                save_response: None
+                save_response: None,
+                index: 0,
+                warnings: vec![],
             }
         );
     }
 
     #[test]
-    pub fn parse_pre_request_script_single_line() {
+    pub fn parse_pre_request_script_variable_rename_multiline() {
         let str = r#####"
 ### Request
-< {%     request.variables.set("firstname", "John") %}
+< {%
+    request.variables.set("firstname", "John")
+    request.variables.set("domain", "httpbin")
+%}
 // @no-log
-GET https://httpbin.org
+GET https://{{domain}}.org/{{firstname}}
 "#####;
+
+        let pre_request_script = r####"
+    request.variables.set("firstname", "John")
+    request.variables.set("domain", "httpbin")
+"####;
+
         let FileParseResult { requests, errs } = Parser::parse(str, false);
         assert_eq!(errs, vec![]);
         assert_eq!(requests.len(), 1);
@@ -2356,55 +4926,84 @@ GET https://httpbin.org
                     no_redirect: Some(false),
                     no_log: Some(true),
                     no_cookie_jar: Some(false),
+                    ..RequestSettings::default()
                 },
                 request_line: RequestLine {
                     method: WithDefault::Some(HttpMethod::GET),
-                    target: RequestTarget::from("https://httpbin.org"),
+                    target: RequestTarget::from("https://httpbin.org/John"),
                     http_version: WithDefault::default()
                 },
                 body: model::RequestBody::None,
-                pre_request_script: Some(model::PreRequestScript::Script(
-                    r#"     request.variables.set("firstname", "John") "#.to_string()
-                )),
+                pre_request_scripts: vec![model::PreRequestScript::Script(
+                    pre_request_script.to_string()
+                )],
                 response_handler: None,
-                save_response: None
+                save_response: None,
+                index: 0,
+                warnings: vec![],
             }
         );
     }
 
     #[test]
-    pub fn parse_pre_request_script_multiple_lines() {
+    pub fn parse_pre_request_script_variable_rename_escaped_braces() {
         let str = r#####"
-### Request
-< {%
- const signature = crypto.hmac.sha256()
-        .withTextSecret(request.environment.get("secret")) // get variable from http-client.private.env.json
-        .updateWithText(request.body.tryGetSubstituted())
-        .digest().toHex();
-    request.variables.set("signature", signature)
+< {% request.variables.set("firstname", "John") %}
+GET https://httpbin.org/{{firstname}}?template=\{\{firstname\}\}
+"#####;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(
+            requests[0].request_line.target,
+            RequestTarget::Absolute {
+                uri: "https://httpbin.org/John?template={{firstname}}".to_string()
+            }
+        );
+    }
 
-    const hash = crypto.sha256()
-        .updateWithText(request.body.tryGetSubstituted())
-        .digest().toHex();
-    request.variables.set("hash", hash)
-%}
-// @no-log
-GET https://httpbin.org
+    #[test]
+    pub fn request_variables() {
+        let str = r#####"
+POST https://{{host}}/api/{{version}}/users
+Authorization: Bearer {{ token }}
+Content-Type: application/json
+
+{
+    "id": "{{id}}",
+    "template": "\{\{ not_a_var \}\}",
+    "host": "{{host}}"
+}
 "#####;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(
+            requests[0].variables(),
+            vec!["host", "version", "token", "id"]
+        );
 
-        let pre_request_script = r#####"
- const signature = crypto.hmac.sha256()
-        .withTextSecret(request.environment.get("secret")) // get variable from http-client.private.env.json
-        .updateWithText(request.body.tryGetSubstituted())
-        .digest().toHex();
-    request.variables.set("signature", signature)
+        let str = r#####"
+POST https://httpbin.org
+Content-Type: application/x-www-form-urlencoded
 
-    const hash = crypto.sha256()
-        .updateWithText(request.body.tryGetSubstituted())
-        .digest().toHex();
-    request.variables.set("hash", hash)
+name={{name}}&literal=\{\{name\}\}
+"#####;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests[0].variables(), vec!["name"]);
+    }
+
+    #[test]
+    pub fn parse_handler_script_single_line() {
+        let str = r#####"
+### Request
+// @no-log
+GET https://httpbin.org
+
+> {% client.global.set("my_cookie", response.headers.valuesOf("Set-Cookie")[0]); %} 
 "#####;
 
+        let response_handler_script = r#####" client.global.set("my_cookie", response.headers.valuesOf("Set-Cookie")[0]); "#####;
+
         let FileParseResult { requests, errs } = Parser::parse(str, false);
         assert_eq!(errs, vec![]);
         assert_eq!(requests.len(), 1);
@@ -2418,6 +5017,7 @@ GET https://httpbin.org
                     no_redirect: Some(false),
                     no_log: Some(true),
                     no_cookie_jar: Some(false),
+                    ..RequestSettings::default()
                 },
                 request_line: RequestLine {
                     method: WithDefault::Some(HttpMethod::GET),
@@ -2425,23 +5025,34 @@ GET https://httpbin.org
                     http_version: WithDefault::default()
                 },
                 body: model::RequestBody::None,
-                pre_request_script: Some(model::PreRequestScript::Script(
-                    pre_request_script.to_string()
+                pre_request_scripts: vec![],
+                response_handler: Some(ResponseHandler::Script(
+                    response_handler_script.to_string()
                 )),
-                response_handler: None,
                 save_response: None,
+                index: 0,
+                warnings: vec![],
             }
         );
     }
-
     #[test]
-    pub fn parse_pre_request_script_variable_rename() {
+    pub fn parse_handler_script_multiple_lines() {
         let str = r#####"
 ### Request
-< {% request.variables.set("firstname", "John") %}
 // @no-log
-GET https://httpbin.org/{{firstname}}
+GET https://httpbin.org
+
+> {%
+    client.global.set("my_cookie", response.headers.valuesOf("Set-Cookie")[0]);
+    client.global.set("my_cookie_2", response.headers.valuesOf("Set-Cookie")[0]);
+%} 
+"#####;
+
+        let response_handler_script = r#####"
+    client.global.set("my_cookie", response.headers.valuesOf("Set-Cookie")[0]);
+    client.global.set("my_cookie_2", response.headers.valuesOf("Set-Cookie")[0]);
 "#####;
+
         let FileParseResult { requests, errs } = Parser::parse(str, false);
         assert_eq!(errs, vec![]);
         assert_eq!(requests.len(), 1);
@@ -2455,38 +5066,197 @@ GET https://httpbin.org/{{firstname}}
                     no_redirect: Some(false),
                     no_log: Some(true),
                     no_cookie_jar: Some(false),
+                    ..RequestSettings::default()
                 },
                 request_line: RequestLine {
                     method: WithDefault::Some(HttpMethod::GET),
-                    target: RequestTarget::from("https://httpbin.org/John"),
+                    target: RequestTarget::from("https://httpbin.org"),
                     http_version: WithDefault::default()
                 },
                 body: model::RequestBody::None,
-                pre_request_script: Some(model::PreRequestScript::Script(
-                    r#" request.variables.set("firstname", "John") "#.to_string()
+                pre_request_scripts: vec![],
+                response_handler: Some(ResponseHandler::Script(
+                    response_handler_script.to_string()
                 )),
-                response_handler: None,
-                save_response: None
+                save_response: None,
+                index: 0,
+                warnings: vec![],
             }
         );
+        assert_eq!(
+            requests[0].response_handler.as_ref().unwrap().globals_set(),
+            vec!["my_cookie".to_string(), "my_cookie_2".to_string()]
+        );
+
+        let handler = ResponseHandler::Script(
+            "client.global.set('token', response.body.token);\nclient.global.set(\"token\", 1);"
+                .to_string(),
+        );
+        assert_eq!(handler.globals_set(), vec!["token".to_string()]);
+        let handler = ResponseHandler::FromFilepath("./handler.js".to_string());
+        assert_eq!(handler.globals_set(), Vec::<String>::new());
     }
 
     #[test]
-    pub fn parse_pre_request_script_variable_rename_multiline() {
+    pub fn has_valid_extension() {
+        // ok
+        assert!(Parser::has_valid_extension(&"test.rest"));
+        assert!(Parser::has_valid_extension(&"rest.http"));
+
+        assert!(Parser::has_valid_extension(&"C:\\folder\\test.rest"));
+        assert!(Parser::has_valid_extension(&"/home/user/test.rest"));
+
+        assert!(Parser::has_valid_extension(&std::path::Path::new(
+            "test.rest"
+        )));
+
+        assert!(Parser::has_valid_extension(&std::path::Path::new(
+            "test.http"
+        )));
+
+        assert!(Parser::has_valid_extension(&std::path::Path::new(
+            "C:\\folder\\test.rest"
+        )));
+
+        assert!(Parser::has_valid_extension(&std::path::Path::new(
+            "/home/usr/folder/test.rest"
+        )));
+
+        assert!(Parser::has_valid_extension(&"TEST.HTTP"));
+        assert!(Parser::has_valid_extension(&"test.Rest"));
+
+        // nok
+        assert!(!Parser::has_valid_extension(&"test"));
+        assert!(!Parser::has_valid_extension(&"/home/user/test"));
+        assert!(!Parser::has_valid_extension(&""));
+    }
+
+    #[test]
+    pub fn trailing_separator_is_not_meaningful() {
         let str = r#####"
-### Request
-< {%
-    request.variables.set("firstname", "John")
-    request.variables.set("domain", "httpbin")
-%}
-// @no-log
-GET https://{{domain}}.org/{{firstname}}
+GET https://test.com
+
+###
+"#####;
+        let mut scanner = Scanner::new(str);
+        let request = Parser::parse_request(&mut scanner).unwrap();
+        assert!(!request.is_empty());
+        scanner.skip_empty_lines();
+        let err = Parser::parse_request(&mut scanner).unwrap_err();
+        assert!(!err.partial_request.is_meaningful());
+        assert!(model::Request::from(err.partial_request).is_empty());
+
+        // the parser drops the trailing separator
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(errs, vec![]);
+
+        // a trailing comment is kept as content
+        let str = r#####"
+GET https://test.com
+
+### some note
 "#####;
+        let FileParseResult { errs, .. } = Parser::parse(str, false);
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].partial_request.is_meaningful());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    pub fn has_valid_extension_non_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+        // extension bytes that are not valid utf8 are not a valid extension, but must not panic
+        let path = std::path::Path::new(std::ffi::OsStr::from_bytes(b"test.\xffhttp"));
+        assert!(!Parser::has_valid_extension(&path));
+        let path = std::path::Path::new(std::ffi::OsStr::from_bytes(b"\xfe\xff.http"));
+        assert!(Parser::has_valid_extension(&path));
+    }
+
+    #[test]
+    // https://www.rfc-editor.org/rfc/rfc2046#section-5.1.1
+    pub fn is_multipart_boundary_valid() {
+        // at least one character is required
+        let boundary = "";
+        assert_eq!(Parser::is_multipart_boundary_valid(boundary).is_err(), true);
+
+        // no more than 70 characters
+        let boundary = "a".repeat(71);
+        assert_eq!(
+            Parser::is_multipart_boundary_valid(&boundary).is_err(),
+            true
+        );
+
+        // at least one character is required
+        let boundary = "a";
+
+        assert_eq!(
+            Parser::is_multipart_boundary_valid(&boundary).is_err(),
+            false
+        );
+
+        // up to 70 characters is ok
+        let boundary = "a".repeat(70);
+        assert_eq!(
+            Parser::is_multipart_boundary_valid(&boundary).is_err(),
+            false
+        );
+
+        // no spaces within allowed
+        let boundary = "a b";
+        assert_eq!(
+            Parser::is_multipart_boundary_valid(&boundary).is_err(),
+            true
+        );
+
+        // these characters are allowed
+        let boundary = "0123456789abcdefghijklmnopqrstuvwyxz";
+        assert_eq!(
+            Parser::is_multipart_boundary_valid(&boundary).is_err(),
+            false
+        );
+
+        let boundary = "ABCDEFGHIJKLMNOPQRSTUVWXYZ'()+_,-./:=?";
+        assert_eq!(
+            Parser::is_multipart_boundary_valid(&boundary).is_err(),
+            false
+        );
+    }
+
+    #[test]
+    pub fn parse_with_redirect_overwrite_response() {
+        let str = r###"# @name=New Request
+GET https://httpbin.org/get
+
+>>! test.txt"###;
+
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0],
+            Request {
+                name: Some("New Request".to_string()),
+                request_line: RequestLine {
+                    method: WithDefault::Some(HttpMethod::GET),
+                    target: RequestTarget::from("https://httpbin.org/get"),
+                    http_version: WithDefault::default()
+                },
+                save_response: Some(SaveResponse::RewriteFile(std::path::PathBuf::from(
+                    "test.txt"
+                ))),
+
+                ..Default::default()
+            }
+        );
+    }
 
-        let pre_request_script = r####"
-    request.variables.set("firstname", "John")
-    request.variables.set("domain", "httpbin")
-"####;
+    #[test]
+    pub fn parse_with_redirect_new_file_response() {
+        let str = r###"# @name=New Request
+GET https://httpbin.org/get
+
+>> test.txt"###;
 
         let FileParseResult { requests, errs } = Parser::parse(str, false);
         assert_eq!(errs, vec![]);
@@ -2494,337 +5264,689 @@ GET https://{{domain}}.org/{{firstname}}
         assert_eq!(
             requests[0],
             Request {
-                name: Some("Request".to_string()),
-                headers: vec![],
-                comments: vec![],
-                settings: RequestSettings {
-                    no_redirect: Some(false),
-                    no_log: Some(true),
-                    no_cookie_jar: Some(false),
-                },
+                name: Some("New Request".to_string()),
                 request_line: RequestLine {
                     method: WithDefault::Some(HttpMethod::GET),
-                    target: RequestTarget::from("https://httpbin.org/John"),
+                    target: RequestTarget::from("https://httpbin.org/get"),
                     http_version: WithDefault::default()
                 },
-                body: model::RequestBody::None,
-                pre_request_script: Some(model::PreRequestScript::Script(
-                    pre_request_script.to_string()
-                )),
-                response_handler: None,
-                save_response: None
+                save_response: Some(SaveResponse::NewFileIfExists(std::path::PathBuf::from(
+                    "test.txt"
+                ))),
+
+                ..Default::default()
             }
         );
     }
 
     #[test]
-    pub fn parse_handler_script_single_line() {
-        let str = r#####"
-### Request
-// @no-log
-GET https://httpbin.org
+    /// If no boundary is given use default boundary '--boundary--'
+    pub fn parse_multipart_no_boundary() {
+        let str = r####"# @name=New Request
+GET https://httpbin.org/{{abc}}
+Content-Type: multipart/form-data
 
-> {% client.global.set("my_cookie", response.headers.valuesOf("Set-Cookie")[0]); %} 
-"#####;
+--boundary--
 
-        let response_handler_script = r#####" client.global.set("my_cookie", response.headers.valuesOf("Set-Cookie")[0]); "#####;
+>>! test.txt"####;
 
         let FileParseResult { requests, errs } = Parser::parse(str, false);
-        assert_eq!(errs, vec![]);
-        assert_eq!(requests.len(), 1);
+        // should have one error warning that no boundary was given
+        assert_eq!(errs.len(), 1);
+        assert!(matches!(
+            errs[0].details[0].error,
+            ParseError::MissingMultipartHeaderBoundaryDefinition(_)
+        ));
+        //assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 0);
         assert_eq!(
-            requests[0],
+            Into::<Request>::into(errs[0].partial_request.clone()),
             Request {
-                name: Some("Request".to_string()),
-                headers: vec![],
-                comments: vec![],
-                settings: RequestSettings {
-                    no_redirect: Some(false),
-                    no_log: Some(true),
-                    no_cookie_jar: Some(false),
-                },
+                name: Some("New Request".to_string()),
                 request_line: RequestLine {
                     method: WithDefault::Some(HttpMethod::GET),
-                    target: RequestTarget::from("https://httpbin.org"),
+                    target: RequestTarget::from("https://httpbin.org/{{abc}}"),
                     http_version: WithDefault::default()
                 },
-                body: model::RequestBody::None,
-                pre_request_script: None,
-                response_handler: Some(ResponseHandler::Script(
-                    response_handler_script.to_string()
-                )),
-                save_response: None
+                headers: vec![Header::new("Content-Type", "multipart/form-data")],
+                body: RequestBody::Multipart {
+                    boundary: "--boundary--".to_string(),
+                    parts: vec![]
+                },
+                save_response: Some(SaveResponse::RewriteFile(std::path::PathBuf::from(
+                    "test.txt"
+                ))),
+
+                ..Default::default()
             }
         );
     }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    pub fn parse_with_metrics() {
+        let str = "GET https://test.com/1\n\n###\nGET https://test.com/2\n\n###\nPOST\n";
+        let (FileParseResult { requests, errs }, metrics) =
+            Parser::parse_with_metrics(str, &ParserOptions::default());
+        assert_eq!(metrics.requests, requests.len());
+        assert_eq!(metrics.requests, 2);
+        assert_eq!(metrics.errors, errs.len());
+        assert_eq!(metrics.bytes_scanned, str.len());
+    }
+
     #[test]
-    pub fn parse_handler_script_multiple_lines() {
+    pub fn parse_multipart_lenient_boundary() {
         let str = r#####"
-### Request
-// @no-log
-GET https://httpbin.org
+POST https://test.com/upload
+Content-Type: multipart/form-data; boundary=WebAppBoundary
 
-> {%
-    client.global.set("my_cookie", response.headers.valuesOf("Set-Cookie")[0]);
-    client.global.set("my_cookie_2", response.headers.valuesOf("Set-Cookie")[0]);
-%} 
-"#####;
+--OtherBoundary
+Content-Disposition: form-data; name="first"
 
-        let response_handler_script = r#####"
-    client.global.set("my_cookie", response.headers.valuesOf("Set-Cookie")[0]);
-    client.global.set("my_cookie_2", response.headers.valuesOf("Set-Cookie")[0]);
-"#####;
+1
+--OtherBoundary
+Content-Disposition: form-data; name="second"
 
+2
+--OtherBoundary--
+"#####;
+        // by default the body does not start with the boundary of the header
         let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(requests.len(), 0);
+        assert_eq!(errs.len(), 1);
+        assert_eq!(
+            errs[0].details[0].error,
+            ParseError::MissingMultipartStartingBoundary
+        );
+
+        let options = ParserOptions {
+            lenient_multipart_boundary: true,
+            ..ParserOptions::default()
+        };
+        let FileParseResult { requests, errs } = Parser::parse_with_options(str, &options);
         assert_eq!(errs, vec![]);
         assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].warnings.len(), 1);
         assert_eq!(
-            requests[0],
+            requests[0].warnings[0].error,
+            ParseError::MultipartBoundaryMismatch {
+                header: "WebAppBoundary".to_string(),
+                body: "OtherBoundary".to_string(),
+            }
+        );
+        match requests[0].body.clone() {
+            RequestBody::Multipart { boundary, parts } => {
+                assert_eq!(boundary, "OtherBoundary");
+                assert_eq!(parts.len(), 2);
+                assert_eq!(parts[1].disposition.name, "second");
+                assert_eq!(parts[1].data, DataSource::Raw("2".to_string()));
+            }
+            body => panic!("expected multipart body, got {:?}", body),
+        }
+
+        // the body boundary is only taken if it also closes the body
+        let unclosed = str.replace("--OtherBoundary--", "");
+        let FileParseResult { requests, errs } = Parser::parse_with_options(&unclosed, &options);
+        assert_eq!(requests.len(), 0);
+        assert_eq!(errs.len(), 1);
+        assert_eq!(
+            errs[0].details[0].error,
+            ParseError::MissingMultipartStartingBoundary
+        );
+        assert_eq!(errs[0].partial_request.warnings, vec![]);
+    }
+
+    #[test]
+    pub fn parse_multipart_bounded() {
+        // a multipart body that is never closed with a final boundary
+        let mut str = String::from(
+            "POST https://test.com\nContent-Type: multipart/form-data; boundary=b\n\n",
+        );
+        for i in 0..20 {
+            str.push_str(&format!(
+                "--b\nContent-Disposition: form-data; name=\"field{}\"\n\nvalue {}\n",
+                i, i
+            ));
+        }
+
+        let options = ParserOptions {
+            max_multipart_parts: 5,
+            ..ParserOptions::default()
+        };
+        let FileParseResult { requests, errs } = Parser::parse_with_options(&str, &options);
+        assert_eq!(requests.len(), 0);
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0]
+            .details
+            .iter()
+            .any(|details| details.error == ParseError::TooManyMultipartParts(5)));
+
+        let options = ParserOptions {
+            max_multipart_bytes: 100,
+            ..ParserOptions::default()
+        };
+        let FileParseResult { requests, errs } = Parser::parse_with_options(&str, &options);
+        assert_eq!(requests.len(), 0);
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0]
+            .details
+            .iter()
+            .any(|details| details.error == ParseError::MultipartBodyTooLarge(100)));
+
+        // a part which is never closed fails once the bound is reached instead of being read
+        // until the end of the input
+        let mut unclosed = String::from(
+            "POST https://test.com\nContent-Type: multipart/form-data; boundary=b\n\n\
+             --b\nContent-Disposition: form-data; name=\"unclosed\"\n\n",
+        );
+        unclosed.push_str(&"content of the part\n".repeat(1_000));
+        let FileParseResult { requests, errs } = Parser::parse_with_options(&unclosed, &options);
+        assert_eq!(requests.len(), 0);
+        assert_eq!(errs.len(), 1);
+        let details = &errs[0].details[0];
+        assert_eq!(details.error, ParseError::MultipartBodyTooLarge(100));
+        assert!(details.end_pos.unwrap() < 300);
+
+        // the default bounds are not reached by the same body
+        let FileParseResult { errs, .. } = Parser::parse(&str, false);
+        assert!(errs
+            .iter()
+            .flat_map(|err| err.details.iter())
+            .all(|details| !matches!(
+                details.error,
+                ParseError::TooManyMultipartParts(_) | ParseError::MultipartBodyTooLarge(_)
+            )));
+    }
+
+    #[test]
+    pub fn best_effort_request() {
+        let str = r####"# @name=New Request
+GET https://httpbin.org/{{abc}}
+Content-Type: multipart/form-data
+
+--boundary--
+
+>>! test.txt"####;
+
+        let FileParseResult { errs, .. } = Parser::parse(str, false);
+        assert_eq!(errs.len(), 1);
+        let request = errs[0].best_effort_request();
+        assert_eq!(request, errs[0].partial_request.clone().into());
+        assert_eq!(request.name, Some("New Request".to_string()));
+        assert_eq!(
+            request.request_line.target,
+            RequestTarget::from("https://httpbin.org/{{abc}}")
+        );
+        assert_eq!(
+            request.headers,
+            vec![Header::new("Content-Type", "multipart/form-data")]
+        );
+        assert_eq!(
+            request.body,
+            RequestBody::Multipart {
+                boundary: "--boundary--".to_string(),
+                parts: vec![]
+            }
+        );
+        assert_eq!(
+            request.save_response,
+            Some(SaveResponse::RewriteFile(std::path::PathBuf::from(
+                "test.txt"
+            )))
+        );
+
+        // parts that could not be parsed are filled with defaults
+        let FileParseResult { errs, .. } = Parser::parse("# @name=only method\nPOST\n", false);
+        assert_eq!(errs.len(), 1);
+        let request = errs[0].best_effort_request();
+        assert_eq!(request.name, Some("only method".to_string()));
+        assert_eq!(request.request_line.target, RequestTarget::Missing);
+        assert_eq!(request.headers, vec![]);
+        assert_eq!(request.body, RequestBody::None);
+    }
+
+    #[test]
+    pub fn parse_multipart_single_boundary_no_filename() {
+        let str = r###"# @name=New Request
+GET https://httpbin.org/{{abc}}
+Content-Type: multipart/form-data; boundary="--boundary--"
+
+----boundary--
+Content-Disposition: form-data; name=""
+
+
+----boundary----"###;
+
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        // one error allowed, name should not be empty of content-disposition inside a multipart
+        assert_eq!(errs.len(), 1);
+        //assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 0);
+        assert_eq!(
+            Into::<Request>::into(errs[0].partial_request.clone()),
             Request {
-                name: Some("Request".to_string()),
-                headers: vec![],
-                comments: vec![],
-                settings: RequestSettings {
-                    no_redirect: Some(false),
-                    no_log: Some(true),
-                    no_cookie_jar: Some(false),
-                },
+                name: Some("New Request".to_string()),
                 request_line: RequestLine {
                     method: WithDefault::Some(HttpMethod::GET),
-                    target: RequestTarget::from("https://httpbin.org"),
+                    target: RequestTarget::from("https://httpbin.org/{{abc}}"),
                     http_version: WithDefault::default()
                 },
-                body: model::RequestBody::None,
-                pre_request_script: None,
-                response_handler: Some(ResponseHandler::Script(
-                    response_handler_script.to_string()
-                )),
-                save_response: None
+                headers: vec![Header::new(
+                    "Content-Type",
+                    "multipart/form-data; boundary=\"--boundary--\""
+                )],
+                body: RequestBody::Multipart {
+                    boundary: "--boundary--".to_string(),
+                    parts: vec![Multipart {
+                        disposition: DispositionField::new(""),
+                        headers: vec![],
+                        data: DataSource::Raw("".to_string())
+                    }]
+                },
+                ..Default::default()
             }
         );
     }
 
     #[test]
-    pub fn has_valid_extension() {
-        // ok
-        assert!(Parser::has_valid_extension(&"test.rest"));
-        assert!(Parser::has_valid_extension(&"rest.http"));
-
-        assert!(Parser::has_valid_extension(&"C:\\folder\\test.rest"));
-        assert!(Parser::has_valid_extension(&"/home/user/test.rest"));
-
-        assert!(Parser::has_valid_extension(&std::path::Path::new(
-            "test.rest"
-        )));
-
-        assert!(Parser::has_valid_extension(&std::path::Path::new(
-            "test.http"
-        )));
+    pub fn parse_body_with_quoted_lines() {
+        let str = r####"
+POST https://test.com/markdown
+Content-Type: text/markdown
 
-        assert!(Parser::has_valid_extension(&std::path::Path::new(
-            "C:\\folder\\test.rest"
-        )));
+# Title
+> quote
+> continued quote
+text after quote
 
-        assert!(Parser::has_valid_extension(&std::path::Path::new(
-            "/home/usr/folder/test.rest"
-        )));
+> {% client.log(response.body) %}
+"####;
 
-        // nok
-        assert!(!Parser::has_valid_extension(&"test"));
-        assert!(!Parser::has_valid_extension(&"/home/user/test"));
-        assert!(!Parser::has_valid_extension(&""));
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].body,
+            RequestBody::Raw {
+                data: DataSource::Raw(
+                    "# Title\n> quote\n> continued quote\ntext after quote".to_string()
+                )
+            }
+        );
+        assert_eq!(
+            requests[0].response_handler,
+            Some(model::ResponseHandler::Script(
+                " client.log(response.body) ".to_string()
+            ))
+        );
     }
 
     #[test]
-    // https://www.rfc-editor.org/rfc/rfc2046#section-5.1.1
-    pub fn is_multipart_boundary_valid() {
-        // at least one character is required
-        let boundary = "";
-        assert_eq!(Parser::is_multipart_boundary_valid(boundary).is_err(), true);
+    pub fn parse_body_followed_by_redirect_or_handler() {
+        let str = r####"
+POST https://test.com/redirect
+Content-Type: text/plain
 
-        // no more than 70 characters
-        let boundary = "a".repeat(71);
+body text
+>> out.txt
+"####;
+
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
         assert_eq!(
-            Parser::is_multipart_boundary_valid(&boundary).is_err(),
-            true
+            requests[0].body,
+            RequestBody::Raw {
+                data: DataSource::Raw("body text".to_string())
+            }
         );
-
-        // at least one character is required
-        let boundary = "a";
-
+        assert_eq!(requests[0].response_handler, None);
         assert_eq!(
-            Parser::is_multipart_boundary_valid(&boundary).is_err(),
-            false
+            requests[0].save_response,
+            Some(SaveResponse::NewFileIfExists(std::path::PathBuf::from(
+                "out.txt"
+            )))
         );
 
-        // up to 70 characters is ok
-        let boundary = "a".repeat(70);
+        let str = r####"
+POST https://test.com/handler
+Content-Type: text/plain
+
+body text
+
+> {% client.log('done') %}
+"####;
+
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
         assert_eq!(
-            Parser::is_multipart_boundary_valid(&boundary).is_err(),
-            false
+            requests[0].body,
+            RequestBody::Raw {
+                data: DataSource::Raw("body text".to_string())
+            }
         );
-
-        // no spaces within allowed
-        let boundary = "a b";
         assert_eq!(
-            Parser::is_multipart_boundary_valid(&boundary).is_err(),
-            true
+            requests[0].response_handler,
+            Some(model::ResponseHandler::Script(
+                " client.log('done') ".to_string()
+            ))
         );
+        assert_eq!(requests[0].save_response, None);
+    }
 
-        // these characters are allowed
-        let boundary = "0123456789abcdefghijklmnopqrstuvwyxz";
+    #[test]
+    pub fn parse_body_leading_blank_lines() {
+        let str = "POST https://test.com\nContent-Type: text/plain\n\n\n\nhello\n\n###\n\
+                   GET https://test.com\n\n\n";
+
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
         assert_eq!(
-            Parser::is_multipart_boundary_valid(&boundary).is_err(),
-            false
+            requests[0].body,
+            RequestBody::Raw {
+                data: DataSource::Raw("hello".to_string())
+            }
         );
 
-        let boundary = "ABCDEFGHIJKLMNOPQRSTUVWXYZ'()+_,-./:=?";
+        let options = ParserOptions {
+            preserve_leading_body_blanks: true,
+            ..ParserOptions::default()
+        };
+        let FileParseResult { requests, errs } = Parser::parse_with_options(str, &options);
+        assert_eq!(errs, vec![]);
         assert_eq!(
-            Parser::is_multipart_boundary_valid(&boundary).is_err(),
-            false
+            requests[0].body,
+            RequestBody::Raw {
+                data: DataSource::Raw("\n\nhello".to_string())
+            }
         );
+        // empty lines without a body following are not a body
+        assert_eq!(requests[1].body, RequestBody::None);
     }
 
     #[test]
-    pub fn parse_with_redirect_overwrite_response() {
-        let str = r###"# @name=New Request
-GET https://httpbin.org/get
+    pub fn parse_disabled_request() {
+        let str = r####"
+### Disabled
+# @disabled
+POST https://test.com/users
+Content-Type: application/json
 
->>! test.txt"###;
+{"name": "test"}
+
+### Enabled
+GET https://test.com/users
+"####;
 
         let FileParseResult { requests, errs } = Parser::parse(str, false);
         assert_eq!(errs, vec![]);
-        assert_eq!(requests.len(), 1);
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].is_disabled());
+        assert_eq!(requests[0].name, Some("Disabled".to_string()));
+        assert_eq!(requests[0].comments, vec![]);
+        assert_eq!(requests[0].method(), HttpMethod::POST);
         assert_eq!(
-            requests[0],
-            Request {
-                name: Some("New Request".to_string()),
-                request_line: RequestLine {
-                    method: WithDefault::Some(HttpMethod::GET),
-                    target: RequestTarget::from("https://httpbin.org/get"),
-                    http_version: WithDefault::default()
-                },
-                save_response: Some(SaveResponse::RewriteFile(std::path::PathBuf::from(
-                    "test.txt"
-                ))),
-
-                ..Default::default()
+            requests[0].headers,
+            vec![Header::new("Content-Type", "application/json")]
+        );
+        assert_eq!(
+            requests[0].body,
+            RequestBody::Raw {
+                data: DataSource::Raw("{\"name\": \"test\"}".to_string())
             }
         );
+        assert!(requests[0].settings.serialized().contains("# @disabled\n"));
+        assert!(!requests[1].is_disabled());
     }
 
     #[test]
-    pub fn parse_with_redirect_new_file_response() {
-        let str = r###"# @name=New Request
-GET https://httpbin.org/get
+    pub fn parse_request_index() {
+        let str = r#"
+GET https://test.com/first
 
->> test.txt"###;
+###
+GET https://test.com/second HTTP/a
+
+###
+GET https://test.com/third
+###
+"#;
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(requests.len(), 2);
+        assert_eq!(errs.len(), 1);
+        assert_eq!(requests[0].index, 0);
+        assert_eq!(errs[0].partial_request.index, 1);
+        assert_eq!(Request::from(errs[0].partial_request.clone()).index, 1);
+        assert_eq!(requests[1].index, 2);
+    }
+
+    #[test]
+    pub fn check_reports_parse_errors() {
+        let str = r#"
+GET https://test.com HTTP/a
+
+###
+# @http-version abc
+POST https://test.com
 
+###
+GET https://test.com/valid
+"#;
         let FileParseResult { requests, errs } = Parser::parse(str, false);
-        assert_eq!(errs, vec![]);
         assert_eq!(requests.len(), 1);
+        let expected = errs
+            .into_iter()
+            .flat_map(|err| err.details)
+            .collect::<Vec<ParseErrorDetails>>();
+        assert_eq!(expected.len(), 2);
+        assert_eq!(Parser::check(str), expected);
+
+        assert_eq!(Parser::check("GET https://test.com"), vec![]);
+    }
+
+    #[test]
+    pub fn file_parse_result_into_result() {
+        let result = Parser::parse("GET https://test.com\n###\nPOST https://test.com", false);
+        assert!(!result.has_errors());
+        assert_eq!(result.error_count(), 0);
+        let requests = result.into_result().expect("no errors");
+        assert_eq!(requests.len(), 2);
+
+        let str = "GET https://test.com\n###\nGET https://test.com HTTP/a";
+        let result = Parser::parse(str, false);
+        assert!(result.has_errors());
+        assert_eq!(result.error_count(), 1);
+        let errs = result.into_result().expect_err("second request is invalid");
+        assert_eq!(errs.len(), 1);
+    }
+
+    #[test]
+    pub fn parse_name_equal_to_separator_comment() {
+        let str = r####"
+### Create
+# @name Create
+POST https://test.com/create
+"####;
+
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests[0].name, Some("Create".to_string()));
+        assert_eq!(requests[0].comments, vec![]);
+
+        // a different separator comment is kept
+        let str = str.replace("### Create", "### Create a user");
+        let FileParseResult { requests, errs } = Parser::parse(&str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests[0].name, Some("Create".to_string()));
         assert_eq!(
-            requests[0],
-            Request {
-                name: Some("New Request".to_string()),
-                request_line: RequestLine {
-                    method: WithDefault::Some(HttpMethod::GET),
-                    target: RequestTarget::from("https://httpbin.org/get"),
-                    http_version: WithDefault::default()
+            requests[0].comments,
+            vec![Comment {
+                value: "Create a user".to_string(),
+                kind: CommentKind::RequestSeparator
+            }]
+        );
+    }
+
+    #[test]
+    pub fn parse_body_blank_lines_before_handler() {
+        // only the blank lines directly before a response handler or redirect are dropped, blank
+        // lines within the body are kept
+        for (str, expected) in [
+            (
+                "POST https://test.com\n\nfirst\n\nsecond\n\n> {% client.log('done') %}\n",
+                "first\n\nsecond",
+            ),
+            (
+                "POST https://test.com\n\nfirst\n\n\nsecond\n\n\n> {% client.log('done') %}",
+                "first\n\n\nsecond",
+            ),
+            (
+                "POST https://test.com\n\nfirst\n\nsecond\n> quoted\n\n> handler.js",
+                "first\n\nsecond\n> quoted",
+            ),
+            (
+                "POST https://test.com\n\n\nfirst\n\nsecond\n\n>> out.json",
+                "first\n\nsecond",
+            ),
+            (
+                "POST https://test.com\n\nfirst\n\n> handler.js\n\n>> out.json",
+                "first",
+            ),
+        ] {
+            let FileParseResult { requests, errs } = Parser::parse(str, false);
+            assert_eq!(errs, vec![]);
+            assert_eq!(
+                requests[0].body,
+                RequestBody::Raw {
+                    data: DataSource::Raw(expected.to_string())
                 },
-                save_response: Some(SaveResponse::NewFileIfExists(std::path::PathBuf::from(
-                    "test.txt"
-                ))),
+                "{}",
+                str
+            );
+        }
+    }
 
-                ..Default::default()
+    #[test]
+    pub fn parse_raw_body_directive() {
+        let str = r####"
+# @raw-body
+POST https://test.com/stream
+Content-Type: text/plain
+
+first line
+
+> quoted line
+>> not a redirect
+
+### next
+GET https://test.com/next
+"####;
+
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].settings.raw_body, Some(true));
+        assert_eq!(
+            requests[0].body,
+            RequestBody::Raw {
+                data: DataSource::Raw("first line\n\n> quoted line\n>> not a redirect".to_string())
+            }
+        );
+        assert_eq!(requests[0].response_handler, None);
+        assert_eq!(requests[0].save_response, None);
+        assert_eq!(requests[1].settings.raw_body, Some(false));
+
+        // without the directive the line starting with '>' is a response handler
+        let FileParseResult { requests, .. } =
+            Parser::parse(&str.replace("# @raw-body\n", ""), false);
+        assert_eq!(
+            requests[0].body,
+            RequestBody::Raw {
+                data: DataSource::Raw("first line".to_string())
             }
         );
+        assert!(requests[0].response_handler.is_some());
     }
 
     #[test]
-    /// If no boundary is given use default boundary '--boundary--'
-    pub fn parse_multipart_no_boundary() {
-        let str = r####"# @name=New Request
-GET https://httpbin.org/{{abc}}
-Content-Type: multipart/form-data
+    pub fn parse_multipart_name_requires_quoting() {
+        let str = r###"POST https://test.com
+Content-Type: multipart/form-data; boundary=boundary
 
---boundary--
+--boundary
+Content-Disposition: form-data; name=my field
 
->>! test.txt"####;
+value
+--boundary--"###;
 
         let FileParseResult { requests, errs } = Parser::parse(str, false);
-        // should have one error warning that no boundary was given
-        assert_eq!(errs.len(), 1);
-        assert!(matches!(
-            errs[0].details[0].error,
-            ParseError::MissingMultipartHeaderBoundaryDefinition(_)
-        ));
-        //assert_eq!(errs, vec![]);
         assert_eq!(requests.len(), 0);
+        assert_eq!(errs.len(), 1);
         assert_eq!(
-            Into::<Request>::into(errs[0].partial_request.clone()),
-            Request {
-                name: Some("New Request".to_string()),
-                request_line: RequestLine {
-                    method: WithDefault::Some(HttpMethod::GET),
-                    target: RequestTarget::from("https://httpbin.org/{{abc}}"),
-                    http_version: WithDefault::default()
-                },
-                headers: vec![Header::new("Content-Type", "multipart/form-data")],
-                body: RequestBody::Multipart {
-                    boundary: "--boundary--".to_string(),
-                    parts: vec![]
-                },
-                save_response: Some(SaveResponse::RewriteFile(std::path::PathBuf::from(
-                    "test.txt"
-                ))),
-
-                ..Default::default()
-            }
+            errs[0].details[0].error,
+            ParseError::MultipartNameRequiresQuoting("my field".to_string())
         );
+        let request: Request = errs[0].partial_request.clone().into();
+        let RequestBody::Multipart { ref parts, .. } = request.body else {
+            panic!("expected multipart body");
+        };
+        assert_eq!(parts[0].disposition, DispositionField::new("my field"));
+
+        // the serialized name is quoted and can be parsed without errors
+        let serialized = request.body.to_string();
+        assert!(serialized.contains("Content-Disposition: form-data; name=\"my field\""));
+        let quoted = str.replace("name=my field", "name=\"my field\"");
+        let FileParseResult { requests, errs } = Parser::parse(&quoted, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests[0].body, request.body);
     }
 
     #[test]
-    pub fn parse_multipart_single_boundary_no_filename() {
-        let str = r###"# @name=New Request
-GET https://httpbin.org/{{abc}}
-Content-Type: multipart/form-data; boundary="--boundary--"
+    pub fn parse_urlencoded_with_charset() {
+        let str = r####"
+POST https://test.com/form
+Content-Type: application/x-www-form-urlencoded; charset=utf-8
 
-----boundary--
-Content-Disposition: form-data; name=""
+key=value&other=text
+"####;
+
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].body,
+            RequestBody::UrlEncoded {
+                url_encoded_params: vec![
+                    UrlEncodedParam::new("key", "value"),
+                    UrlEncodedParam::new("other", "text"),
+                ]
+            }
+        );
+    }
 
+    #[test]
+    pub fn parse_urlencoded_case_insensitive() {
+        let str = r####"
+POST https://test.com/form
+Content-Type: APPLICATION/X-WWW-FORM-URLENCODED
 
-----boundary----"###;
+key=value
+"####;
 
         let FileParseResult { requests, errs } = Parser::parse(str, false);
-        // one error allowed, name should not be empty of content-disposition inside a multipart
-        assert_eq!(errs.len(), 1);
-        //assert_eq!(errs, vec![]);
-        assert_eq!(requests.len(), 0);
+        assert_eq!(errs, vec![]);
+        assert_eq!(requests.len(), 1);
         assert_eq!(
-            Into::<Request>::into(errs[0].partial_request.clone()),
-            Request {
-                name: Some("New Request".to_string()),
-                request_line: RequestLine {
-                    method: WithDefault::Some(HttpMethod::GET),
-                    target: RequestTarget::from("https://httpbin.org/{{abc}}"),
-                    http_version: WithDefault::default()
-                },
-                headers: vec![Header::new(
-                    "Content-Type",
-                    "multipart/form-data; boundary=\"--boundary--\""
-                )],
-                body: RequestBody::Multipart {
-                    boundary: "--boundary--".to_string(),
-                    parts: vec![Multipart {
-                        disposition: DispositionField::new(""),
-                        headers: vec![],
-                        data: DataSource::Raw("".to_string())
-                    }]
-                },
-                ..Default::default()
+            requests[0].body,
+            RequestBody::UrlEncoded {
+                url_encoded_params: vec![UrlEncodedParam::new("key", "value")]
             }
         );
+
+        let str = "POST https://test.com/form\nContent-Type: Application/x-www-form-urlencoded; \
+            charset=utf-8\n\nkey=value";
+        let FileParseResult { requests, errs } = Parser::parse(str, false);
+        assert_eq!(errs, vec![]);
+        assert!(matches!(requests[0].body, RequestBody::UrlEncoded { .. }));
     }
 
     #[test]
@@ -2844,11 +5966,12 @@ Content-Type: application/json
             vec![Header::new("Content-Type", "application/json")]
         );
 
+        // a content type without content is an empty body and neither no body nor raw content
+        assert_eq!(request.body, RequestBody::Empty);
+        assert!(request.body.is_present());
         assert_eq!(
-            request.body,
-            RequestBody::Raw {
-                data: DataSource::Raw(String::new())
-            }
+            crate::Serializer::serialize_request(&request),
+            "POST https://test.com/formEncoded\nContent-Type: application/json\n\n"
         );
 
         let str = r####"
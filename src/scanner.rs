@@ -4,6 +4,11 @@ pub use regex::Regex;
 pub struct Scanner {
     cursor: usize,
     characters: Vec<char>,
+    // the scanned string and the byte offset of every character within it, the last offset is
+    // the length of the string. The offsets are only needed for non ascii input, otherwise the
+    // index of a character is also its byte offset.
+    source: String,
+    byte_offsets: Option<Vec<usize>>,
 }
 
 #[derive(PartialEq, Debug)]
@@ -18,6 +23,17 @@ impl From<regex::Error> for ScanError {
     }
 }
 
+/// Options of `Scanner::get_tokens_with`, by default tokens are only split on whitespace
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct TokenOptions {
+    /// A token starting with a double quote lasts until the closing quote, including whitespace
+    /// in between. The quotes are removed from the token.
+    pub quoted: bool,
+    /// A token starting with '#' begins a comment, it and all following tokens are dropped. '//'
+    /// does not start a comment as it may be the start of a target such as '//host/path'.
+    pub comments: bool,
+}
+
 #[derive(Eq, Debug, Clone)]
 pub struct ScannerPos {
     pub cursor: usize,
@@ -50,6 +66,11 @@ pub struct ErrorContext {
     pub context: String,
     pub line: u32,
     pub column: u32,
+    // the full line of the input where the error starts
+    pub source_line: String,
+    // marks the error within the source line with '^~~~', tabs of the source line are kept so the
+    // marker is aligned when printed below it
+    pub caret: String,
 }
 
 #[derive(Debug)]
@@ -117,9 +138,41 @@ pub const WS_CHARS: [char; 4] = [' ', '\t', '\r', '\u{000C}'];
 
 impl Scanner {
     pub fn new(string: &str) -> Scanner {
+        let byte_offsets = (!string.is_ascii()).then(|| {
+            let mut byte_offsets: Vec<usize> =
+                string.char_indices().map(|(index, _)| index).collect();
+            byte_offsets.push(string.len());
+            byte_offsets
+        });
         Scanner {
             cursor: 0,
             characters: string.chars().collect(),
+            source: string.to_string(),
+            byte_offsets,
+        }
+    }
+
+    /// Returns the input that has not been scanned yet, starting at the cursor. This is an O(1)
+    /// slice into the scanned string.
+    pub fn remaining(&self) -> &str {
+        &self.source[self.byte_offset()..]
+    }
+
+    /// Returns the input that has already been scanned, everything before the cursor. This is an
+    /// O(1) slice into the scanned string.
+    pub fn consumed(&self) -> &str {
+        &self.source[..self.byte_offset()]
+    }
+
+    fn byte_offset(&self) -> usize {
+        self.byte_offset_at(self.cursor)
+    }
+
+    fn byte_offset_at(&self, index: usize) -> usize {
+        let index = index.min(self.characters.len());
+        match &self.byte_offsets {
+            Some(byte_offsets) => byte_offsets[index],
+            None => index,
         }
     }
 
@@ -145,6 +198,9 @@ impl Scanner {
     }
 
     pub fn get_error_context(&self, start_pos: usize, end_pos: Option<usize>) -> ErrorContext {
+        // positions of errors may point past the end of the input
+        let start_pos = start_pos.min(self.characters.len());
+        let end_pos = end_pos.map(|end_pos| end_pos.clamp(start_pos, self.characters.len()));
         let mut line = 0;
         let mut last_newline_pos = 0;
         for (index, char) in self.characters[..start_pos].iter().enumerate() {
@@ -166,10 +222,30 @@ impl Scanner {
                 .collect::<String>()
         };
 
+        let line_start = if line == 0 { 0 } else { last_newline_pos + 1 };
+        let line_end = self.characters[start_pos..]
+            .iter()
+            .position(|c| c == &'\n')
+            .map_or(self.characters.len(), |offset| start_pos + offset);
+        let source_line = self.characters[line_start..line_end]
+            .iter()
+            .collect::<String>();
+
+        let mut caret = self.characters[line_start..start_pos]
+            .iter()
+            .map(|c| if c == &'\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        caret.push('^');
+        let marked_len =
+            end_pos.map_or(0, |end_pos| end_pos.min(line_end).saturating_sub(start_pos));
+        caret.push_str(&"~".repeat(marked_len.saturating_sub(1)));
+
         ErrorContext {
             line,
             column: column as u32,
             context,
+            source_line,
+            caret,
         }
     }
 
@@ -291,6 +367,19 @@ impl Scanner {
     /// matching the empty string "" will always return in a match without moving the cursor
     /// forward.
     pub fn match_str_forward(&mut self, str: &str) -> bool {
+        self.match_str_forward_with(str, |a, b| a == b)
+    }
+
+    /// Same as `match_str_forward` but ASCII characters are compared case insensitive. Use it
+    /// for parts that are case insensitive in http such as schemes, header names or media types.
+    pub fn match_str_forward_ci(&mut self, str: &str) -> bool {
+        self.match_str_forward_with(str, |a, b| a.eq_ignore_ascii_case(&b))
+    }
+
+    fn match_str_forward_with<F>(&mut self, str: &str, eq: F) -> bool
+    where
+        F: Fn(char, char) -> bool,
+    {
         let chars = str.chars().collect::<Vec<char>>();
         let sequence = chars.as_slice();
 
@@ -307,7 +396,7 @@ impl Scanner {
                 break false;
             }
             let current_char: char = self.characters[peek_cursor];
-            if current_char != sequence[sequence_cursor] {
+            if !eq(current_char, sequence[sequence_cursor]) {
                 break false;
             }
             sequence_cursor += 1;
@@ -355,10 +444,22 @@ impl Scanner {
             regex_str = format!("^{}", user_regex_str);
         }
         let regex = regex::bytes::Regex::new(&regex_str)?;
+        self.match_compiled_regex_forward(&regex)
+    }
+
+    /// Same as `match_regex_forward` but with an already compiled regex so regexes used
+    /// repeatedly are only compiled once. The regex has to start with `^` to only match at the
+    /// cursor.
+    pub fn match_compiled_regex_forward(
+        &mut self,
+        regex: &regex::bytes::Regex,
+    ) -> Result<Option<Vec<String>>, ScanError> {
+        if self.cursor >= self.characters.len() {
+            return Err(ScanError::EndOfLine);
+        }
 
-        let string_tmp = self.characters[self.cursor..].iter().collect::<String>();
-        let bytes = string_tmp.as_bytes();
-        return match regex.captures(bytes) {
+        let mut num_chars = 0;
+        let captures = match regex.captures(self.remaining().as_bytes()) {
             Some(comment_captures) => {
                 let mut str_captures: Vec<String> = Vec::new();
 
@@ -367,8 +468,7 @@ impl Scanner {
                     // if we got a match we adjust the cursor otherwise we don't
                     if i == 0 {
                         let matched_str = std::str::from_utf8(capture.unwrap().as_bytes()).unwrap();
-                        let num_chars = matched_str.chars().count();
-                        self.cursor += num_chars;
+                        num_chars = matched_str.chars().count();
                     } else {
                         let capture_bytes: Vec<u8> = capture.unwrap().as_bytes().to_owned();
                         match String::from_utf8(capture_bytes) {
@@ -379,10 +479,12 @@ impl Scanner {
                         }
                     }
                 }
-                return Ok(Some(str_captures));
+                Some(str_captures)
             }
-            None => Ok(None),
+            None => None,
         };
+        self.cursor += num_chars;
+        Ok(captures)
     }
 
     /// Get the current line (excluding the new line character) and advance to the next.
@@ -431,6 +533,37 @@ impl Scanner {
         )
     }
 
+    /// Same as `peek_line` but borrows the line from the scanned string instead of allocating
+    /// a new one
+    pub fn peek_line_ref(&self) -> Option<&str> {
+        if self.is_done() {
+            return None;
+        }
+        let line_end = self.line_end();
+        Some(&self.source[self.byte_offset()..self.byte_offset_at(line_end)])
+    }
+
+    /// Same as `get_line_and_advance` but borrows the line from the scanned string instead of
+    /// allocating a new one
+    pub fn get_line_and_advance_ref(&mut self) -> Option<&str> {
+        if self.is_done() {
+            return None;
+        }
+        let line_start = self.byte_offset();
+        let line_end = self.line_end();
+        // skip \n character
+        self.cursor = (line_end + 1).min(self.characters.len());
+        Some(&self.source[line_start..self.byte_offset_at(line_end)])
+    }
+
+    // position of the '\n' ending the current line or the end of the input
+    fn line_end(&self) -> usize {
+        self.characters[self.cursor..]
+            .iter()
+            .position(|c| *c == '\n')
+            .map_or(self.characters.len(), |offset| self.cursor + offset)
+    }
+
     pub fn skip_to_next_line(&mut self) {
         loop {
             if self.is_done() {
@@ -444,14 +577,53 @@ impl Scanner {
         }
     }
 
+    /// Move the cursor to the start of the next line starting with `separator`, leading
+    /// whitespace of the line is ignored. The cursor is not moved if the current line already
+    /// starts with it. Returns false if no such line is found, the scanner is done afterwards.
+    pub fn skip_to_next_separator(&mut self, separator: &str) -> bool {
+        while let Some(line) = self.peek_line_ref() {
+            if line.trim_start().starts_with(separator) {
+                return true;
+            }
+            self.skip_to_next_line();
+        }
+        false
+    }
+
+    /// Split the whole content of the scanner, independent of the cursor, into tokens separated
+    /// by one or more whitespace characters (as defined by `char::is_whitespace`). Leading and
+    /// trailing whitespace is ignored, there are no empty tokens. Percent encoded spaces such as
+    /// `%20` are not whitespace and do not split a token.
     pub fn get_tokens(&self) -> Vec<String> {
-        // @TODO check whitespace
-        self.characters
-            .iter()
-            .collect::<String>()
-            .split_whitespace()
-            .map(|s| s.to_string())
-            .collect()
+        self.get_tokens_with(&TokenOptions::default())
+    }
+
+    /// Split the content into tokens same as `get_tokens` with the given options to keep quoted
+    /// strings as single token or to drop a trailing comment. An unclosed quote lasts until the
+    /// end of the content.
+    pub fn get_tokens_with(&self, options: &TokenOptions) -> Vec<String> {
+        let mut tokens: Vec<String> = Vec::new();
+        let mut chars = self.characters.iter().peekable();
+        loop {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            let Some(&first) = chars.next() else {
+                break;
+            };
+            if options.comments && first == '#' {
+                break;
+            }
+            let mut token = String::new();
+            if options.quoted && first == '"' {
+                token.extend(chars.by_ref().take_while(|c| **c != '"'));
+            } else {
+                token.push(first);
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    token.push(*c);
+                }
+            }
+            tokens.push(token);
+        }
+        tokens
     }
 
     /// Return the previous line's bounds (start and end position)
@@ -539,6 +711,48 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    pub fn error_context_caret() {
+        let scanner = Scanner::new("first line\nGET https://test.com HTTP/abc\nlast");
+        let context = scanner.get_error_context(32, Some(40));
+        assert_eq!(context.line, 1);
+        assert_eq!(context.source_line, "GET https://test.com HTTP/abc");
+        assert_eq!(
+            context.caret,
+            format!("{}^{}", " ".repeat(21), "~".repeat(7))
+        );
+
+        // tabs are kept to align the caret, the marker ends at the end of the line
+        let scanner = Scanner::new("\tkey\tvalue\nnext");
+        let context = scanner.get_error_context(5, Some(100));
+        assert_eq!(context.line, 0);
+        assert_eq!(context.source_line, "\tkey\tvalue");
+        assert_eq!(context.caret, "\t   \t^~~~~");
+
+        // without end only the start is marked
+        let context = scanner.get_error_context(0, None);
+        assert_eq!(context.caret, "^");
+    }
+
+    #[test]
+    pub fn remaining_and_consumed() {
+        let mut scanner = Scanner::new("GET ü/path\nnext");
+        assert_eq!(scanner.remaining(), "GET ü/path\nnext");
+        assert_eq!(scanner.consumed(), "");
+
+        assert!(scanner.match_str_forward("GET ü"));
+        assert_eq!(scanner.consumed(), "GET ü");
+        assert_eq!(scanner.remaining(), "/path\nnext");
+
+        scanner.skip_to_next_line();
+        assert_eq!(scanner.consumed(), "GET ü/path\n");
+        assert_eq!(scanner.remaining(), "next");
+
+        scanner.set_pos(100_usize);
+        assert_eq!(scanner.remaining(), "");
+        assert_eq!(scanner.consumed(), "GET ü/path\nnext");
+    }
+
     #[test]
     pub fn seek_return() {
         let string = "abc def    ghi\n\n next line";
@@ -599,6 +813,29 @@ mod tests {
         assert!(scanner.cursor == string.len());
     }
 
+    #[test]
+    pub fn line_refs_match_owned_lines() {
+        for string in [
+            "",
+            "\n",
+            "first\r\nsecond\n\n",
+            "äöü line\n😀 end",
+            "no newline",
+        ] {
+            let mut owned = Scanner::new(string);
+            let mut borrowed = Scanner::new(string);
+            loop {
+                assert_eq!(borrowed.peek_line_ref(), owned.peek_line().as_deref());
+                let line = owned.get_line_and_advance();
+                assert_eq!(borrowed.get_line_and_advance_ref(), line.as_deref());
+                assert_eq!(borrowed.get_cursor(), owned.get_cursor());
+                if line.is_none() {
+                    break;
+                }
+            }
+        }
+    }
+
     #[test]
     pub fn skip_to_next_line() {
         let string = "First line\nSecond Line\n\n";
@@ -615,6 +852,26 @@ mod tests {
         assert!(scanner.is_done());
     }
 
+    #[test]
+    pub fn skip_to_next_separator() {
+        let string = "GET first\nAccept: */*\n\n  ### second\nGET second\n###\nGET third";
+        let mut scanner = Scanner::new(string);
+
+        assert!(scanner.skip_to_next_separator("###"));
+        assert_eq!(scanner.peek_line_ref(), Some("  ### second"));
+        // already at a separator
+        assert!(scanner.skip_to_next_separator("###"));
+        assert_eq!(scanner.peek_line_ref(), Some("  ### second"));
+
+        scanner.skip_to_next_line();
+        assert!(scanner.skip_to_next_separator("###"));
+        assert_eq!(scanner.peek_line_ref(), Some("###"));
+
+        scanner.skip_to_next_line();
+        assert!(!scanner.skip_to_next_separator("###"));
+        assert!(scanner.is_done());
+    }
+
     #[test]
     pub fn skip_empty_lines() {
         let string = "0\n\n\n4";
@@ -668,6 +925,27 @@ mod tests {
         assert!(scanner.match_str_forward(""));
     }
 
+    #[test]
+    pub fn match_str_forward_ci() {
+        let mut scanner = Scanner::new("HTTPS://Test.com\nContent-TYPE: Ünicode");
+
+        assert!(scanner.match_str_forward_ci("https://"));
+        assert_eq!(scanner.cursor, 8);
+        assert!(!scanner.match_str_forward("test"));
+        assert!(scanner.match_str_forward_ci("test.COM\n"));
+
+        assert!(!scanner.match_str_forward_ci("content-length"));
+        assert_eq!(scanner.cursor, 17);
+        assert!(scanner.match_str_forward_ci("content-type: "));
+        // only ascii characters are compared case insensitive
+        assert!(!scanner.match_str_forward_ci("ünicode"));
+        assert!(scanner.match_str_forward_ci("ÜNICODE"));
+        assert!(scanner.is_done());
+
+        assert!(!scanner.match_str_forward_ci("a"));
+        assert!(scanner.match_str_forward_ci(""));
+    }
+
     #[test]
     pub fn take() {
         let string = "0 \n";
@@ -752,6 +1030,28 @@ mod tests {
         assert_eq!(matches, vec!["000"]);
     }
 
+    #[test]
+    pub fn match_compiled_regex_forward() {
+        let regex = regex::bytes::Regex::new("^([a-z]+):").unwrap();
+        let mut scanner = Scanner::new("key: value\nother: value");
+        assert_eq!(
+            scanner.match_compiled_regex_forward(&regex).unwrap(),
+            Some(vec!["key".to_string()])
+        );
+        // the same regex can be reused and only matches at the cursor
+        assert_eq!(scanner.match_compiled_regex_forward(&regex).unwrap(), None);
+        scanner.skip_to_next_line();
+        assert_eq!(
+            scanner.match_compiled_regex_forward(&regex).unwrap(),
+            Some(vec!["other".to_string()])
+        );
+        scanner.get_line_and_advance();
+        assert_eq!(
+            scanner.match_compiled_regex_forward(&regex),
+            Err(ScanError::EndOfLine)
+        );
+    }
+
     #[test]
     pub fn match_regex_forward_no_captures() {
         let string = "000 123 456 | abc def ghi | \n\t\r\n end";
@@ -780,6 +1080,50 @@ mod tests {
         assert_eq!(matches, ["\t\r".to_string()]);
     }
 
+    #[test]
+    pub fn get_tokens() {
+        let scanner = Scanner::new("  GET\thttps://test.com/a%20b   HTTP/1.1 \n");
+        assert_eq!(
+            scanner.get_tokens(),
+            vec!["GET", "https://test.com/a%20b", "HTTP/1.1"]
+        );
+
+        let scanner = Scanner::new(r#"GET https://test.com "HTTP/1.1" # comment"#);
+        assert_eq!(
+            scanner.get_tokens(),
+            vec!["GET", "https://test.com", "\"HTTP/1.1\"", "#", "comment"]
+        );
+        let options = TokenOptions {
+            quoted: true,
+            comments: true,
+        };
+        assert_eq!(
+            scanner.get_tokens_with(&options),
+            vec!["GET", "https://test.com", "HTTP/1.1"]
+        );
+
+        let scanner = Scanner::new(r#"GET "https://test.com/a b" #tag "unclosed"#);
+        assert_eq!(
+            scanner.get_tokens_with(&TokenOptions {
+                quoted: true,
+                comments: false
+            }),
+            vec!["GET", "https://test.com/a b", "#tag", "unclosed"]
+        );
+        assert_eq!(
+            scanner.get_tokens_with(&options),
+            vec!["GET", "https://test.com/a b"]
+        );
+
+        // '#' within a token does not start a comment, neither does '//'
+        let scanner = Scanner::new("GET //test.com/?tag=#1");
+        assert_eq!(
+            scanner.get_tokens_with(&options),
+            vec!["GET", "//test.com/?tag=#1"]
+        );
+        assert_eq!(Scanner::new("   ").get_tokens(), Vec::<String>::new());
+    }
+
     #[test]
     pub fn get_prev_line_bounds() {
         let string = "abc\ndef\n\n\n";
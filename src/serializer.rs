@@ -12,9 +12,12 @@ impl Serializer {
         if let Some(ext) = file_model.extension.as_ref() {
             path = file_model.path.with_extension(ext.to_string());
         }
-        let content = Serializer::serialize_requests(
+        let mut content = Serializer::serialize_requests(
             &file_model.requests.iter().collect::<Vec<&model::Request>>()[..],
         );
+        if let Some(ref modeline) = file_model.modeline {
+            content.insert_str(0, &format!("{}\n", modeline));
+        }
 
         match std::fs::write(path, content) {
             Ok(_) => Ok(()),
@@ -34,6 +37,7 @@ impl Serializer {
                 })
             {
                 result.push_str(crate::parser::REQUEST_SEPARATOR);
+                result.push('\n');
             }
             result.push_str(&Serializer::serialize_request(request));
 
@@ -66,7 +70,7 @@ impl Serializer {
 
         result.push_str(&request.settings.serialized());
 
-        if let Some(pre_request_script) = &request.pre_request_script {
+        for pre_request_script in request.pre_request_scripts.iter() {
             result.push_str(&pre_request_script.to_string());
             result.push('\n');
         }
@@ -148,6 +152,7 @@ mod tests {
                 no_redirect: Some(true),
                 no_log: Some(true),
                 no_cookie_jar: Some(true),
+                ..RequestSettings::default()
             },
             request_line: RequestLine {
                 method: WithDefault::Some(HttpMethod::GET),
@@ -155,9 +160,11 @@ mod tests {
                 http_version: WithDefault::default(),
             },
             body: RequestBody::None,
-            pre_request_script: None,
+            pre_request_scripts: vec![],
             response_handler: None,
             save_response: None,
+            index: 0,
+            warnings: vec![],
         };
         let expected = r"### The Request
 # @name=RequestName
@@ -170,6 +177,88 @@ GET https://httpbin.org";
         assert_eq!(serialized, expected);
     }
 
+    #[test]
+    pub fn serialize_requests_without_separator() {
+        let request = |target: &str| Request {
+            request_line: RequestLine {
+                method: WithDefault::Some(HttpMethod::GET),
+                target: RequestTarget::from(target),
+                http_version: WithDefault::default(),
+            },
+            ..Default::default()
+        };
+        let first = request("https://httpbin.org/first");
+        let second = request("https://httpbin.org/second");
+
+        // the generated separator is on its own line
+        let serialized = Serializer::serialize_requests(&[&first, &second]);
+        assert_eq!(
+            serialized,
+            "GET https://httpbin.org/first\n###\nGET https://httpbin.org/second"
+        );
+        let parsed = Parser::parse(&serialized, false);
+        assert_eq!(parsed.errs, vec![]);
+        assert_eq!(parsed.requests.len(), 2);
+        assert_eq!(
+            parsed.requests[1].request_line.target,
+            second.request_line.target
+        );
+    }
+
+    #[test]
+    pub fn serialize_timeouts() {
+        let request = Request {
+            settings: RequestSettings {
+                connection_timeout: Some(10),
+                socket_timeout: Some(30),
+                ..RequestSettings::default()
+            },
+            request_line: RequestLine {
+                method: WithDefault::Some(HttpMethod::GET),
+                target: RequestTarget::from("https://httpbin.org"),
+                http_version: WithDefault::default(),
+            },
+            ..Default::default()
+        };
+        let expected = r"# @connection-timeout 10
+# @socket-timeout 30
+GET https://httpbin.org";
+
+        let serialized = Serializer::serialize_requests(&[&request]);
+        assert_eq!(serialized, expected);
+
+        let file_parse_result = Parser::parse(&serialized, false);
+        assert_eq!(file_parse_result.errs, vec![]);
+        assert_eq!(file_parse_result.requests, vec![request]);
+    }
+
+    #[test]
+    pub fn serialize_proxy() {
+        let request = Request {
+            settings: RequestSettings {
+                no_proxy: Some(true),
+                proxy: Some("http://proxy:8080".to_string()),
+                ..RequestSettings::default()
+            },
+            request_line: RequestLine {
+                method: WithDefault::Some(HttpMethod::GET),
+                target: RequestTarget::from("https://httpbin.org"),
+                http_version: WithDefault::default(),
+            },
+            ..Default::default()
+        };
+        let expected = r"# @no-proxy
+# @proxy http://proxy:8080
+GET https://httpbin.org";
+
+        let serialized = Serializer::serialize_requests(&[&request]);
+        assert_eq!(serialized, expected);
+
+        let file_parse_result = Parser::parse(&serialized, false);
+        assert_eq!(file_parse_result.errs, vec![]);
+        assert_eq!(file_parse_result.requests, vec![request]);
+    }
+
     #[test]
     pub fn serialize_only_url() {
         let request = Request {
@@ -180,6 +269,7 @@ GET https://httpbin.org";
                 no_redirect: None,
                 no_log: None,
                 no_cookie_jar: None,
+                ..RequestSettings::default()
             },
             request_line: RequestLine {
                 method: WithDefault::default(),
@@ -187,9 +277,11 @@ GET https://httpbin.org";
                 http_version: WithDefault::default(),
             },
             body: RequestBody::None,
-            pre_request_script: None,
+            pre_request_scripts: vec![],
             response_handler: None,
             save_response: None,
+            index: 0,
+            warnings: vec![],
         };
         let expected = r"https://httpbin.org";
 
@@ -207,6 +299,7 @@ GET https://httpbin.org";
                 no_redirect: None,
                 no_log: None,
                 no_cookie_jar: None,
+                ..RequestSettings::default()
             },
             request_line: RequestLine {
                 method: WithDefault::Some(HttpMethod::GET),
@@ -214,9 +307,11 @@ GET https://httpbin.org";
                 http_version: WithDefault::default(),
             },
             body: RequestBody::None,
-            pre_request_script: None,
+            pre_request_scripts: vec![],
             response_handler: None,
             save_response: None,
+            index: 0,
+            warnings: vec![],
         };
         let expected = r"GET https://httpbin.org";
 
@@ -234,6 +329,7 @@ GET https://httpbin.org";
                 no_redirect: None,
                 no_log: None,
                 no_cookie_jar: None,
+                ..RequestSettings::default()
             },
             request_line: RequestLine {
                 method: WithDefault::Some(HttpMethod::GET),
@@ -241,9 +337,11 @@ GET https://httpbin.org";
                 http_version: WithDefault::Some(HttpVersion { major: 1, minor: 1 }),
             },
             body: RequestBody::None,
-            pre_request_script: None,
+            pre_request_scripts: vec![],
             response_handler: None,
             save_response: None,
+            index: 0,
+            warnings: vec![],
         };
         let expected = r"GET https://httpbin.org HTTP/1.1";
 
@@ -261,6 +359,7 @@ GET https://httpbin.org";
                 no_redirect: None,
                 no_log: None,
                 no_cookie_jar: None,
+                ..RequestSettings::default()
             },
             request_line: RequestLine {
                 method: WithDefault::Some(HttpMethod::CUSTOM("CustomMethod".to_string())),
@@ -268,9 +367,11 @@ GET https://httpbin.org";
                 http_version: WithDefault::Some(HttpVersion { major: 2, minor: 1 }),
             },
             body: RequestBody::None,
-            pre_request_script: None,
+            pre_request_scripts: vec![],
             response_handler: None,
             save_response: None,
+            index: 0,
+            warnings: vec![],
         };
         let expected = r"CustomMethod https://httpbin.org HTTP/2.1";
         let serialized = Serializer::serialize_requests(&[&request]);
@@ -318,6 +419,7 @@ abc=def&ghi=jkl"####;
                 no_redirect: None,
                 no_log: None,
                 no_cookie_jar: None,
+                ..RequestSettings::default()
             },
             request_line: RequestLine {
                 method: WithDefault::Some(HttpMethod::POST),
@@ -351,9 +453,11 @@ abc=def&ghi=jkl"####;
                         .to_string(),
                 ),
             },
-            pre_request_script: None,
+            pre_request_scripts: vec![],
             response_handler: None,
             save_response: None,
+            index: 0,
+            warnings: vec![],
         };
         let expected = r####"POST https://httpbin.org/post
 Content-Type: application/json
@@ -395,6 +499,7 @@ Content-Type: application/json
                 no_redirect: None,
                 no_log: None,
                 no_cookie_jar: None,
+                ..RequestSettings::default()
             },
             request_line: RequestLine {
                 method: WithDefault::Some(HttpMethod::POST),
@@ -404,9 +509,11 @@ Content-Type: application/json
             body: RequestBody::Raw {
                 data: DataSource::FromFilepath("/path/to/file.json".to_string()),
             },
-            pre_request_script: None,
+            pre_request_scripts: vec![],
             response_handler: None,
             save_response: None,
+            index: 0,
+            warnings: vec![],
         };
         let expected = r####"POST https://httpbin.org/post
 Content-Type: application/json
@@ -427,6 +534,7 @@ Content-Type: application/json
                 no_redirect: None,
                 no_log: None,
                 no_cookie_jar: None,
+                ..RequestSettings::default()
             },
             request_line: RequestLine {
                 method: WithDefault::Some(HttpMethod::POST),
@@ -436,11 +544,13 @@ Content-Type: application/json
             body: RequestBody::Raw {
                 data: DataSource::FromFilepath("/path/to/file.json".to_string()),
             },
-            pre_request_script: None,
+            pre_request_scripts: vec![],
             response_handler: None,
             save_response: Some(SaveResponse::NewFileIfExists(PathBuf::from(
                 "./path/to/out.json",
             ))),
+            index: 0,
+            warnings: vec![],
         };
         let expected = r####"POST https://httpbin.org/post
 Content-Type: application/json
@@ -468,6 +578,7 @@ Header::new("Cache-Control", "max-age=3600")
                 no_redirect: None,
                 no_log: None,
                 no_cookie_jar: None,
+                ..RequestSettings::default()
             },
             request_line: RequestLine {
                 method: WithDefault::Some(HttpMethod::POST),
@@ -475,9 +586,11 @@ Header::new("Cache-Control", "max-age=3600")
                 http_version: WithDefault::default(),
             },
             body: RequestBody::None,
-            pre_request_script: None,
+            pre_request_scripts: vec![],
             response_handler: None,
             save_response: None,
+            index: 0,
+            warnings: vec![],
         };
         // we expect a newline after the headers
         let expected = r"POST https://httpbin.org/post
@@ -509,6 +622,7 @@ comments: vec![Comment {
                 no_redirect: Some(true),
                 no_log: Some(true),
                 no_cookie_jar: Some(true),
+                ..RequestSettings::default()
             },
             request_line: RequestLine {
                 method: WithDefault::Some(HttpMethod::POST),
@@ -537,9 +651,11 @@ comments: vec![Comment {
   ],
   "isActive": true
 }"####.to_string() )},
-            pre_request_script: Some(PreRequestScript::Script(r####" request.variables.set("firstname", "John") "####.to_string())),
+            pre_request_scripts: vec![PreRequestScript::Script(r####" request.variables.set("firstname", "John") "####.to_string())],
             response_handler: Some(ResponseHandler::FromFilepath(r####"/path/to/responseHandler.js"####.to_string())),
             save_response: Some(SaveResponse::RewriteFile(PathBuf::from("/path/to/out_file"))),
+            index: 0,
+            warnings: vec![],
         };
 
         // we expect a newline after the headers
@@ -614,6 +730,7 @@ comments: vec![Comment {
                 no_redirect: Some(true),
                 no_log: Some(true),
                 no_cookie_jar: Some(true),
+                ..RequestSettings::default()
             },
             request_line: RequestLine {
                 method: WithDefault::Some(HttpMethod::POST),
@@ -642,9 +759,11 @@ comments: vec![Comment {
                 ]
             },
 
-            pre_request_script: Some(PreRequestScript::Script("\nrequest.variables.set(\"firstname\", \"John\")\n".to_string())),
+            pre_request_scripts: vec![PreRequestScript::Script("\nrequest.variables.set(\"firstname\", \"John\")\n".to_string())],
             response_handler: Some(ResponseHandler::Script("\n    client.global.set(\"my_cookie\", response.headers.valuesOf(\"Set-Cookie\")[0]);\n".to_string())),
             save_response: Some(SaveResponse::NewFileIfExists(PathBuf::from("/path/to/out_file"))),
+            index: 0,
+            warnings: vec![],
         };
 
         // we expect a newline after the headers
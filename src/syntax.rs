@@ -0,0 +1,377 @@
+//! Lossless representation of a request file. Other than the `model` which only keeps the values
+//! of a request, the `SyntaxTree` keeps every line of the input as is including whitespace,
+//! comments and line endings. Converting the tree back to a string results in the exact input.
+//! Edits only replace the affected lines so the remaining file keeps its formatting.
+
+use crate::parser::REQUEST_SEPARATOR;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxKind {
+    // '###' with an optional name
+    Separator,
+    // '#' or '//' comment
+    Comment,
+    // comment containing meta information such as '# @name=...' or '# @no-log'
+    Directive,
+    // pre request script, either '< path' or the lines of a '< {% ... %}' block
+    PreRequestScript,
+    // the request line and indented lines continuing it
+    RequestLine,
+    Header,
+    Blank,
+    // every line after the empty line following the headers
+    Body,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxLine {
+    pub kind: SyntaxKind,
+    // text of the line including its line ending
+    pub text: String,
+}
+
+/// Lines from a request separator (or the start of the file) until the next separator
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SyntaxRequest {
+    pub lines: Vec<SyntaxLine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SyntaxTree {
+    pub requests: Vec<SyntaxRequest>,
+}
+
+#[derive(PartialEq, Eq)]
+enum State {
+    Preamble,
+    Script,
+    RequestLine,
+    Headers,
+    Body,
+}
+
+impl SyntaxTree {
+    /// Split the input into requests and classify every line of them
+    pub fn parse(string: &str) -> SyntaxTree {
+        let mut requests: Vec<SyntaxRequest> = Vec::new();
+        let mut current = SyntaxRequest::default();
+        let mut state = State::Preamble;
+
+        for text in string.split_inclusive('\n') {
+            let trimmed = text.trim();
+            if trimmed.starts_with(REQUEST_SEPARATOR) {
+                if !current.lines.is_empty() {
+                    requests.push(current);
+                }
+                current = SyntaxRequest::default();
+                current.lines.push(SyntaxLine {
+                    kind: SyntaxKind::Separator,
+                    text: text.to_string(),
+                });
+                state = State::Preamble;
+                continue;
+            }
+
+            let kind = match state {
+                State::Preamble | State::Script if trimmed.is_empty() => SyntaxKind::Blank,
+                State::Script => {
+                    if trimmed.contains("%}") {
+                        state = State::Preamble;
+                    }
+                    SyntaxKind::PreRequestScript
+                }
+                State::Preamble => {
+                    if trimmed.starts_with('#') || trimmed.starts_with("//") {
+                        let comment = trimmed.trim_start_matches(['#', '/']).trim_start();
+                        if comment.starts_with('@') {
+                            SyntaxKind::Directive
+                        } else {
+                            SyntaxKind::Comment
+                        }
+                    } else if trimmed.starts_with('<') {
+                        if trimmed.contains("{%") && !trimmed.contains("%}") {
+                            state = State::Script;
+                        }
+                        SyntaxKind::PreRequestScript
+                    } else {
+                        state = State::RequestLine;
+                        SyntaxKind::RequestLine
+                    }
+                }
+                State::RequestLine if text.starts_with([' ', '\t']) && !trimmed.is_empty() => {
+                    SyntaxKind::RequestLine
+                }
+                State::RequestLine | State::Headers if trimmed.is_empty() => {
+                    state = State::Body;
+                    SyntaxKind::Blank
+                }
+                State::RequestLine | State::Headers => {
+                    state = State::Headers;
+                    SyntaxKind::Header
+                }
+                State::Body => SyntaxKind::Body,
+            };
+            current.lines.push(SyntaxLine {
+                kind,
+                text: text.to_string(),
+            });
+        }
+        if !current.lines.is_empty() {
+            requests.push(current);
+        }
+        SyntaxTree { requests }
+    }
+
+    // requests that contain a request line, in the same order as parsed by the `Parser`
+    fn request_mut(&mut self, index: usize) -> Option<&mut SyntaxRequest> {
+        self.requests
+            .iter_mut()
+            .filter(|request| {
+                request
+                    .lines
+                    .iter()
+                    .any(|line| line.kind == SyntaxKind::RequestLine)
+            })
+            .nth(index)
+    }
+
+    /// Set the name of the request at `index`, counting only requests with a request line. An
+    /// existing `@name` directive is replaced, otherwise the name of the separator. If neither is
+    /// present a `# @name=` directive is added before the request line. Returns false if there
+    /// is no request at the index.
+    pub fn set_request_name(&mut self, index: usize, name: &str) -> bool {
+        let Some(request) = self.request_mut(index) else {
+            return false;
+        };
+
+        if let Some(line) = request.lines.iter_mut().find(|line| {
+            line.kind == SyntaxKind::Directive
+                && line
+                    .text
+                    .trim_start()
+                    .trim_start_matches(['#', '/'])
+                    .trim_start()
+                    .starts_with("@name")
+        }) {
+            let start = line.text.find("@name").unwrap();
+            let line_ending = line_ending(&line.text);
+            line.text = format!("{}@name={}{}", &line.text[..start], name, line_ending);
+            return true;
+        }
+
+        if let Some(line) = request
+            .lines
+            .iter_mut()
+            .find(|line| line.kind == SyntaxKind::Separator)
+        {
+            let line_ending = line_ending(&line.text);
+            // only the leading '#' are kept, the old name may contain '#' itself
+            let indent = line.text.len() - line.text.trim_start().len();
+            let hashes = line.text[indent..]
+                .chars()
+                .take_while(|c| *c == '#')
+                .count();
+            let separator = &line.text[..indent + hashes];
+            line.text = format!("{} {}{}", separator, name, line_ending);
+            return true;
+        }
+
+        let position = request
+            .lines
+            .iter()
+            .position(|line| line.kind == SyntaxKind::RequestLine)
+            .unwrap();
+        request.lines.insert(
+            position,
+            SyntaxLine {
+                kind: SyntaxKind::Directive,
+                text: format!("# @name={}\n", name),
+            },
+        );
+        true
+    }
+
+    /// Set the value of the header `key` (compared case insensitive) of the request at `index`,
+    /// counting only requests with a request line. The header is added after the other headers
+    /// if it is not present. Returns false if there is no request at the index.
+    pub fn set_header_value(&mut self, index: usize, key: &str, value: &str) -> bool {
+        let Some(request) = self.request_mut(index) else {
+            return false;
+        };
+
+        if let Some(line) = request.lines.iter_mut().find(|line| {
+            line.kind == SyntaxKind::Header
+                && line
+                    .text
+                    .split_once(':')
+                    .is_some_and(|(line_key, _)| line_key.trim().eq_ignore_ascii_case(key))
+        }) {
+            let (line_key, line_value) = line.text.split_once(':').unwrap();
+            // keep the whitespace between the colon and the value
+            let whitespace_len =
+                line_value.len() - line_value.trim_start_matches([' ', '\t']).len();
+            let line_ending = line_ending(&line.text);
+            line.text = format!(
+                "{}:{}{}{}",
+                line_key,
+                &line_value[..whitespace_len],
+                value,
+                line_ending
+            );
+            return true;
+        }
+
+        let position = request
+            .lines
+            .iter()
+            .rposition(|line| {
+                line.kind == SyntaxKind::Header || line.kind == SyntaxKind::RequestLine
+            })
+            .unwrap();
+        // the last line may not end with a newline if it is at the end of the file
+        if !request.lines[position].text.ends_with('\n') {
+            request.lines[position].text.push('\n');
+        }
+        request.lines.insert(
+            position + 1,
+            SyntaxLine {
+                kind: SyntaxKind::Header,
+                text: format!("{}: {}\n", key, value),
+            },
+        );
+        true
+    }
+}
+
+fn line_ending(text: &str) -> &str {
+    if text.ends_with("\r\n") {
+        "\r\n"
+    } else if text.ends_with('\n') {
+        "\n"
+    } else {
+        ""
+    }
+}
+
+impl std::fmt::Display for SyntaxTree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for request in self.requests.iter() {
+            for line in request.lines.iter() {
+                f.write_str(&line.text)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parser;
+
+    const FILE: &str = "# leading comment\r
+### First
+# @no-log
+GET https://test.com/first
+    ?id=1
+Accept:  */*
+Content-Type: application/json
+
+{
+  \"key\": \"value\"
+}
+
+> {% client.log('done') %}
+#####
+// @name = second
+< {%
+  request.variables.set('a', 'b')
+%}
+POST https://test.com/second
+
+
+body text";
+
+    #[test]
+    pub fn lossless_round_trip() {
+        let tree = Parser::parse_lossless(FILE);
+        assert_eq!(tree.to_string(), FILE);
+        // the leading comment is before the first separator
+        assert_eq!(tree.requests.len(), 3);
+        assert_eq!(tree.requests[0].lines[0].kind, SyntaxKind::Comment);
+
+        let kinds = tree.requests[1]
+            .lines
+            .iter()
+            .map(|line| line.kind)
+            .collect::<Vec<SyntaxKind>>();
+        assert_eq!(
+            kinds,
+            vec![
+                SyntaxKind::Separator,
+                SyntaxKind::Directive,
+                SyntaxKind::RequestLine,
+                SyntaxKind::RequestLine,
+                SyntaxKind::Header,
+                SyntaxKind::Header,
+                SyntaxKind::Blank,
+                SyntaxKind::Body,
+                SyntaxKind::Body,
+                SyntaxKind::Body,
+                SyntaxKind::Body,
+                SyntaxKind::Body,
+            ]
+        );
+
+        assert_eq!(Parser::parse_lossless("").to_string(), "");
+    }
+
+    #[test]
+    pub fn lossless_set_request_name() {
+        let mut tree = Parser::parse_lossless(FILE);
+        assert!(tree.set_request_name(0, "renamed"));
+        assert!(tree.set_request_name(1, "other"));
+        assert!(!tree.set_request_name(2, "missing"));
+
+        let expected = FILE
+            .replace("### First\n", "### renamed\n")
+            .replace("// @name = second", "// @name=other");
+        assert_eq!(tree.to_string(), expected);
+
+        let mut tree = Parser::parse_lossless("GET https://test.com\n");
+        assert!(tree.set_request_name(0, "name"));
+        assert_eq!(tree.to_string(), "# @name=name\nGET https://test.com\n");
+        let requests = Parser::parse(&tree.to_string(), false).requests;
+        assert_eq!(requests[0].name, Some("name".to_string()));
+
+        let mut tree = Parser::parse_lossless(
+            "### Fix #42
+GET https://test.com
+",
+        );
+        assert!(tree.set_request_name(0, "Fix #43"));
+        assert_eq!(
+            tree.to_string(),
+            "### Fix #43
+GET https://test.com
+"
+        );
+    }
+
+    #[test]
+    pub fn lossless_set_header_value() {
+        let mut tree = Parser::parse_lossless(FILE);
+        assert!(tree.set_header_value(0, "accept", "text/html"));
+        assert!(tree.set_header_value(1, "Authorization", "Bearer token"));
+
+        let expected = FILE.replace("Accept:  */*", "Accept:  text/html").replace(
+            "POST https://test.com/second\n",
+            "POST https://test.com/second\nAuthorization: Bearer token\n",
+        );
+        assert_eq!(tree.to_string(), expected);
+
+        let mut tree = Parser::parse_lossless("GET https://test.com");
+        assert!(tree.set_header_value(0, "Accept", "*/*"));
+        assert_eq!(tree.to_string(), "GET https://test.com\nAccept: */*\n");
+    }
+}